@@ -1,12 +1,57 @@
+/// containerd's own gRPC services, used by the native `ctr` tools
+const CONTAINERD_PROTOS: &[&str] = &[
+    "proto/containerd/containerd/api/services/containers/v1/containers.proto",
+    "proto/containerd/containerd/api/services/content/v1/content.proto",
+    "proto/containerd/containerd/api/services/images/v1/images.proto",
+    "proto/containerd/containerd/api/services/introspection/v1/introspection.proto",
+    "proto/containerd/containerd/api/services/leases/v1/leases.proto",
+    "proto/containerd/containerd/api/services/namespaces/v1/namespace.proto",
+    "proto/containerd/containerd/api/services/snapshots/v1/snapshots.proto",
+    "proto/containerd/containerd/api/services/tasks/v1/tasks.proto",
+    "proto/containerd/containerd/api/services/transfer/v1/transfer.proto",
+    "proto/containerd/containerd/api/types/transfer/imagestore.proto",
+    "proto/containerd/containerd/api/types/transfer/registry.proto",
+    "proto/containerd/containerd/api/services/version/v1/version.proto",
+    // payloads of task metrics and process info, packed in `Any` values by the runc shim
+    "proto/containerd/cgroups/v3/cgroup1/stats/metrics.proto",
+    "proto/containerd/cgroups/v3/cgroup2/stats/metrics.proto",
+    "proto/containerd/containerd/runtime/v2/runc/options/oci.proto",
+];
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut config = prost_build::Config::new();
     config.include_file("_includes.rs");
     config.enable_type_names();
     config.type_attribute(".", "#[derive(serde::Serialize, serde::Deserialize)]");
+    // JSON schemas for structured tool outputs, using the schemars version rmcp is built with
+    config.type_attribute(
+        ".",
+        "#[derive(rmcp::schemars::JsonSchema)] #[schemars(crate = \"rmcp::schemars\")]",
+    );
+    // configs given as JSON only need the fields that differ from the protobuf defaults
+    config.message_attribute(".", "#[serde(default)]");
 
     tonic_build::configure()
         .build_server(true)
         .build_client(true)
         .compile_with_config(config, &["proto/api.proto"], &["proto"])?;
+
+    let mut config = prost_build::Config::new();
+    config.include_file("_containerd.rs");
+    config.enable_type_names();
+    // the upstream protos import each other as github.com/containerd/..., which is proto/containerd here
+    config.protoc_arg("--proto_path=github.com/containerd=proto/containerd");
+    config.type_attribute(".", "#[derive(serde::Serialize, serde::Deserialize)]");
+    config.type_attribute(
+        ".",
+        "#[derive(rmcp::schemars::JsonSchema)] #[schemars(crate = \"rmcp::schemars\")]",
+    );
+
+    // well known types are generated too, so timestamps and any values get the same derives
+    tonic_build::configure()
+        .build_server(false)
+        .build_client(true)
+        .compile_well_known_types(true)
+        .compile_with_config(config, CONTAINERD_PROTOS, &["proto"])?;
     Ok(())
 }
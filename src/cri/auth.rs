@@ -0,0 +1,185 @@
+/**
+ * Registry credentials for authenticated image pulls
+ *
+ * Credentials are loaded once at startup from one or more files, later files
+ * override earlier ones for the same registry. Two formats are accepted:
+ *
+ * - a docker-style `config.json`, with an `auths` map of registry to
+ *   `{"auth": base64("user:password")}` or `{"identitytoken": ...}` entries
+ * - a plain credentials file keyed by registry host:
+ *   `{"registry.example.com": {"username": "...", "password": "..."}}`
+ *
+ * Secrets never reach the logs, use [`redact`] before printing an `AuthConfig`.
+ */
+use crate::api::runtime::v1::AuthConfig;
+use anyhow::{Context, Result};
+use base64::Engine;
+use serde_json::Value;
+use std::collections::HashMap;
+use tracing::debug;
+
+const DOCKER_HUB: &str = "docker.io";
+const REDACTED: &str = "<redacted>";
+
+/// Credentials for a single registry
+#[derive(Clone, Default, serde::Deserialize)]
+pub struct RegistryCredential {
+    #[serde(default)]
+    pub username: String,
+    #[serde(default)]
+    pub password: String,
+    #[serde(default, alias = "identitytoken")]
+    pub identity_token: String,
+    #[serde(default, alias = "registrytoken")]
+    pub registry_token: String,
+}
+
+impl std::fmt::Debug for RegistryCredential {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RegistryCredential")
+            .field("username", &self.username)
+            .field("password", &mask(&self.password))
+            .field("identity_token", &mask(&self.identity_token))
+            .field("registry_token", &mask(&self.registry_token))
+            .finish()
+    }
+}
+
+impl RegistryCredential {
+    pub fn is_empty(&self) -> bool {
+        self.username.is_empty()
+            && self.password.is_empty()
+            && self.identity_token.is_empty()
+            && self.registry_token.is_empty()
+    }
+
+    pub fn to_auth_config(&self, registry: &str) -> AuthConfig {
+        AuthConfig {
+            username: self.username.clone(),
+            password: self.password.clone(),
+            auth: String::new(),
+            server_address: registry.to_string(),
+            identity_token: self.identity_token.clone(),
+            registry_token: self.registry_token.clone(),
+        }
+    }
+}
+
+/// Registry credentials keyed by normalized registry host
+#[derive(Debug, Default, Clone)]
+pub struct CredentialStore {
+    credentials: HashMap<String, RegistryCredential>,
+}
+
+impl CredentialStore {
+    /// Load credentials from the given files, later files take precedence
+    pub fn load(paths: &[String]) -> Result<Self> {
+        let mut store = Self::default();
+        for path in paths {
+            let content = std::fs::read_to_string(path)
+                .with_context(|| format!("failed to read registry config {}", path))?;
+            let value: Value = serde_json::from_str(&content)
+                .with_context(|| format!("registry config {} is not valid JSON", path))?;
+            store
+                .merge(&value)
+                .with_context(|| format!("invalid registry config {}", path))?;
+            debug!("loaded registry config {}", path);
+        }
+        Ok(store)
+    }
+
+    fn merge(&mut self, value: &Value) -> Result<()> {
+        // docker config.json nests entries under "auths"
+        let entries = value
+            .get("auths")
+            .unwrap_or(value)
+            .as_object()
+            .context("expected an object of registry entries")?;
+
+        for (registry, entry) in entries {
+            let mut credential: RegistryCredential = serde_json::from_value(entry.clone())
+                .with_context(|| format!("invalid entry for registry {}", registry))?;
+            if let Some(auth) = entry.get("auth").and_then(Value::as_str) {
+                let (username, password) = decode_basic_auth(auth)
+                    .with_context(|| format!("invalid auth for registry {}", registry))?;
+                credential.username = username;
+                credential.password = password;
+            }
+            if !credential.is_empty() {
+                self.credentials
+                    .insert(normalize_registry(registry), credential);
+            }
+        }
+        Ok(())
+    }
+
+    /// Registries that have credentials configured
+    pub fn registries(&self) -> Vec<String> {
+        let mut registries: Vec<String> = self.credentials.keys().cloned().collect();
+        registries.sort();
+        registries
+    }
+
+    /// Find the credentials for the registry an image reference points to
+    pub fn lookup(&self, image_reference: &str) -> Option<AuthConfig> {
+        let registry = registry_host(image_reference);
+        self.credentials
+            .get(&registry)
+            .map(|credential| credential.to_auth_config(&registry))
+    }
+}
+
+/// Registry host of an image reference, following the docker reference rules
+pub fn registry_host(image_reference: &str) -> String {
+    match image_reference.split_once('/') {
+        Some((host, _)) if host.contains('.') || host.contains(':') || host == "localhost" => {
+            normalize_registry(host)
+        }
+        _ => DOCKER_HUB.to_string(),
+    }
+}
+
+/// Normalize registry keys such as "https://index.docker.io/v1/" to a bare host
+fn normalize_registry(registry: &str) -> String {
+    let host = registry
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .split('/')
+        .next()
+        .unwrap_or_default();
+    match host {
+        "index.docker.io" | "registry-1.docker.io" | "registry.hub.docker.com" => {
+            DOCKER_HUB.to_string()
+        }
+        _ => host.to_string(),
+    }
+}
+
+fn decode_basic_auth(auth: &str) -> Result<(String, String)> {
+    let decoded = base64::engine::general_purpose::STANDARD.decode(auth.trim())?;
+    let decoded = String::from_utf8(decoded)?;
+    let (username, password) = decoded
+        .split_once(':')
+        .context("expected base64 encoded 'username:password'")?;
+    Ok((username.to_string(), password.to_string()))
+}
+
+fn mask(secret: &str) -> &str {
+    if secret.is_empty() {
+        ""
+    } else {
+        REDACTED
+    }
+}
+
+/// Copy of an `AuthConfig` that is safe to log
+pub fn redact(auth: &AuthConfig) -> AuthConfig {
+    AuthConfig {
+        username: auth.username.clone(),
+        password: mask(&auth.password).to_string(),
+        auth: mask(&auth.auth).to_string(),
+        server_address: auth.server_address.clone(),
+        identity_token: mask(&auth.identity_token).to_string(),
+        registry_token: mask(&auth.registry_token).to_string(),
+    }
+}
@@ -0,0 +1,152 @@
+use crate::api::runtime::v1::CheckpointContainerRequest;
+use anyhow::Result;
+use std::path::{Component, Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tonic::transport::Channel;
+use tracing::debug;
+
+/// Directory used for checkpoint archives when none is configured
+pub const DEFAULT_CHECKPOINT_DIR: &str = "/var/lib/mcp-containerd/checkpoints";
+
+/// A checkpoint archive found on disk
+#[derive(Debug, serde::Serialize)]
+pub struct CheckpointArchive {
+    /// Path relative to the checkpoint directory, the location it was written with
+    pub name: String,
+    pub path: String,
+    pub size: u64,
+    /// Last modification time in seconds since the unix epoch
+    pub modified_at: Option<u64>,
+}
+
+impl CheckpointArchive {
+    fn new(checkpoint_dir: &Path, path: &Path, metadata: std::fs::Metadata) -> Self {
+        Self {
+            name: path
+                .strip_prefix(checkpoint_dir)
+                .unwrap_or(path)
+                .to_string_lossy()
+                .to_string(),
+            path: path.to_string_lossy().to_string(),
+            size: metadata.len(),
+            modified_at: metadata
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_secs()),
+        }
+    }
+}
+
+/// Resolve where a checkpoint archive should be written.
+///
+/// Locations are relative to `checkpoint_dir` and must stay inside it, since the runtime
+/// writes the archive as root. Without a location a timestamped `<container_id>-<secs>.tar`
+/// name is generated, so the container id must be a plain file name.
+pub fn checkpoint_location(
+    checkpoint_dir: &Path,
+    container_id: &str,
+    location: Option<String>,
+) -> Result<PathBuf, String> {
+    let Some(location) = location else {
+        let mut components = Path::new(container_id).components();
+        let plain = matches!(
+            (components.next(), components.next()),
+            (Some(Component::Normal(_)), None)
+        );
+        if !plain || container_id.contains('/') {
+            return Err(format!("invalid container id {}", container_id));
+        }
+        let now = std::time::SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        return Ok(checkpoint_dir.join(format!("{}-{}.tar", container_id, now)));
+    };
+    let mut relative = PathBuf::new();
+    for component in Path::new(&location).components() {
+        match component {
+            Component::Normal(part) => relative.push(part),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                return Err(format!(
+                    "checkpoint location {} must not contain '..'",
+                    location
+                ))
+            }
+            Component::RootDir | Component::Prefix(_) => {
+                return Err(format!(
+                    "checkpoint location {} must be relative to the checkpoint directory",
+                    location
+                ))
+            }
+        }
+    }
+    if relative.as_os_str().is_empty() {
+        return Err("checkpoint location must name a file".to_string());
+    }
+    Ok(checkpoint_dir.join(relative))
+}
+
+pub async fn checkpoint_container(
+    client: &mut crate::api::runtime::v1::RuntimeServiceClient<Channel>,
+    checkpoint_dir: &Path,
+    container_id: String,
+    location: PathBuf,
+    timeout: i64,
+) -> Result<CheckpointArchive, tonic::Status> {
+    if let Some(parent) = location.parent() {
+        tokio::fs::create_dir_all(parent).await.map_err(|e| {
+            tonic::Status::internal(format!(
+                "Failed to create checkpoint directory {}: {}",
+                parent.display(),
+                e
+            ))
+        })?;
+    }
+
+    let request = CheckpointContainerRequest {
+        container_id,
+        location: location.to_string_lossy().to_string(),
+        timeout,
+    };
+    debug!("checkpoint container request: {:?}", request);
+    client.checkpoint_container(request).await?;
+
+    match tokio::fs::metadata(&location).await {
+        Ok(metadata) => Ok(CheckpointArchive::new(checkpoint_dir, &location, metadata)),
+        Err(e) => Err(tonic::Status::internal(format!(
+            "Checkpoint finished but archive {} is not readable: {}",
+            location.display(),
+            e
+        ))),
+    }
+}
+
+/// List checkpoint archives in a directory and the subdirectories nested locations created,
+/// newest first. Symlinked directories are not followed
+pub fn list_checkpoints(checkpoint_dir: &Path) -> std::io::Result<Vec<CheckpointArchive>> {
+    if !checkpoint_dir.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut archives = Vec::new();
+    let mut dirs = vec![checkpoint_dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                dirs.push(entry.path());
+            } else if file_type.is_file() {
+                archives.push(CheckpointArchive::new(
+                    checkpoint_dir,
+                    &entry.path(),
+                    entry.metadata()?,
+                ));
+            }
+        }
+    }
+    archives.sort_by(|a, b| b.modified_at.cmp(&a.modified_at));
+    Ok(archives)
+}
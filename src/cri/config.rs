@@ -7,7 +7,8 @@
  *
  * Key features:
  * - Default configuration generation for both pods and containers
- * - Robust error handling for missing or invalid fields
+ * - Diagnostics for unknown keys and wrong-typed fields, each with its JSON path
+ * - A strict mode rejecting unknown keys, and a lenient mode reporting them as warnings
  * - Support for incremental configuration (only specify what you need)
  * - Type-safe conversion from unstructured JSON to CRI data structures
  */
@@ -16,39 +17,402 @@ use crate::api::runtime::v1::{
     LinuxContainerConfig, LinuxPodSandboxConfig, Mount, PodSandboxConfig, PodSandboxMetadata,
     PortMapping, WindowsContainerConfig, WindowsPodSandboxConfig,
 };
+use crate::cri::security::PolicyViolation;
+use rmcp::handler::server::tool::schema_for_type;
+use rmcp::schemars::{self, JsonSchema, Schema, SchemaGenerator};
 use serde_json::{Map, Value};
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::fmt;
+use std::marker::PhantomData;
 use tracing::debug;
 use uuid::Uuid;
 
-/// Helper trait for extracting and converting values from JSON maps
-trait ValueExtractor {
-    fn extract_string(&self, key: &str) -> Option<String>;
-    fn extract_u32(&self, key: &str) -> Option<u32>;
-    fn extract_bool(&self, key: &str) -> Option<bool>;
-    fn extract_map(&self, key: &str) -> Option<Map<String, Value>>;
-    fn extract_value(&self, key: &str) -> Option<&Value>;
+const POD_CONFIG_KEYS: &[&str] = &[
+    "metadata",
+    "hostname",
+    "log_directory",
+    "dns_config",
+    "port_mappings",
+    "labels",
+    "annotations",
+    "linux",
+    "windows",
+];
+const POD_METADATA_KEYS: &[&str] = &["name", "uid", "namespace", "attempt"];
+const CONTAINER_CONFIG_KEYS: &[&str] = &[
+    "metadata",
+    "image",
+    "command",
+    "args",
+    "working_dir",
+    "envs",
+    "mounts",
+    "devices",
+    "labels",
+    "annotations",
+    "log_path",
+    "stdin",
+    "stdin_once",
+    "tty",
+    "linux",
+    "windows",
+    "cdi_devices",
+];
+const CONTAINER_METADATA_KEYS: &[&str] = &["name", "attempt"];
+const IMAGE_SPEC_KEYS: &[&str] = &[
+    "image",
+    "annotations",
+    "runtime_handler",
+    "user_specified_image",
+];
+
+/// How the parsers treat keys they do not know
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Unknown keys are errors
+    Strict,
+    /// Unknown keys are ignored and reported as warnings
+    #[default]
+    Lenient,
 }
 
-impl ValueExtractor for Map<String, Value> {
-    fn extract_string(&self, key: &str) -> Option<String> {
-        self.get(key)?.as_str().map(|s| s.to_string())
+impl ParseMode {
+    pub fn from_strict(strict: bool) -> Self {
+        if strict {
+            ParseMode::Strict
+        } else {
+            ParseMode::Lenient
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    /// A key the configuration does not have, usually a typo
+    UnknownField,
+    /// A value of the wrong type, such as a string where a number belongs
+    WrongType,
+    /// Text that is not a JSON object
+    InvalidJson,
+}
+
+/// A problem found in a configuration, at the JSON path of the offending value
+#[derive(Debug, Clone, serde::Serialize, schemars::JsonSchema)]
+pub struct ConfigIssue {
+    /// JSON path of the value, such as $.linux.security_context.run_as_user
+    pub path: String,
+    pub kind: IssueKind,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// A configuration that could not be parsed
+#[derive(Debug, Clone, serde::Serialize, schemars::JsonSchema)]
+pub struct ConfigError {
+    pub errors: Vec<ConfigIssue>,
+    /// Unknown keys the lenient mode found next to the errors
+    pub warnings: Vec<ConfigIssue>,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let errors: Vec<String> = self.errors.iter().map(|e| e.to_string()).collect();
+        write!(f, "invalid configuration: {}", errors.join("; "))
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// A parsed configuration, with the warnings the lenient mode found in it
+#[derive(Debug, Clone)]
+pub struct Parsed<T> {
+    pub value: T,
+    pub warnings: Vec<ConfigIssue>,
+}
+
+/// Why creating a pod or container failed: its configuration, the security policy, or
+/// the runtime
+#[derive(Debug)]
+pub enum CreateError {
+    Config(ConfigError),
+    Policy(PolicyViolation),
+    Runtime(tonic::Status),
+}
+
+impl fmt::Display for CreateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CreateError::Config(e) => e.fmt(f),
+            CreateError::Policy(e) => e.fmt(f),
+            CreateError::Runtime(status) => status.fmt(f),
+        }
     }
+}
+
+impl std::error::Error for CreateError {}
 
-    fn extract_u32(&self, key: &str) -> Option<u32> {
-        self.get(key)?.as_u64().map(|n| n as u32)
+impl From<ConfigError> for CreateError {
+    fn from(e: ConfigError) -> Self {
+        CreateError::Config(e)
     }
+}
 
-    fn extract_bool(&self, key: &str) -> Option<bool> {
-        self.get(key)?.as_bool()
+impl From<PolicyViolation> for CreateError {
+    fn from(e: PolicyViolation) -> Self {
+        CreateError::Policy(e)
     }
+}
 
-    fn extract_map(&self, key: &str) -> Option<Map<String, Value>> {
-        serde_json::from_value(self.get(key)?.clone()).ok()
+impl From<tonic::Status> for CreateError {
+    fn from(status: tonic::Status) -> Self {
+        CreateError::Runtime(status)
     }
+}
+
+/// Collects the issues found while parsing one configuration
+struct Diagnostics {
+    mode: ParseMode,
+    errors: Vec<ConfigIssue>,
+    warnings: Vec<ConfigIssue>,
+}
 
-    fn extract_value(&self, key: &str) -> Option<&Value> {
-        self.get(key)
+impl Diagnostics {
+    fn new(mode: ParseMode) -> Self {
+        Self {
+            mode,
+            errors: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    fn unknown_field(&mut self, path: String) {
+        let issue = ConfigIssue {
+            message: "unknown field".to_string(),
+            path,
+            kind: IssueKind::UnknownField,
+        };
+        match self.mode {
+            ParseMode::Strict => self.errors.push(issue),
+            ParseMode::Lenient => self.warnings.push(issue),
+        }
+    }
+
+    fn error(&mut self, path: String, kind: IssueKind, message: String) {
+        self.errors.push(ConfigIssue {
+            path,
+            kind,
+            message,
+        });
+    }
+
+    /// Report the keys of `map` that are not in `known`
+    fn check_keys(&mut self, map: &Map<String, Value>, known: &[&str], path: &str) {
+        for key in map.keys().filter(|key| !known.contains(&key.as_str())) {
+            self.unknown_field(join(path, key));
+        }
+    }
+
+    /// Deserialize the value under `key`, reporting nested unknown keys and type errors
+    fn field<T>(&mut self, map: &Map<String, Value>, key: &str, path: &str) -> Option<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let value = map.get(key)?;
+        let path = join(path, key);
+        let mut unknown = Vec::new();
+        let mut track = |ignored: serde_ignored::Path| unknown.push(ignored_path(&ignored));
+        let deserializer = serde_ignored::Deserializer::new(value, &mut track);
+        let result: Result<T, _> = serde_path_to_error::deserialize(deserializer);
+        for suffix in unknown {
+            self.unknown_field(format!("{}{}", path, suffix));
+        }
+        match result {
+            Ok(value) => Some(value),
+            Err(e) => {
+                let inner = e.path().to_string();
+                let path = match inner.as_str() {
+                    "." => path,
+                    inner if inner.starts_with('[') => format!("{}{}", path, inner),
+                    inner => format!("{}.{}", path, inner),
+                };
+                self.error(path, IssueKind::WrongType, e.into_inner().to_string());
+                None
+            }
+        }
+    }
+
+    /// The object under `key`, or nothing when it is missing or not an object
+    fn object<'a>(
+        &mut self,
+        map: &'a Map<String, Value>,
+        key: &str,
+        path: &str,
+    ) -> Option<&'a Map<String, Value>> {
+        match map.get(key)? {
+            Value::Object(object) => Some(object),
+            other => {
+                self.error(
+                    join(path, key),
+                    IssueKind::WrongType,
+                    format!("expected an object, found {}", type_name(other)),
+                );
+                None
+            }
+        }
+    }
+
+    fn finish<T>(self, value: T) -> Result<Parsed<T>, ConfigError> {
+        if self.errors.is_empty() {
+            Ok(Parsed {
+                value,
+                warnings: self.warnings,
+            })
+        } else {
+            Err(ConfigError {
+                errors: self.errors,
+                warnings: self.warnings,
+            })
+        }
+    }
+}
+
+fn join(path: &str, key: &str) -> String {
+    format!("{}.{}", path, key)
+}
+
+/// Format a path below a field the way the rest of the diagnostics do, as .key and [index]
+fn ignored_path(path: &serde_ignored::Path) -> String {
+    match path {
+        serde_ignored::Path::Root => String::new(),
+        serde_ignored::Path::Seq { parent, index } => {
+            format!("{}[{}]", ignored_path(parent), index)
+        }
+        serde_ignored::Path::Map { parent, key } => format!("{}.{}", ignored_path(parent), key),
+        serde_ignored::Path::Some { parent }
+        | serde_ignored::Path::NewtypeStruct { parent }
+        | serde_ignored::Path::NewtypeVariant { parent } => ignored_path(parent),
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+/// URI of the resource holding the JSON schema of pod options
+pub const POD_CONFIG_SCHEMA_URI: &str = "schema://pod-config";
+
+/// URI of the resource holding the JSON schema of container options
+pub const CONTAINER_CONFIG_SCHEMA_URI: &str = "schema://container-config";
+
+/// The JSON schema published under a config schema URI, in the draft tool schemas use
+pub fn config_schema(uri: &str) -> Option<Map<String, Value>> {
+    match uri {
+        POD_CONFIG_SCHEMA_URI => Some(schema_for_type::<PodSandboxConfig>()),
+        CONTAINER_CONFIG_SCHEMA_URI => Some(schema_for_type::<ContainerConfig>()),
+        _ => None,
+    }
+}
+
+/// Options for a config of type `T`: a partial object of its fields, or the same object
+/// encoded as a JSON string
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(transparent)]
+pub struct ConfigOptions<T> {
+    value: Value,
+    #[serde(skip)]
+    config: PhantomData<T>,
+}
+
+impl<T> ConfigOptions<T> {
+    /// The options as a JSON object, parsing them first when they were given as a string
+    pub fn to_object(&self) -> Result<Map<String, Value>, ConfigError> {
+        match &self.value {
+            Value::Object(map) => Ok(map.clone()),
+            Value::String(options) => parse_options(options),
+            Value::Null => Ok(Map::new()),
+            other => Err(invalid_json(format!(
+                "expected a JSON object, found {}",
+                type_name(other)
+            ))),
+        }
+    }
+}
+
+impl<T> Default for ConfigOptions<T> {
+    fn default() -> Self {
+        Map::new().into()
+    }
+}
+
+impl<T> From<Map<String, Value>> for ConfigOptions<T> {
+    fn from(map: Map<String, Value>) -> Self {
+        Self {
+            value: Value::Object(map),
+            config: PhantomData,
+        }
+    }
+}
+
+impl<T: JsonSchema> JsonSchema for ConfigOptions<T> {
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn schema_name() -> Cow<'static, str> {
+        format!("{}Options", T::schema_name()).into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        let config = generator.subschema_for::<T>();
+        schemars::json_schema!({
+            "anyOf": [
+                config,
+                {
+                    "type": "string",
+                    "description": "The same object encoded as a JSON string"
+                }
+            ]
+        })
+    }
+}
+
+fn invalid_json(message: String) -> ConfigError {
+    ConfigError {
+        errors: vec![ConfigIssue {
+            path: "$".to_string(),
+            kind: IssueKind::InvalidJson,
+            message,
+        }],
+        warnings: Vec::new(),
+    }
+}
+
+/// Parse options given as JSON text, an empty string being an empty object
+fn parse_options(options: &str) -> Result<Map<String, Value>, ConfigError> {
+    if options.trim().is_empty() {
+        return Ok(Map::new());
+    }
+    match serde_json::from_str::<Value>(options) {
+        Ok(Value::Object(map)) => Ok(map),
+        Ok(other) => Err(invalid_json(format!(
+            "expected a JSON object, found {}",
+            type_name(&other)
+        ))),
+        Err(e) => Err(invalid_json(e.to_string())),
     }
 }
 
@@ -70,15 +434,6 @@ macro_rules! extend_if_some {
     };
 }
 
-/// Helper function to parse typed values from JSON
-fn parse_typed_field<T>(map: &Map<String, Value>, key: &str) -> Option<T>
-where
-    T: serde::de::DeserializeOwned,
-{
-    map.extract_value(key)
-        .and_then(|v| serde_json::from_value(v.clone()).ok())
-}
-
 /// Creates default pod sandbox metadata
 fn default_pod_metadata() -> PodSandboxMetadata {
     PodSandboxMetadata {
@@ -105,14 +460,25 @@ fn default_pod_config() -> PodSandboxConfig {
 }
 
 /// Parse pod metadata from JSON map
-fn parse_pod_metadata(metadata_map: &Map<String, Value>) -> PodSandboxMetadata {
+fn parse_pod_metadata(
+    diagnostics: &mut Diagnostics,
+    metadata_map: &Map<String, Value>,
+    path: &str,
+) -> PodSandboxMetadata {
     let mut metadata = default_pod_metadata();
-    
-    update_if_some!(metadata.name, metadata_map.extract_string("name"));
-    update_if_some!(metadata.uid, metadata_map.extract_string("uid"));
-    update_if_some!(metadata.namespace, metadata_map.extract_string("namespace"));
-    update_if_some!(metadata.attempt, metadata_map.extract_u32("attempt"));
-    
+    diagnostics.check_keys(metadata_map, POD_METADATA_KEYS, path);
+
+    update_if_some!(metadata.name, diagnostics.field(metadata_map, "name", path));
+    update_if_some!(metadata.uid, diagnostics.field(metadata_map, "uid", path));
+    update_if_some!(
+        metadata.namespace,
+        diagnostics.field(metadata_map, "namespace", path)
+    );
+    update_if_some!(
+        metadata.attempt,
+        diagnostics.field(metadata_map, "attempt", path)
+    );
+
     metadata
 }
 
@@ -131,13 +497,19 @@ fn parse_pod_metadata(metadata_map: &Map<String, Value>) -> PodSandboxMetadata {
  * - "default-hostname" hostname
  * - "/var/log/pods" log directory
  *
+ * Fields of the wrong type are always errors. Unknown keys, at any depth, are errors in
+ * strict mode and warnings in lenient mode.
+ *
  * # Arguments
  *
  * * `config` - JSON value containing pod configuration, which can be partial or complete
+ * * `mode` - Whether unknown keys are rejected or reported as warnings
  *
  * # Returns
  *
- * * `PodSandboxConfig` - The merged pod configuration with all required fields populated
+ * * `Parsed<PodSandboxConfig>` - The merged pod configuration with all required fields
+ *   populated, and the warnings found in it
+ * * `ConfigError` - Every issue that made the configuration invalid, with its JSON path
  *
  * # Example
  *
@@ -147,96 +519,93 @@ fn parse_pod_metadata(metadata_map: &Map<String, Value>) -> PodSandboxMetadata {
  *         "name": "my-pod"
  *     }
  * });
- * let pod_config = parse_pod_config(json);
+ * let pod_config = parse_pod_config(json, ParseMode::Strict)?.value;
  * // Returns a complete PodSandboxConfig with "my-pod" as name and defaults for other fields
  * ```
  */
-pub fn parse_pod_config(config: Value) -> PodSandboxConfig {
+pub fn parse_pod_config(
+    config: Value,
+    mode: ParseMode,
+) -> Result<Parsed<PodSandboxConfig>, ConfigError> {
     debug!("Parsing pod configuration: {:?}", config);
 
     let mut pod_config = default_pod_config();
+    let mut diagnostics = Diagnostics::new(mode);
 
-    // Try to parse as JSON map first
-    if let Ok(user_config) = serde_json::from_value::<Map<String, Value>>(config.clone()) {
-        parse_pod_config_from_map(&mut pod_config, &user_config);
-    } else if let Ok(direct_config) = serde_json::from_value::<PodSandboxConfig>(config) {
-        // Fallback to direct parsing
-        merge_pod_config(&mut pod_config, direct_config);
+    match &config {
+        Value::Object(user_config) => {
+            parse_pod_config_from_map(&mut diagnostics, &mut pod_config, user_config)
+        }
+        other => diagnostics.error(
+            "$".to_string(),
+            IssueKind::WrongType,
+            format!("expected an object, found {}", type_name(other)),
+        ),
     }
 
-    pod_config
+    diagnostics.finish(pod_config)
 }
 
 /// Parse pod configuration from a JSON map
-fn parse_pod_config_from_map(pod_config: &mut PodSandboxConfig, user_config: &Map<String, Value>) {
+fn parse_pod_config_from_map(
+    diagnostics: &mut Diagnostics,
+    pod_config: &mut PodSandboxConfig,
+    user_config: &Map<String, Value>,
+) {
+    let path = "$";
+    diagnostics.check_keys(user_config, POD_CONFIG_KEYS, path);
+
     // Handle metadata
-    if let Some(metadata_map) = user_config.extract_map("metadata") {
-        pod_config.metadata = Some(parse_pod_metadata(&metadata_map));
+    if let Some(metadata_map) = diagnostics.object(user_config, "metadata", path) {
+        pod_config.metadata = Some(parse_pod_metadata(
+            diagnostics,
+            metadata_map,
+            &join(path, "metadata"),
+        ));
     }
 
     // Handle basic string fields
-    update_if_some!(pod_config.hostname, user_config.extract_string("hostname"));
-    update_if_some!(pod_config.log_directory, user_config.extract_string("log_directory"));
+    update_if_some!(
+        pod_config.hostname,
+        diagnostics.field(user_config, "hostname", path)
+    );
+    update_if_some!(
+        pod_config.log_directory,
+        diagnostics.field(user_config, "log_directory", path)
+    );
 
     // Handle complex typed fields
-    if let Some(dns_config) = parse_typed_field::<DnsConfig>(user_config, "dns_config") {
+    if let Some(dns_config) = diagnostics.field::<DnsConfig>(user_config, "dns_config", path) {
         pod_config.dns_config = Some(dns_config);
     }
 
-    if let Some(port_mappings) = parse_typed_field::<Vec<PortMapping>>(user_config, "port_mappings") {
+    if let Some(port_mappings) =
+        diagnostics.field::<Vec<PortMapping>>(user_config, "port_mappings", path)
+    {
         pod_config.port_mappings = port_mappings;
     }
 
-    if let Some(linux_config) = parse_typed_field::<LinuxPodSandboxConfig>(user_config, "linux") {
+    if let Some(linux_config) =
+        diagnostics.field::<LinuxPodSandboxConfig>(user_config, "linux", path)
+    {
         pod_config.linux = Some(linux_config);
     }
 
-    if let Some(windows_config) = parse_typed_field::<WindowsPodSandboxConfig>(user_config, "windows") {
+    if let Some(windows_config) =
+        diagnostics.field::<WindowsPodSandboxConfig>(user_config, "windows", path)
+    {
         pod_config.windows = Some(windows_config);
     }
 
     // Handle labels and annotations
-    extend_if_some!(pod_config.labels, parse_typed_field::<HashMap<String, String>>(user_config, "labels"));
-    extend_if_some!(pod_config.annotations, parse_typed_field::<HashMap<String, String>>(user_config, "annotations"));
-}
-
-/// Merge a direct PodSandboxConfig into the default configuration
-fn merge_pod_config(target: &mut PodSandboxConfig, source: PodSandboxConfig) {
-    if source.metadata.is_some() {
-        target.metadata = source.metadata;
-    }
-
-    if !source.hostname.is_empty() {
-        target.hostname = source.hostname;
-    }
-
-    if !source.log_directory.is_empty() {
-        target.log_directory = source.log_directory;
-    }
-
-    if source.dns_config.is_some() {
-        target.dns_config = source.dns_config;
-    }
-
-    if !source.port_mappings.is_empty() {
-        target.port_mappings = source.port_mappings;
-    }
-
-    if !source.labels.is_empty() {
-        target.labels = source.labels;
-    }
-
-    if !source.annotations.is_empty() {
-        target.annotations = source.annotations;
-    }
-
-    if source.linux.is_some() {
-        target.linux = source.linux;
-    }
-
-    if source.windows.is_some() {
-        target.windows = source.windows;
-    }
+    extend_if_some!(
+        pod_config.labels,
+        diagnostics.field::<HashMap<String, String>>(user_config, "labels", path)
+    );
+    extend_if_some!(
+        pod_config.annotations,
+        diagnostics.field::<HashMap<String, String>>(user_config, "annotations", path)
+    );
 }
 
 /// Creates default container metadata
@@ -281,27 +650,51 @@ fn default_container_config() -> ContainerConfig {
 }
 
 /// Parse container metadata from JSON map
-fn parse_container_metadata(metadata_map: &Map<String, Value>) -> ContainerMetadata {
+fn parse_container_metadata(
+    diagnostics: &mut Diagnostics,
+    metadata_map: &Map<String, Value>,
+    path: &str,
+) -> ContainerMetadata {
     let mut metadata = default_container_metadata();
-    
-    update_if_some!(metadata.name, metadata_map.extract_string("name"));
-    update_if_some!(metadata.attempt, metadata_map.extract_u32("attempt"));
-    
+    diagnostics.check_keys(metadata_map, CONTAINER_METADATA_KEYS, path);
+
+    update_if_some!(metadata.name, diagnostics.field(metadata_map, "name", path));
+    update_if_some!(
+        metadata.attempt,
+        diagnostics.field(metadata_map, "attempt", path)
+    );
+
     metadata
 }
 
 /// Parse image specification from JSON map
-fn parse_image_spec(image_map: &Map<String, Value>) -> ImageSpec {
+fn parse_image_spec(
+    diagnostics: &mut Diagnostics,
+    image_map: &Map<String, Value>,
+    path: &str,
+) -> ImageSpec {
     let mut image_spec = default_image_spec();
-    
-    update_if_some!(image_spec.image, image_map.extract_string("image"));
-    update_if_some!(image_spec.runtime_handler, image_map.extract_string("runtime_handler"));
-    update_if_some!(image_spec.user_specified_image, image_map.extract_string("user_specified_image"));
-    
-    if let Some(annotations) = parse_typed_field::<HashMap<String, String>>(image_map, "annotations") {
+    diagnostics.check_keys(image_map, IMAGE_SPEC_KEYS, path);
+
+    update_if_some!(
+        image_spec.image,
+        diagnostics.field(image_map, "image", path)
+    );
+    update_if_some!(
+        image_spec.runtime_handler,
+        diagnostics.field(image_map, "runtime_handler", path)
+    );
+    update_if_some!(
+        image_spec.user_specified_image,
+        diagnostics.field(image_map, "user_specified_image", path)
+    );
+
+    if let Some(annotations) =
+        diagnostics.field::<HashMap<String, String>>(image_map, "annotations", path)
+    {
         image_spec.annotations = annotations;
     }
-    
+
     image_spec
 }
 
@@ -319,13 +712,19 @@ fn parse_image_spec(image_map: &Map<String, Value>) -> ImageSpec {
  * - "/" as the default working directory
  * - Standard configuration for stdin, stdout, and tty
  *
+ * Fields of the wrong type are always errors. Unknown keys, at any depth, are errors in
+ * strict mode and warnings in lenient mode.
+ *
  * # Arguments
  *
  * * `config` - JSON value containing container configuration, which can be partial or complete
+ * * `mode` - Whether unknown keys are rejected or reported as warnings
  *
  * # Returns
  *
- * * `ContainerConfig` - The merged container configuration with all required fields populated
+ * * `Parsed<ContainerConfig>` - The merged container configuration with all required fields
+ *   populated, and the warnings found in it
+ * * `ConfigError` - Every issue that made the configuration invalid, with its JSON path
  *
  * # Example
  *
@@ -338,151 +737,228 @@ fn parse_image_spec(image_map: &Map<String, Value>) -> ImageSpec {
  *         "image": "busybox:latest"
  *     }
  * });
- * let container_config = parse_container_config(json);
+ * let container_config = parse_container_config(json, ParseMode::Lenient)?.value;
  * // Returns a complete ContainerConfig with "my-container" as name,
  * // "busybox:latest" as image, and defaults for other fields
  * ```
  */
-pub fn parse_container_config(config: Value) -> ContainerConfig {
+pub fn parse_container_config(
+    config: Value,
+    mode: ParseMode,
+) -> Result<Parsed<ContainerConfig>, ConfigError> {
     debug!("Parsing container configuration: {:?}", config);
 
     let mut container_config = default_container_config();
+    let mut diagnostics = Diagnostics::new(mode);
 
-    // Try to parse as JSON map first
-    if let Ok(user_config) = serde_json::from_value::<Map<String, Value>>(config.clone()) {
-        parse_container_config_from_map(&mut container_config, &user_config);
-    } else if let Ok(direct_config) = serde_json::from_value::<ContainerConfig>(config) {
-        // Fallback to direct parsing
-        merge_container_config(&mut container_config, direct_config);
+    match &config {
+        Value::Object(user_config) => {
+            parse_container_config_from_map(&mut diagnostics, &mut container_config, user_config)
+        }
+        other => diagnostics.error(
+            "$".to_string(),
+            IssueKind::WrongType,
+            format!("expected an object, found {}", type_name(other)),
+        ),
     }
 
-    container_config
+    diagnostics.finish(container_config)
 }
 
 /// Parse container configuration from a JSON map
-fn parse_container_config_from_map(container_config: &mut ContainerConfig, user_config: &Map<String, Value>) {
-    // Handle metadata
-    if let Some(metadata_map) = user_config.extract_map("metadata") {
-        container_config.metadata = Some(parse_container_metadata(&metadata_map));
-    }
+fn parse_container_config_from_map(
+    diagnostics: &mut Diagnostics,
+    container_config: &mut ContainerConfig,
+    user_config: &Map<String, Value>,
+) {
+    let path = "$";
+    diagnostics.check_keys(user_config, CONTAINER_CONFIG_KEYS, path);
 
-    // Handle image configuration
-    if let Some(image_map) = user_config.extract_map("image") {
-        container_config.image = Some(parse_image_spec(&image_map));
-    } else if let Some(image_ref) = user_config.extract_string("image") {
-        // Handle simple image reference as string
-        if let Some(ref mut image_spec) = container_config.image {
-            image_spec.image = image_ref;
+    // Handle metadata
+    if let Some(metadata_map) = diagnostics.object(user_config, "metadata", path) {
+        container_config.metadata = Some(parse_container_metadata(
+            diagnostics,
+            metadata_map,
+            &join(path, "metadata"),
+        ));
+    }
+
+    // Handle image configuration, either a spec or a simple image reference
+    match user_config.get("image") {
+        Some(Value::Object(image_map)) => {
+            container_config.image = Some(parse_image_spec(
+                diagnostics,
+                image_map,
+                &join(path, "image"),
+            ));
         }
+        Some(_) => {
+            if let Some(image_ref) = diagnostics.field::<String>(user_config, "image", path) {
+                if let Some(ref mut image_spec) = container_config.image {
+                    image_spec.image = image_ref;
+                }
+            }
+        }
+        None => {}
     }
 
     // Handle basic string fields
-    update_if_some!(container_config.working_dir, user_config.extract_string("working_dir"));
-    update_if_some!(container_config.log_path, user_config.extract_string("log_path"));
+    update_if_some!(
+        container_config.working_dir,
+        diagnostics.field(user_config, "working_dir", path)
+    );
+    update_if_some!(
+        container_config.log_path,
+        diagnostics.field(user_config, "log_path", path)
+    );
 
     // Handle boolean fields
-    update_if_some!(container_config.stdin, user_config.extract_bool("stdin"));
-    update_if_some!(container_config.stdin_once, user_config.extract_bool("stdin_once"));
-    update_if_some!(container_config.tty, user_config.extract_bool("tty"));
+    update_if_some!(
+        container_config.stdin,
+        diagnostics.field(user_config, "stdin", path)
+    );
+    update_if_some!(
+        container_config.stdin_once,
+        diagnostics.field(user_config, "stdin_once", path)
+    );
+    update_if_some!(
+        container_config.tty,
+        diagnostics.field(user_config, "tty", path)
+    );
 
     // Handle vector fields
-    if let Some(command) = parse_typed_field::<Vec<String>>(user_config, "command") {
+    if let Some(command) = diagnostics.field::<Vec<String>>(user_config, "command", path) {
         container_config.command = command;
     }
 
-    if let Some(args) = parse_typed_field::<Vec<String>>(user_config, "args") {
+    if let Some(args) = diagnostics.field::<Vec<String>>(user_config, "args", path) {
         container_config.args = args;
     }
 
-    if let Some(envs) = parse_typed_field::<Vec<KeyValue>>(user_config, "envs") {
+    if let Some(envs) = diagnostics.field::<Vec<KeyValue>>(user_config, "envs", path) {
         container_config.envs = envs;
     }
 
-    if let Some(mounts) = parse_typed_field::<Vec<Mount>>(user_config, "mounts") {
+    if let Some(mounts) = diagnostics.field::<Vec<Mount>>(user_config, "mounts", path) {
         container_config.mounts = mounts;
     }
 
-    if let Some(devices) = parse_typed_field::<Vec<Device>>(user_config, "devices") {
+    if let Some(devices) = diagnostics.field::<Vec<Device>>(user_config, "devices", path) {
         container_config.devices = devices;
     }
 
-    if let Some(cdi_devices) = parse_typed_field::<Vec<CdiDevice>>(user_config, "cdi_devices") {
+    if let Some(cdi_devices) = diagnostics.field::<Vec<CdiDevice>>(user_config, "cdi_devices", path)
+    {
         container_config.cdi_devices = cdi_devices;
     }
 
     // Handle complex typed fields
-    if let Some(linux_config) = parse_typed_field::<LinuxContainerConfig>(user_config, "linux") {
+    if let Some(linux_config) =
+        diagnostics.field::<LinuxContainerConfig>(user_config, "linux", path)
+    {
         container_config.linux = Some(linux_config);
     }
 
-    if let Some(windows_config) = parse_typed_field::<WindowsContainerConfig>(user_config, "windows") {
+    if let Some(windows_config) =
+        diagnostics.field::<WindowsContainerConfig>(user_config, "windows", path)
+    {
         container_config.windows = Some(windows_config);
     }
 
     // Handle labels and annotations
-    extend_if_some!(container_config.labels, parse_typed_field::<HashMap<String, String>>(user_config, "labels"));
-    extend_if_some!(container_config.annotations, parse_typed_field::<HashMap<String, String>>(user_config, "annotations"));
+    extend_if_some!(
+        container_config.labels,
+        diagnostics.field::<HashMap<String, String>>(user_config, "labels", path)
+    );
+    extend_if_some!(
+        container_config.annotations,
+        diagnostics.field::<HashMap<String, String>>(user_config, "annotations", path)
+    );
 }
 
-/// Merge a direct ContainerConfig into the default configuration
-fn merge_container_config(target: &mut ContainerConfig, source: ContainerConfig) {
-    if source.metadata.is_some() {
-        target.metadata = source.metadata;
-    }
-
-    if source.image.is_some() {
-        target.image = source.image;
-    }
-
-    if !source.command.is_empty() {
-        target.command = source.command;
-    }
-
-    if !source.args.is_empty() {
-        target.args = source.args;
-    }
-
-    if !source.working_dir.is_empty() {
-        target.working_dir = source.working_dir;
-    }
-
-    if !source.envs.is_empty() {
-        target.envs = source.envs;
-    }
-
-    if !source.mounts.is_empty() {
-        target.mounts = source.mounts;
-    }
-
-    if !source.devices.is_empty() {
-        target.devices = source.devices;
-    }
-
-    if !source.labels.is_empty() {
-        target.labels = source.labels;
-    }
-
-    if !source.annotations.is_empty() {
-        target.annotations = source.annotations;
-    }
-
-    if !source.log_path.is_empty() {
-        target.log_path = source.log_path;
-    }
-
-    target.stdin = source.stdin;
-    target.stdin_once = source.stdin_once;
-    target.tty = source.tty;
-
-    if source.linux.is_some() {
-        target.linux = source.linux;
-    }
-
-    if source.windows.is_some() {
-        target.windows = source.windows;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn paths(issues: &[ConfigIssue]) -> Vec<&str> {
+        issues.iter().map(|issue| issue.path.as_str()).collect()
+    }
+
+    #[test]
+    fn reports_nested_unknown_keys_with_their_path() {
+        let parsed = parse_container_config(
+            json!({
+                "metadata": { "name": "web", "atempt": 1 },
+                "mounts": [{ "container_path": "/data", "host_pth": "/srv" }],
+                "linux": { "security_context": { "run_as_usr": { "value": 1000 } } },
+            }),
+            ParseMode::Lenient,
+        )
+        .unwrap();
+        assert_eq!(
+            paths(&parsed.warnings),
+            [
+                "$.metadata.atempt",
+                "$.mounts[0].host_pth",
+                "$.linux.security_context.run_as_usr",
+            ]
+        );
+        assert!(parsed
+            .warnings
+            .iter()
+            .all(|issue| issue.kind == IssueKind::UnknownField));
+        assert_eq!(parsed.value.metadata.unwrap().name, "web");
+        assert_eq!(parsed.value.mounts[0].container_path, "/data");
+    }
+
+    #[test]
+    fn wrong_types_are_errors_in_both_modes() {
+        for mode in [ParseMode::Strict, ParseMode::Lenient] {
+            let error = parse_container_config(
+                json!({
+                    "tty": "yes",
+                    "linux": { "security_context": { "run_as_user": { "value": "root" } } },
+                }),
+                mode,
+            )
+            .unwrap_err();
+            assert_eq!(
+                paths(&error.errors),
+                ["$.tty", "$.linux.security_context.run_as_user.value"],
+                "{:?}",
+                mode
+            );
+            assert!(error
+                .errors
+                .iter()
+                .all(|issue| issue.kind == IssueKind::WrongType));
+
+            let error = parse_pod_config(json!({ "metadata": "web" }), mode).unwrap_err();
+            assert_eq!(paths(&error.errors), ["$.metadata"], "{:?}", mode);
+        }
     }
 
-    if !source.cdi_devices.is_empty() {
-        target.cdi_devices = source.cdi_devices;
+    #[test]
+    fn strict_mode_rejects_unknown_keys() {
+        let config = json!({
+            "metadata": { "name": "web" },
+            "hostnme": "web",
+            "dns_config": { "servers": ["1.1.1.1"], "search": ["local"] },
+        });
+
+        let parsed = parse_pod_config(config.clone(), ParseMode::Lenient).unwrap();
+        assert_eq!(
+            paths(&parsed.warnings),
+            ["$.hostnme", "$.dns_config.search"]
+        );
+
+        let error = parse_pod_config(config, ParseMode::Strict).unwrap_err();
+        assert_eq!(paths(&error.errors), ["$.hostnme", "$.dns_config.search"]);
+        assert!(error
+            .errors
+            .iter()
+            .all(|issue| issue.kind == IssueKind::UnknownField));
+        assert!(error.warnings.is_empty());
     }
 }
@@ -1,24 +1,67 @@
 use crate::api::runtime::v1::{
-    ContainerStatsRequest, CreateContainerRequest, RemoveContainerRequest,
+    ContainerConfig, ContainerFilter, ContainerState, ContainerStatsFilter, ContainerStatsRequest,
+    ContainerStatusRequest, ContainerStatusResponse, CreateContainerRequest, HugepageLimit,
+    LinuxContainerResources, ListContainerStatsRequest, ListContainerStatsResponse,
+    ListContainersRequest, ListContainersResponse, PodSandboxConfig, RemoveContainerRequest,
+    UpdateContainerResourcesRequest,
 };
-use crate::cri::config::parse_container_config;
+use crate::cri::config::{parse_container_config, ConfigOptions, CreateError, ParseMode, Parsed};
+use crate::cri::security::{ExplicitFields, Security};
 use anyhow::Result;
+use std::collections::HashMap;
 use tonic::transport::Channel;
 use tracing::debug;
 
+#[allow(clippy::too_many_arguments)]
 pub async fn create_container(
     client: &mut crate::api::runtime::v1::RuntimeServiceClient<Channel>,
     pod_id: String,
     name: String,
     image: String,
-    options: String,
-    pod_config: String,
-) -> Result<String, tonic::Status> {
+    options: &ConfigOptions<ContainerConfig>,
+    sandbox_config: PodSandboxConfig,
+    mode: ParseMode,
+    security: &Security,
+) -> Result<Parsed<String>, CreateError> {
     debug!(
         "Create container request - pod_id: {}, name: {}, image: {}, options: {:?}",
         pod_id, name, image, options
     );
+    let pod_uid = sandbox_config
+        .metadata
+        .as_ref()
+        .map(|metadata| metadata.uid.clone())
+        .unwrap_or_default();
+    let Parsed {
+        value: container_config,
+        warnings,
+    } = container_config(name, image, options, &pod_uid, mode, security).await?;
 
+    let request = CreateContainerRequest {
+        pod_sandbox_id: pod_id,
+        config: Some(container_config),
+        sandbox_config: Some(sandbox_config),
+    };
+
+    debug!("create container request: {:?}", request);
+
+    let response = client.create_container(request).await?;
+    Ok(Parsed {
+        value: response.into_inner().container_id,
+        warnings,
+    })
+}
+
+/// Build the config of a container of the pod with `pod_uid` from its options, with the
+/// security preset applied and checked against the minimum level
+pub async fn container_config(
+    name: String,
+    image: String,
+    options: &ConfigOptions<ContainerConfig>,
+    pod_uid: &str,
+    mode: ParseMode,
+    security: &Security,
+) -> Result<Parsed<ContainerConfig>, CreateError> {
     // Create a base config with required fields
     let mut container_config_value = serde_json::json!({
         "metadata": {
@@ -31,30 +74,24 @@ pub async fn create_container(
     });
 
     // Merge the options
-    let container_obj = container_config_value.as_object_mut().unwrap();
-    let options_value = serde_json::from_str::<serde_json::Value>(&options).unwrap();
-    for (key, value) in options_value.as_object().unwrap() {
-        container_obj.insert(key.clone(), value.clone());
+    let options = options.to_object()?;
+    let explicit = ExplicitFields::of(&options);
+    if let Some(container_obj) = container_config_value.as_object_mut() {
+        container_obj.extend(options);
     }
 
     // Parse container configuration with defaults
-    let container_config = parse_container_config(container_config_value);
-
-    // Convert HashMap to JSON Value directly
-    let pod_config_value = serde_json::from_str::<serde_json::Value>(&pod_config).unwrap();
-    // Parse pod configuration for sandbox_config
-    let sandbox_config = crate::cri::config::parse_pod_config(pod_config_value);
-
-    let request = CreateContainerRequest {
-        pod_sandbox_id: pod_id,
-        config: Some(container_config),
-        sandbox_config: Some(sandbox_config),
-    };
-
-    debug!("create container request: {:?}", request);
-
-    let response = client.create_container(request).await?;
-    Ok(response.into_inner().container_id)
+    let Parsed {
+        value: mut container_config,
+        warnings,
+    } = parse_container_config(container_config_value, mode)?;
+    security
+        .apply_to_container(&mut container_config, explicit, pod_uid)
+        .await?;
+    Ok(Parsed {
+        value: container_config,
+        warnings,
+    })
 }
 
 pub async fn remove_container(
@@ -97,6 +134,57 @@ pub async fn container_stats(
     Ok(response.into_inner())
 }
 
+pub async fn list_container_stats(
+    client: &mut crate::api::runtime::v1::RuntimeServiceClient<Channel>,
+    filter: Option<ContainerStatsFilter>,
+) -> Result<ListContainerStatsResponse, tonic::Status> {
+    let request = ListContainerStatsRequest { filter };
+    debug!("list container stats request: {:?}", request);
+    let response = client.list_container_stats(request).await?;
+    Ok(response.into_inner())
+}
+
+pub async fn list_containers(
+    client: &mut crate::api::runtime::v1::RuntimeServiceClient<Channel>,
+    filter: Option<ContainerFilter>,
+) -> Result<ListContainersResponse, tonic::Status> {
+    let request = ListContainersRequest { filter };
+    debug!("list containers request: {:?}", request);
+    let response = client.list_containers(request).await?;
+    Ok(response.into_inner())
+}
+
+pub async fn container_status(
+    client: &mut crate::api::runtime::v1::RuntimeServiceClient<Channel>,
+    container_id: String,
+    verbose: bool,
+) -> Result<ContainerStatusResponse, tonic::Status> {
+    let request = ContainerStatusRequest {
+        container_id,
+        verbose,
+    };
+    let response = client.container_status(request).await?;
+    Ok(response.into_inner())
+}
+
+/// Parse a container state such as "running" or "CONTAINER_RUNNING"
+pub fn parse_container_state(state: &str) -> Option<ContainerState> {
+    let name = state.trim().to_uppercase();
+    let name = if name.starts_with("CONTAINER_") {
+        name
+    } else {
+        format!("CONTAINER_{}", name)
+    };
+    ContainerState::from_str_name(&name)
+}
+
+/// Human readable name of a raw container state value
+pub fn container_state_name(state: i32) -> String {
+    ContainerState::try_from(state)
+        .map(|s| s.as_str_name().to_string())
+        .unwrap_or_else(|_| state.to_string())
+}
+
 pub async fn container_logs(
     client: &mut crate::api::runtime::v1::RuntimeServiceClient<Channel>,
     container_id: String,
@@ -133,15 +221,254 @@ pub async fn container_logs(
     }
 }
 
+/// Requested changes to the Linux resources of a running container, unset fields are kept
+#[derive(Debug, Default)]
+pub struct ContainerResourceUpdate {
+    pub cpu_shares: Option<i64>,
+    pub cpu_quota: Option<i64>,
+    pub cpu_period: Option<i64>,
+    pub cpuset_cpus: Option<String>,
+    pub cpuset_mems: Option<String>,
+    pub memory_limit_in_bytes: Option<i64>,
+    pub memory_swap_limit_in_bytes: Option<i64>,
+    /// Hugepage limits keyed by page size, e.g. "2MB"
+    pub hugepage_limits: Option<HashMap<String, u64>>,
+    /// Raw cgroup v2 keys, e.g. "memory.high"
+    pub unified: Option<HashMap<String, String>>,
+}
+
+/// The smallest memory limit accepted, below this containers fail to start or get OOM killed at once
+const MIN_MEMORY_LIMIT: i64 = 6 * 1024 * 1024;
+
+impl ContainerResourceUpdate {
+    pub fn is_empty(&self) -> bool {
+        self.cpu_shares.is_none()
+            && self.cpu_quota.is_none()
+            && self.cpu_period.is_none()
+            && self.cpuset_cpus.is_none()
+            && self.cpuset_mems.is_none()
+            && self.memory_limit_in_bytes.is_none()
+            && self.memory_swap_limit_in_bytes.is_none()
+            && self.hugepage_limits.is_none()
+            && self.unified.is_none()
+    }
+
+    /// Check every requested value against the limits enforced by the kernel and runc
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+
+        if let Some(shares) = self.cpu_shares {
+            if !(2..=262144).contains(&shares) {
+                errors.push(format!(
+                    "cpu_shares must be between 2 and 262144, got {}",
+                    shares
+                ));
+            }
+        }
+        if let Some(quota) = self.cpu_quota {
+            if quota != -1 && quota < 1000 {
+                errors.push(format!(
+                    "cpu_quota must be -1 (unlimited) or at least 1000 microseconds, got {}",
+                    quota
+                ));
+            }
+        }
+        if let Some(period) = self.cpu_period {
+            if !(1000..=1_000_000).contains(&period) {
+                errors.push(format!(
+                    "cpu_period must be between 1000 and 1000000 microseconds, got {}",
+                    period
+                ));
+            }
+        }
+        for (name, value) in [
+            ("cpuset_cpus", &self.cpuset_cpus),
+            ("cpuset_mems", &self.cpuset_mems),
+        ] {
+            if let Some(value) = value {
+                if !is_valid_cpuset(value) {
+                    errors.push(format!(
+                        "{} must be a list of ids or ranges such as '0-3,6', got '{}'",
+                        name, value
+                    ));
+                }
+            }
+        }
+        if let Some(memory) = self.memory_limit_in_bytes {
+            if memory < MIN_MEMORY_LIMIT {
+                errors.push(format!(
+                    "memory_limit_in_bytes must be at least {} bytes, got {}",
+                    MIN_MEMORY_LIMIT, memory
+                ));
+            }
+        }
+        if let Some(swap) = self.memory_swap_limit_in_bytes {
+            if swap != -1 && swap < self.memory_limit_in_bytes.unwrap_or(0) {
+                errors.push(format!(
+                    "memory_swap_limit_in_bytes must be -1 (unlimited) or not less than memory_limit_in_bytes, got {}",
+                    swap
+                ));
+            }
+        }
+        if let Some(hugepage_limits) = &self.hugepage_limits {
+            for page_size in hugepage_limits.keys() {
+                if !is_valid_page_size(page_size) {
+                    errors.push(format!(
+                        "hugepage page size must look like '2MB' or '1GB', got '{}'",
+                        page_size
+                    ));
+                }
+            }
+        }
+        if let Some(unified) = &self.unified {
+            for key in unified.keys() {
+                if !key.contains('.') || key.contains('/') {
+                    errors.push(format!(
+                        "unified key must be a cgroup v2 file name such as 'memory.high', got '{}'",
+                        key
+                    ));
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Merge the requested values over the current resources of the container
+    pub fn apply(&self, mut resources: LinuxContainerResources) -> LinuxContainerResources {
+        if let Some(shares) = self.cpu_shares {
+            resources.cpu_shares = shares;
+        }
+        if let Some(quota) = self.cpu_quota {
+            resources.cpu_quota = quota;
+        }
+        if let Some(period) = self.cpu_period {
+            resources.cpu_period = period;
+        }
+        if let Some(cpus) = &self.cpuset_cpus {
+            resources.cpuset_cpus = cpus.clone();
+        }
+        if let Some(mems) = &self.cpuset_mems {
+            resources.cpuset_mems = mems.clone();
+        }
+        if let Some(memory) = self.memory_limit_in_bytes {
+            resources.memory_limit_in_bytes = memory;
+        }
+        if let Some(swap) = self.memory_swap_limit_in_bytes {
+            resources.memory_swap_limit_in_bytes = swap;
+        }
+        if let Some(hugepage_limits) = &self.hugepage_limits {
+            for (page_size, limit) in hugepage_limits {
+                match resources
+                    .hugepage_limits
+                    .iter_mut()
+                    .find(|h| &h.page_size == page_size)
+                {
+                    Some(existing) => existing.limit = *limit,
+                    None => resources.hugepage_limits.push(HugepageLimit {
+                        page_size: page_size.clone(),
+                        limit: *limit,
+                    }),
+                }
+            }
+        }
+        if let Some(unified) = &self.unified {
+            resources.unified.extend(unified.clone());
+        }
+        resources
+    }
+}
+
+fn is_valid_cpuset(value: &str) -> bool {
+    !value.is_empty()
+        && value.split(',').all(|part| {
+            let mut bounds = part.splitn(2, '-');
+            let start = bounds.next().and_then(|b| b.trim().parse::<u32>().ok());
+            match (start, bounds.next()) {
+                (Some(_), None) => true,
+                (Some(start), Some(end)) => end.trim().parse::<u32>().is_ok_and(|end| end >= start),
+                _ => false,
+            }
+        })
+}
+
+fn is_valid_page_size(value: &str) -> bool {
+    ["KB", "MB", "GB", "TB", "PB"].iter().any(|unit| {
+        value
+            .strip_suffix(unit)
+            .is_some_and(|size| !size.is_empty() && size.chars().all(|c| c.is_ascii_digit()))
+    })
+}
+
+/// Apply a resource update to a running container.
+///
+/// The current resources are read from `ContainerStatus` first and the update is merged over
+/// them, so fields that are not part of the update keep their value. Returns the resources
+/// before and after the update as reported by the runtime.
+pub async fn update_container_resources(
+    client: &mut crate::api::runtime::v1::RuntimeServiceClient<Channel>,
+    container_id: String,
+    update: &ContainerResourceUpdate,
+) -> Result<
+    (
+        Option<LinuxContainerResources>,
+        Option<LinuxContainerResources>,
+    ),
+    tonic::Status,
+> {
+    update
+        .validate()
+        .map_err(|errors| tonic::Status::invalid_argument(errors.join("; ")))?;
+
+    let before = current_resources(client, container_id.clone()).await?;
+
+    let resources = update.apply(before.clone().unwrap_or_default());
+    if resources.memory_swap_limit_in_bytes > 0
+        && resources.memory_swap_limit_in_bytes < resources.memory_limit_in_bytes
+    {
+        return Err(tonic::Status::invalid_argument(format!(
+            "memory_swap_limit_in_bytes {} is less than the memory limit {}",
+            resources.memory_swap_limit_in_bytes, resources.memory_limit_in_bytes
+        )));
+    }
+
+    let request = UpdateContainerResourcesRequest {
+        container_id: container_id.clone(),
+        linux: Some(resources),
+        windows: None,
+        annotations: HashMap::new(),
+    };
+    debug!("update container resources request: {:?}", request);
+    client.update_container_resources(request).await?;
+
+    let after = current_resources(client, container_id).await?;
+    Ok((before, after))
+}
+
+async fn current_resources(
+    client: &mut crate::api::runtime::v1::RuntimeServiceClient<Channel>,
+    container_id: String,
+) -> Result<Option<LinuxContainerResources>, tonic::Status> {
+    let status = container_status(client, container_id, false).await?;
+    Ok(status
+        .status
+        .and_then(|s| s.resources)
+        .and_then(|r| r.linux))
+}
+
 pub async fn exec_sync(
     client: &mut crate::api::runtime::v1::RuntimeServiceClient<Channel>,
     container_id: String,
-    command: String,
+    cmd: Vec<String>,
     timeout: Option<i64>,
 ) -> Result<crate::api::runtime::v1::ExecSyncResponse, tonic::Status> {
     let request = crate::api::runtime::v1::ExecSyncRequest {
         container_id,
-        cmd: vec![command],
+        cmd,
         timeout: timeout.unwrap_or(10), // Default timeout of 10 seconds
     };
 
@@ -149,6 +476,28 @@ pub async fn exec_sync(
     Ok(response.into_inner())
 }
 
+pub async fn exec(
+    client: &mut crate::api::runtime::v1::RuntimeServiceClient<Channel>,
+    container_id: String,
+    cmd: Vec<String>,
+    tty: bool,
+    stdin: bool,
+) -> Result<crate::api::runtime::v1::ExecResponse, tonic::Status> {
+    // stderr cannot be multiplexed on a TTY, it is merged into stdout instead
+    let request = crate::api::runtime::v1::ExecRequest {
+        container_id,
+        cmd,
+        tty,
+        stdin,
+        stdout: true,
+        stderr: !tty,
+    };
+    debug!("exec request: {:?}", request);
+
+    let response = client.exec(request).await?;
+    Ok(response.into_inner())
+}
+
 pub async fn reopen_container_log(
     client: &mut crate::api::runtime::v1::RuntimeServiceClient<Channel>,
     container_id: String,
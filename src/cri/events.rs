@@ -0,0 +1,95 @@
+use crate::api::runtime::v1::{ContainerEventResponse, ContainerEventType, GetEventsRequest};
+use crate::cri::container::container_state_name;
+use anyhow::Result;
+use std::collections::VecDeque;
+use tonic::transport::Channel;
+
+/// Number of container events kept in memory for `recent_container_events`
+pub const DEFAULT_HISTORY_CAPACITY: usize = 512;
+
+/// Compact view of a CRI container event, suitable for MCP notifications
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ContainerEvent {
+    pub container_id: String,
+    pub event_type: String,
+    /// Creation timestamp in nanoseconds
+    pub created_at: i64,
+    pub pod_sandbox_id: Option<String>,
+    pub pod_name: Option<String>,
+    pub pod_namespace: Option<String>,
+    /// State of the container as reported alongside the event
+    pub state: Option<String>,
+    pub exit_code: Option<i32>,
+    pub reason: Option<String>,
+}
+
+impl From<ContainerEventResponse> for ContainerEvent {
+    fn from(response: ContainerEventResponse) -> Self {
+        let event_type = ContainerEventType::try_from(response.container_event_type)
+            .map(|t| t.as_str_name().to_string())
+            .unwrap_or_else(|_| response.container_event_type.to_string());
+
+        let pod = response.pod_sandbox_status.as_ref();
+        let pod_metadata = pod.and_then(|p| p.metadata.as_ref());
+
+        let status = response
+            .containers_statuses
+            .iter()
+            .find(|s| s.id == response.container_id);
+
+        Self {
+            event_type,
+            created_at: response.created_at,
+            pod_sandbox_id: pod.map(|p| p.id.clone()),
+            pod_name: pod_metadata.map(|m| m.name.clone()),
+            pod_namespace: pod_metadata.map(|m| m.namespace.clone()),
+            state: status.map(|s| container_state_name(s.state)),
+            exit_code: status.map(|s| s.exit_code),
+            reason: status.map(|s| s.reason.clone()).filter(|r| !r.is_empty()),
+            container_id: response.container_id,
+        }
+    }
+}
+
+/// Bounded in-memory history of container events, oldest events are dropped first
+pub struct EventHistory {
+    events: VecDeque<ContainerEvent>,
+    capacity: usize,
+}
+
+impl EventHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            events: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub fn push(&mut self, event: ContainerEvent) {
+        if self.events.len() == self.capacity {
+            self.events.pop_front();
+        }
+        self.events.push_back(event);
+    }
+
+    /// Return up to `limit` most recent events, oldest first, optionally for one container
+    pub fn recent(&self, limit: usize, container_id: Option<&str>) -> Vec<ContainerEvent> {
+        let mut events: Vec<ContainerEvent> = self
+            .events
+            .iter()
+            .rev()
+            .filter(|e| container_id.is_none_or(|id| e.container_id == id))
+            .take(limit)
+            .cloned()
+            .collect();
+        events.reverse();
+        events
+    }
+}
+
+pub async fn container_events(
+    client: &mut crate::api::runtime::v1::RuntimeServiceClient<Channel>,
+) -> Result<tonic::Streaming<ContainerEventResponse>, tonic::Status> {
+    let response = client.get_container_events(GetEventsRequest {}).await?;
+    Ok(response.into_inner())
+}
@@ -0,0 +1,351 @@
+/**
+ * Streaming exec sessions for the Container Runtime Interface
+ *
+ * The CRI `Exec` RPC does not run anything by itself: it returns the URL of the
+ * runtime's streaming server, and the caller is expected to upgrade that URL to a
+ * multiplexed stream. This module speaks the Kubernetes `v4.channel.k8s.io`
+ * WebSocket protocol against that URL, where every frame starts with a channel byte:
+ *
+ * - 0: stdin (client -> server)
+ * - 1: stdout
+ * - 2: stderr
+ * - 3: error / final status, encoded as a Kubernetes `metav1.Status`
+ * - 4: terminal resize (client -> server), `{"Width": .., "Height": ..}`
+ *
+ * Output is buffered in memory so MCP clients can poll it incrementally. Sessions
+ * record when they were last used so idle ones can be reaped.
+ */
+use futures::{SinkExt, StreamExt};
+use rmcp::schemars;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, Mutex, Notify};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::debug;
+
+const STREAM_PROTOCOL: &str = "v4.channel.k8s.io";
+
+const STDIN_CHANNEL: u8 = 0;
+const STDOUT_CHANNEL: u8 = 1;
+const STDERR_CHANNEL: u8 = 2;
+const ERROR_CHANNEL: u8 = 3;
+const RESIZE_CHANNEL: u8 = 4;
+
+/// Maximum number of unread bytes kept per output stream before the oldest are dropped
+const MAX_BUFFERED_OUTPUT: usize = 1024 * 1024;
+
+/// Output collected from a session since the last read
+#[derive(Debug, Default, serde::Serialize, schemars::JsonSchema)]
+pub struct ExecOutput {
+    pub stdout: String,
+    pub stderr: String,
+    /// Whether unread output was discarded because the buffer was full
+    pub truncated: bool,
+    /// Whether the remote process has finished and the stream is closed
+    pub exited: bool,
+    pub exit_code: Option<i32>,
+    pub error: Option<String>,
+}
+
+#[derive(Default)]
+struct OutputBuffer {
+    stdout: VecDeque<u8>,
+    stderr: VecDeque<u8>,
+    truncated: bool,
+    exited: bool,
+    exit_code: Option<i32>,
+    error: Option<String>,
+}
+
+impl OutputBuffer {
+    fn push(&mut self, channel: u8, data: &[u8]) {
+        let target = match channel {
+            STDOUT_CHANNEL => &mut self.stdout,
+            STDERR_CHANNEL => &mut self.stderr,
+            _ => return,
+        };
+        target.extend(data);
+        if target.len() > MAX_BUFFERED_OUTPUT {
+            let overflow = target.len() - MAX_BUFFERED_OUTPUT;
+            target.drain(..overflow);
+            self.truncated = true;
+        }
+    }
+
+    fn has_data(&self) -> bool {
+        // a lone partial character is not output to wake a reader for
+        let has_text = |stream: &VecDeque<u8>| {
+            let last: Vec<u8> = stream.iter().rev().take(3).rev().copied().collect();
+            stream.len() > incomplete_tail(&last)
+        };
+        has_text(&self.stdout) || has_text(&self.stderr) || self.exited
+    }
+
+    fn take(&mut self) -> ExecOutput {
+        let output = ExecOutput {
+            stdout: drain_text(&mut self.stdout, self.exited),
+            stderr: drain_text(&mut self.stderr, self.exited),
+            truncated: self.truncated,
+            exited: self.exited,
+            exit_code: self.exit_code,
+            error: self.error.clone(),
+        };
+        self.truncated = false;
+        output
+    }
+}
+
+/// Drain the buffered output of a stream as text. A multi-byte character split across frames
+/// stays buffered until the rest of it arrives, unless the stream has ended
+fn drain_text(stream: &mut VecDeque<u8>, ended: bool) -> String {
+    let mut bytes: Vec<u8> = stream.drain(..).collect();
+    if !ended {
+        let tail = incomplete_tail(&bytes);
+        stream.extend(bytes.drain(bytes.len() - tail..));
+    }
+    String::from_utf8_lossy(&bytes).to_string()
+}
+
+/// Length of the UTF-8 character cut off at the end of `bytes`, 0 when the last one is whole
+fn incomplete_tail(bytes: &[u8]) -> usize {
+    for len in 1..=bytes.len().min(3) {
+        let byte = bytes[bytes.len() - len];
+        // continuation bytes are 10xxxxxx, look further back for the leading byte
+        if byte & 0xC0 == 0x80 {
+            continue;
+        }
+        let width = match byte {
+            0xC0..=0xDF => 2,
+            0xE0..=0xEF => 3,
+            0xF0..=0xF7 => 4,
+            _ => 1,
+        };
+        return if width > len { len } else { 0 };
+    }
+    0
+}
+
+/// A live exec stream connected to the runtime's streaming server
+pub struct ExecSession {
+    pub container_id: String,
+    pub tty: bool,
+    pub stdin: bool,
+    input: mpsc::UnboundedSender<Message>,
+    output: Arc<Mutex<OutputBuffer>>,
+    notify: Arc<Notify>,
+    task: JoinHandle<()>,
+    last_used: std::sync::Mutex<Instant>,
+}
+
+impl ExecSession {
+    /// Connect to the streaming URL returned by the CRI `Exec` RPC
+    pub async fn connect(
+        url: &str,
+        container_id: String,
+        tty: bool,
+        stdin: bool,
+    ) -> Result<Self, tonic::Status> {
+        let ws_url = if let Some(rest) = url.strip_prefix("http://") {
+            format!("ws://{}", rest)
+        } else if let Some(rest) = url.strip_prefix("https://") {
+            format!("wss://{}", rest)
+        } else {
+            url.to_string()
+        };
+        debug!("Connecting exec stream: {}", ws_url);
+
+        let mut request = ws_url
+            .into_client_request()
+            .map_err(|e| tonic::Status::invalid_argument(format!("Invalid exec URL: {}", e)))?;
+        request.headers_mut().insert(
+            "Sec-WebSocket-Protocol",
+            HeaderValue::from_static(STREAM_PROTOCOL),
+        );
+
+        let (stream, _) = tokio_tungstenite::connect_async(request)
+            .await
+            .map_err(|e| {
+                tonic::Status::unavailable(format!("Failed to connect exec stream: {}", e))
+            })?;
+        let (mut sink, mut source) = stream.split();
+
+        let (input, mut input_rx) = mpsc::unbounded_channel::<Message>();
+        let output = Arc::new(Mutex::new(OutputBuffer::default()));
+        let notify = Arc::new(Notify::new());
+
+        let task_output = output.clone();
+        let task_notify = notify.clone();
+        let task = tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    outgoing = input_rx.recv() => {
+                        let Some(message) = outgoing else { break };
+                        if let Err(e) = sink.send(message).await {
+                            task_output.lock().await.error = Some(format!("Failed to write to exec stream: {}", e));
+                            break;
+                        }
+                    }
+                    incoming = source.next() => {
+                        match incoming {
+                            Some(Ok(Message::Binary(frame))) => {
+                                let Some((&channel, data)) = frame.split_first() else { continue };
+                                let mut buffer = task_output.lock().await;
+                                if channel == ERROR_CHANNEL {
+                                    if !data.is_empty() {
+                                        let (exit_code, error) = parse_exit_status(data);
+                                        buffer.exit_code = exit_code;
+                                        buffer.error = error;
+                                    }
+                                } else {
+                                    buffer.push(channel, data);
+                                }
+                                drop(buffer);
+                                task_notify.notify_waiters();
+                            }
+                            Some(Ok(Message::Close(_))) | None => break,
+                            Some(Ok(_)) => {}
+                            Some(Err(e)) => {
+                                task_output.lock().await.error = Some(format!("Exec stream error: {}", e));
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+
+            let mut buffer = task_output.lock().await;
+            buffer.exited = true;
+            drop(buffer);
+            task_notify.notify_waiters();
+            let _ = sink.close().await;
+        });
+
+        Ok(Self {
+            container_id,
+            tty,
+            stdin,
+            input,
+            output,
+            notify,
+            task,
+            last_used: std::sync::Mutex::new(Instant::now()),
+        })
+    }
+
+    /// Write data to the process stdin
+    pub fn write_stdin(&self, data: &[u8]) -> Result<(), tonic::Status> {
+        if !self.stdin {
+            return Err(tonic::Status::failed_precondition(
+                "Session was opened without stdin",
+            ));
+        }
+        self.touch();
+        self.send(STDIN_CHANNEL, data)
+    }
+
+    /// Resize the remote terminal
+    pub fn resize(&self, width: u16, height: u16) -> Result<(), tonic::Status> {
+        if !self.tty {
+            return Err(tonic::Status::failed_precondition(
+                "Session was opened without a TTY",
+            ));
+        }
+        self.touch();
+        let size = serde_json::json!({ "Width": width, "Height": height });
+        self.send(RESIZE_CHANNEL, size.to_string().as_bytes())
+    }
+
+    /// Drain the output buffered since the last read, waiting up to `wait` for new data
+    pub async fn read(&self, wait: Duration) -> ExecOutput {
+        self.touch();
+        let notified = self.notify.notified();
+        {
+            let mut buffer = self.output.lock().await;
+            if buffer.has_data() || wait.is_zero() {
+                return buffer.take();
+            }
+        }
+        let _ = tokio::time::timeout(wait, notified).await;
+        self.output.lock().await.take()
+    }
+
+    /// Close the stream, returning any output that was still unread
+    pub async fn close(&self) -> ExecOutput {
+        let _ = self.input.send(Message::Close(None));
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(2);
+        loop {
+            let notified = self.notify.notified();
+            if self.output.lock().await.exited {
+                break;
+            }
+            if tokio::time::timeout_at(deadline, notified).await.is_err() {
+                debug!(
+                    "Exec stream for {} did not close in time",
+                    self.container_id
+                );
+                break;
+            }
+        }
+        self.output.lock().await.take()
+    }
+
+    /// Whether the session has not been read from or written to for `ttl`
+    pub fn idle_for(&self, ttl: Duration) -> bool {
+        let last_used = *self.last_used.lock().unwrap_or_else(|e| e.into_inner());
+        last_used.elapsed() >= ttl
+    }
+
+    fn touch(&self) {
+        *self.last_used.lock().unwrap_or_else(|e| e.into_inner()) = Instant::now();
+    }
+
+    fn send(&self, channel: u8, data: &[u8]) -> Result<(), tonic::Status> {
+        let mut frame = Vec::with_capacity(data.len() + 1);
+        frame.push(channel);
+        frame.extend_from_slice(data);
+        self.input
+            .send(Message::Binary(frame))
+            .map_err(|_| tonic::Status::unavailable("Exec stream is closed"))
+    }
+}
+
+impl Drop for ExecSession {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Parse the `metav1.Status` sent on the error channel into an exit code and error message
+fn parse_exit_status(data: &[u8]) -> (Option<i32>, Option<String>) {
+    let status: serde_json::Value = match serde_json::from_slice(data) {
+        Ok(status) => status,
+        Err(_) => return (None, Some(String::from_utf8_lossy(data).to_string())),
+    };
+
+    if status["status"] == "Success" {
+        return (Some(0), None);
+    }
+
+    let exit_code = status["details"]["causes"]
+        .as_array()
+        .and_then(|causes| {
+            causes
+                .iter()
+                .find(|cause| cause["reason"] == "ExitCode")
+                .and_then(|cause| cause["message"].as_str())
+        })
+        .and_then(|code| code.parse::<i32>().ok());
+
+    let error = if status["reason"] == "NonZeroExitCode" {
+        None
+    } else {
+        status["message"].as_str().map(|s| s.to_string())
+    };
+
+    (exit_code, error)
+}
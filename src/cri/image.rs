@@ -1,15 +1,24 @@
 use crate::api::runtime::v1::{
-    ImageFsInfoRequest, ImageFsInfoResponse, ImageSpec, ListImagesRequest, ListImagesResponse,
+    AuthConfig, ImageFilter, ImageFsInfoRequest, ImageFsInfoResponse, ImageSpec,
+    ImageStatusRequest, ImageStatusResponse, ListImagesRequest, ListImagesResponse,
     PullImageRequest, RemoveImageRequest,
 };
 use anyhow::Result;
 use std::collections::HashMap;
 use tonic::transport::Channel;
+use tracing::debug;
 
 pub async fn pull_image(
     client: &mut crate::api::runtime::v1::ImageServiceClient<Channel>,
     image_reference: String,
+    auth: Option<AuthConfig>,
 ) -> Result<String, tonic::Status> {
+    debug!(
+        "Pull image request - image: {}, auth: {:?}",
+        image_reference,
+        auth.as_ref().map(crate::cri::auth::redact)
+    );
+
     let request = PullImageRequest {
         image: Some(ImageSpec {
             image: image_reference,
@@ -17,7 +26,7 @@ pub async fn pull_image(
             runtime_handler: "".to_string(),
             user_specified_image: "".to_string(),
         }),
-        auth: None,
+        auth,
         sandbox_config: None,
     };
 
@@ -42,14 +51,160 @@ pub async fn remove_image(
     Ok(())
 }
 
+/// List images, optionally only the one matching an exact reference.
+///
+/// containerd ignores the CRI `ImageFilter`, so the reference is also matched
+/// against the image id, tags and digests of the response.
 pub async fn list_images(
     client: &mut crate::api::runtime::v1::ImageServiceClient<Channel>,
+    image_reference: Option<String>,
 ) -> Result<ListImagesResponse, tonic::Status> {
-    let request = ListImagesRequest { filter: None };
-    let response = client.list_images(request).await?;
+    let filter = image_reference.clone().map(|image| ImageFilter {
+        image: Some(ImageSpec {
+            image,
+            annotations: HashMap::new(),
+            runtime_handler: "".to_string(),
+            user_specified_image: "".to_string(),
+        }),
+    });
+    let request = ListImagesRequest { filter };
+    debug!("list images request: {:?}", request);
+    let mut response = client.list_images(request).await?.into_inner();
+
+    if let Some(reference) = image_reference {
+        response.images.retain(|image| {
+            image.id == reference
+                || image.repo_tags.contains(&reference)
+                || image.repo_digests.contains(&reference)
+        });
+    }
+    Ok(response)
+}
+
+pub async fn image_status(
+    client: &mut crate::api::runtime::v1::ImageServiceClient<Channel>,
+    image_reference: String,
+    verbose: bool,
+) -> Result<ImageStatusResponse, tonic::Status> {
+    let request = ImageStatusRequest {
+        image: Some(ImageSpec {
+            image: image_reference,
+            annotations: HashMap::new(),
+            runtime_handler: "".to_string(),
+            user_specified_image: "".to_string(),
+        }),
+        verbose,
+    };
+    let response = client.image_status(request).await?;
     Ok(response.into_inner())
 }
 
+/// Structured view of an image and its OCI image config
+#[derive(Debug, Default, serde::Serialize)]
+pub struct ImageDetails {
+    pub id: String,
+    pub repo_tags: Vec<String>,
+    pub repo_digests: Vec<String>,
+    pub size: u64,
+    pub pinned: bool,
+    pub architecture: Option<String>,
+    pub os: Option<String>,
+    pub variant: Option<String>,
+    pub created: Option<String>,
+    pub entrypoint: Vec<String>,
+    pub cmd: Vec<String>,
+    pub env: Vec<String>,
+    pub exposed_ports: Vec<String>,
+    pub volumes: Vec<String>,
+    pub user: Option<String>,
+    pub working_dir: Option<String>,
+    pub stop_signal: Option<String>,
+    pub labels: HashMap<String, String>,
+    /// Number of filesystem layers in the image
+    pub layers: usize,
+    /// Uncompressed layer digests from `rootfs.diff_ids`, base layer first
+    pub diff_ids: Vec<String>,
+    /// How the image was built, oldest step first, empty when the config has no history
+    pub history: Vec<ImageHistory>,
+}
+
+/// A build step from the OCI image config history
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct ImageHistory {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_by: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    /// Whether the step left the filesystem unchanged, so has no entry in `diff_ids`
+    pub empty_layer: bool,
+}
+
+impl ImageDetails {
+    /// Build the view from an `ImageStatus` response, returns None when the image is not present
+    pub fn from_status(response: &ImageStatusResponse) -> Option<Self> {
+        let image = response.image.as_ref()?;
+        let mut details = ImageDetails {
+            id: image.id.clone(),
+            repo_tags: image.repo_tags.clone(),
+            repo_digests: image.repo_digests.clone(),
+            size: image.size,
+            pinned: image.pinned,
+            ..Default::default()
+        };
+
+        let info = crate::cri::runtime::parse_info(&response.info);
+        let Some(spec) = info.values().find_map(|v| v.get("imageSpec")) else {
+            return Some(details);
+        };
+
+        let string =
+            |v: &serde_json::Value| v.as_str().filter(|s| !s.is_empty()).map(|s| s.to_string());
+        let strings = |v: &serde_json::Value| -> Vec<String> {
+            v.as_array()
+                .map(|a| {
+                    a.iter()
+                        .filter_map(|s| s.as_str().map(String::from))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        let keys = |v: &serde_json::Value| -> Vec<String> {
+            let mut keys: Vec<String> = v
+                .as_object()
+                .map(|o| o.keys().cloned().collect())
+                .unwrap_or_default();
+            keys.sort();
+            keys
+        };
+
+        details.architecture = string(&spec["architecture"]);
+        details.os = string(&spec["os"]);
+        details.variant = string(&spec["variant"]);
+        details.created = string(&spec["created"]);
+        details.diff_ids = strings(&spec["rootfs"]["diff_ids"]);
+        details.layers = details.diff_ids.len();
+        details.history = serde_json::from_value(spec["history"].clone()).unwrap_or_default();
+
+        let config = &spec["config"];
+        details.entrypoint = strings(&config["Entrypoint"]);
+        details.cmd = strings(&config["Cmd"]);
+        details.env = strings(&config["Env"]);
+        details.exposed_ports = keys(&config["ExposedPorts"]);
+        details.volumes = keys(&config["Volumes"]);
+        details.user = string(&config["User"]);
+        details.working_dir = string(&config["WorkingDir"]);
+        details.stop_signal = string(&config["StopSignal"]);
+        details.labels = serde_json::from_value(config["Labels"].clone()).unwrap_or_default();
+
+        Some(details)
+    }
+}
+
 pub async fn image_fs_info(
     client: &mut crate::api::runtime::v1::ImageServiceClient<Channel>,
 ) -> Result<ImageFsInfoResponse, tonic::Status> {
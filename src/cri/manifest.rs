@@ -0,0 +1,1014 @@
+/**
+ * Kubernetes Pod manifest translation
+ *
+ * Maps a `v1.Pod` manifest, in YAML or JSON, onto the pod spec `run_pod` takes. The parts
+ * of the manifest that have a CRI equivalent become PodSandboxConfig and ContainerConfig
+ * fields, everything else is reported as unsupported rather than dropped silently.
+ *
+ * Supported:
+ * - metadata: name, namespace, uid, labels, annotations
+ * - spec: containers, volumes (hostPath, emptyDir), hostNetwork, hostPID, hostIPC,
+ *   shareProcessNamespace, hostname, dnsPolicy (None, Default), dnsConfig, securityContext
+ * - containers: image, command, args, workingDir, env values, volumeMounts, ports with a
+ *   hostPort, securityContext, cpu requests and limits, memory limits, stdin, stdinOnce, tty
+ *
+ * emptyDir volumes are directories the server creates under EMPTY_DIR_ROOT, so they only
+ * work when the server runs on the containerd host. They are removed with the pod.
+ */
+use crate::api::runtime::v1::{
+    security_profile::ProfileType, Capability, DnsConfig, Int64Value, LinuxContainerConfig,
+    LinuxContainerResources, LinuxContainerSecurityContext, LinuxPodSandboxConfig,
+    LinuxSandboxSecurityContext, NamespaceMode, NamespaceOption, SecurityProfile,
+};
+use crate::cri::workload::{ContainerSpec, MountSpec, PodSpec, PortSpec, RunningPod};
+use rmcp::schemars;
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+
+/// Host directory under which the emptyDir volumes of imported pods are created, per pod uid
+pub const EMPTY_DIR_ROOT: &str = "/var/lib/mcp-containerd/pods";
+
+/// Host directory holding the emptyDir volumes of the pod with the given uid. None for a
+/// uid that is not a DNS label, no manifest pod can have one
+pub fn volume_root(uid: &str) -> Option<PathBuf> {
+    dns_label(uid, "uid").ok()?;
+    Some(Path::new(EMPTY_DIR_ROOT).join(uid))
+}
+
+/// Create the volume root of a pod for its emptyDir volumes. The root must not exist yet, an
+/// existing one belongs to another pod with the same uid
+pub async fn claim_volume_root(uid: &str) -> std::io::Result<PathBuf> {
+    let root = volume_root(uid).ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("uid '{}' is not a DNS label", uid),
+        )
+    })?;
+    tokio::fs::create_dir_all(EMPTY_DIR_ROOT).await?;
+    tokio::fs::create_dir(&root).await?;
+    Ok(root)
+}
+
+/// Remove the emptyDir volumes of a pod, returning the directory if there was one
+pub async fn remove_volumes(uid: &str) -> std::io::Result<Option<PathBuf>> {
+    let Some(root) = volume_root(uid) else {
+        return Ok(None);
+    };
+    match tokio::fs::remove_dir_all(&root).await {
+        Ok(()) => Ok(Some(root)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Create the host directories of a manifest's volumes. The topmost directory each one had
+/// to create is pushed onto `created` before creating it, so a failed run can remove exactly
+/// what it made, even after a partial create_dir_all
+pub async fn create_host_dirs(dirs: &[String], created: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for dir in dirs {
+        let mut topmost = None;
+        for ancestor in Path::new(dir).ancestors() {
+            match tokio::fs::symlink_metadata(ancestor).await {
+                Ok(_) => break,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    topmost = Some(ancestor.to_path_buf())
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        created.extend(topmost);
+        tokio::fs::create_dir_all(dir).await.map_err(|e| {
+            std::io::Error::new(e.kind(), format!("volume directory {}: {}", dir, e))
+        })?;
+    }
+    Ok(())
+}
+
+/// CFS period the cpu limit of a container is converted with, the one kubelet uses
+const CPU_PERIOD: i64 = 100_000;
+
+/// A manifest field that has no CRI equivalent and was left out of the translation
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct UnsupportedField {
+    /// Path of the field in the manifest, such as spec.containers[0].livenessProbe
+    pub field: String,
+    pub reason: String,
+}
+
+/// A Pod manifest translated into a pod spec
+#[derive(Debug)]
+pub struct PodManifest {
+    pub pod: PodSpec,
+    /// Host directories backing emptyDir volumes, to create before the pod runs
+    pub host_dirs: Vec<String>,
+    pub unsupported: Vec<UnsupportedField>,
+}
+
+/// Outcome of running a Pod manifest
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct ManifestRun {
+    /// The started pod, unset for a dry run
+    pub pod: Option<RunningPod>,
+    /// The translated pod spec, only set for a dry run
+    pub spec: Option<PodSpec>,
+    /// Host directories created for emptyDir and DirectoryOrCreate volumes
+    pub host_dirs: Vec<String>,
+    pub unsupported: Vec<UnsupportedField>,
+}
+
+/// Parse a Pod manifest in YAML or JSON and translate it into a pod spec
+pub fn parse_manifest(manifest: &str) -> Result<PodManifest, String> {
+    let value: Value =
+        serde_yaml::from_str(manifest).map_err(|e| format!("Invalid manifest: {}", e))?;
+    translate_pod(&value)
+}
+
+/// Translate a Pod manifest, already parsed into a JSON value, into a pod spec
+pub fn translate_pod(manifest: &Value) -> Result<PodManifest, String> {
+    let mut translator = Translator::default();
+    let mut root = Fields::new(String::new(), manifest)?;
+    match root.string("apiVersion")?.as_deref() {
+        None | Some("v1") => {}
+        Some(other) => return Err(format!("Unsupported apiVersion '{}', expected v1", other)),
+    }
+    match root.string("kind")?.as_deref() {
+        None | Some("Pod") => {}
+        Some(other) => return Err(format!("Unsupported kind '{}', expected Pod", other)),
+    }
+
+    let mut metadata = root
+        .object("metadata")?
+        .ok_or("The manifest has no metadata")?;
+    let name = metadata
+        .string("name")?
+        .ok_or("The manifest has no metadata.name")?;
+    let namespace = metadata.string("namespace")?;
+    let uid = metadata
+        .string("uid")?
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    // the uid names the host directory of the emptyDir volumes
+    dns_label(&uid, &metadata.path("uid"))?;
+    let labels = metadata.string_map("labels")?;
+    let annotations = metadata.string_map("annotations")?;
+    translator.finish(metadata);
+
+    let mut spec = root.object("spec")?.ok_or("The manifest has no spec")?;
+    let pod = translator.pod(&mut spec, name, namespace, uid, labels, annotations)?;
+    translator.finish(spec);
+    // status is written by the cluster, never by the author of the manifest
+    root.take("status");
+    translator.finish(root);
+
+    Ok(PodManifest {
+        pod,
+        host_dirs: translator.host_dirs,
+        unsupported: translator.unsupported,
+    })
+}
+
+/// The fields of a manifest object, tracking which of them the translation used
+struct Fields<'a> {
+    path: String,
+    map: &'a Map<String, Value>,
+    taken: HashSet<&'a str>,
+}
+
+impl<'a> Fields<'a> {
+    fn new(path: String, value: &'a Value) -> Result<Self, String> {
+        let map = value.as_object().ok_or_else(|| {
+            let name = if path.is_empty() {
+                "The manifest"
+            } else {
+                &path
+            };
+            format!("{} must be an object", name)
+        })?;
+        Ok(Self {
+            path,
+            map,
+            taken: HashSet::new(),
+        })
+    }
+
+    fn path(&self, key: &str) -> String {
+        if self.path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", self.path, key)
+        }
+    }
+
+    /// Mark a field as used, returning its value unless it is null
+    fn take(&mut self, key: &str) -> Option<&'a Value> {
+        let (key, value) = self.map.get_key_value(key)?;
+        self.taken.insert(key.as_str());
+        Some(value).filter(|value| !value.is_null())
+    }
+
+    fn string(&mut self, key: &str) -> Result<Option<String>, String> {
+        match self.take(key) {
+            None => Ok(None),
+            Some(Value::String(s)) => Ok(Some(s.clone())),
+            Some(_) => Err(format!("{} must be a string", self.path(key))),
+        }
+    }
+
+    fn bool(&mut self, key: &str) -> Result<Option<bool>, String> {
+        match self.take(key) {
+            None => Ok(None),
+            Some(Value::Bool(b)) => Ok(Some(*b)),
+            Some(_) => Err(format!("{} must be a boolean", self.path(key))),
+        }
+    }
+
+    fn int(&mut self, key: &str) -> Result<Option<i64>, String> {
+        match self.take(key) {
+            None => Ok(None),
+            Some(value) => value
+                .as_i64()
+                .map(Some)
+                .ok_or_else(|| format!("{} must be an integer", self.path(key))),
+        }
+    }
+
+    fn strings(&mut self, key: &str) -> Result<Option<Vec<String>>, String> {
+        match self.take(key) {
+            None => Ok(None),
+            Some(value) => serde_json::from_value(value.clone())
+                .map(Some)
+                .map_err(|_| format!("{} must be a list of strings", self.path(key))),
+        }
+    }
+
+    fn ints(&mut self, key: &str) -> Result<Vec<i64>, String> {
+        match self.take(key) {
+            None => Ok(Vec::new()),
+            Some(value) => serde_json::from_value(value.clone())
+                .map_err(|_| format!("{} must be a list of integers", self.path(key))),
+        }
+    }
+
+    fn string_map(&mut self, key: &str) -> Result<Option<HashMap<String, String>>, String> {
+        match self.take(key) {
+            None => Ok(None),
+            Some(value) => serde_json::from_value(value.clone())
+                .map(Some)
+                .map_err(|_| format!("{} must map strings to strings", self.path(key))),
+        }
+    }
+
+    fn object(&mut self, key: &str) -> Result<Option<Fields<'a>>, String> {
+        let path = self.path(key);
+        self.take(key)
+            .map(|value| Fields::new(path, value))
+            .transpose()
+    }
+
+    fn list(&mut self, key: &str) -> Result<Vec<Fields<'a>>, String> {
+        let path = self.path(key);
+        match self.take(key) {
+            None => Ok(Vec::new()),
+            Some(Value::Array(items)) => items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| Fields::new(format!("{}[{}]", path, i), item))
+                .collect(),
+            Some(_) => Err(format!("{} must be a list", path)),
+        }
+    }
+}
+
+/// Which namespaces of the host a pod shares
+#[derive(Clone, Copy)]
+struct HostNamespaces {
+    network: bool,
+    pid: bool,
+    ipc: bool,
+    share_pid: bool,
+}
+
+impl HostNamespaces {
+    fn options(&self) -> NamespaceOption {
+        let mode = |host: bool, otherwise: NamespaceMode| {
+            if host {
+                NamespaceMode::Node as i32
+            } else {
+                otherwise as i32
+            }
+        };
+        NamespaceOption {
+            network: mode(self.network, NamespaceMode::Pod),
+            pid: mode(
+                self.pid,
+                if self.share_pid {
+                    NamespaceMode::Pod
+                } else {
+                    NamespaceMode::Container
+                },
+            ),
+            ipc: mode(self.ipc, NamespaceMode::Pod),
+            ..Default::default()
+        }
+    }
+}
+
+/// The security settings of the pod that its containers inherit
+#[derive(Default)]
+struct PodSecurity {
+    run_as_user: Option<i64>,
+    run_as_group: Option<i64>,
+    run_as_non_root: bool,
+    supplemental_groups: Vec<i64>,
+    seccomp: Option<SecurityProfile>,
+    apparmor: Option<SecurityProfile>,
+}
+
+/// A volume of the pod, by the host path that backs it
+struct Volume {
+    host_path: String,
+}
+
+#[derive(Default)]
+struct Translator {
+    unsupported: Vec<UnsupportedField>,
+    host_dirs: Vec<String>,
+}
+
+impl Translator {
+    fn unsupported(&mut self, field: String, reason: &str) {
+        self.unsupported.push(UnsupportedField {
+            field,
+            reason: reason.to_string(),
+        });
+    }
+
+    /// Report the fields of an object the translation did not use
+    fn finish(&mut self, fields: Fields) {
+        let mut keys: Vec<&String> = fields
+            .map
+            .keys()
+            .filter(|key| !fields.taken.contains(key.as_str()))
+            .collect();
+        keys.sort();
+        for key in keys {
+            self.unsupported(fields.path(key), "no CRI equivalent");
+        }
+    }
+
+    fn pod(
+        &mut self,
+        spec: &mut Fields,
+        name: String,
+        namespace: Option<String>,
+        uid: String,
+        labels: Option<HashMap<String, String>>,
+        annotations: Option<HashMap<String, String>>,
+    ) -> Result<PodSpec, String> {
+        let host = HostNamespaces {
+            network: spec.bool("hostNetwork")?.unwrap_or(false),
+            pid: spec.bool("hostPID")?.unwrap_or(false),
+            ipc: spec.bool("hostIPC")?.unwrap_or(false),
+            share_pid: spec.bool("shareProcessNamespace")?.unwrap_or(false),
+        };
+
+        let mut options = Map::new();
+        if let Some(hostname) = spec.string("hostname")? {
+            options.insert("hostname".to_string(), Value::String(hostname));
+        } else if !host.network {
+            options.insert("hostname".to_string(), Value::String(name.clone()));
+        }
+        if let Some(dns_config) = self.dns(spec)? {
+            options.insert("dns_config".to_string(), to_value(&dns_config));
+        }
+
+        let mut security = PodSecurity::default();
+        let mut sysctls = HashMap::new();
+        if let Some(mut context) = spec.object("securityContext")? {
+            security.run_as_user = context.int("runAsUser")?;
+            security.run_as_group = context.int("runAsGroup")?;
+            security.run_as_non_root = context.bool("runAsNonRoot")?.unwrap_or(false);
+            security.supplemental_groups = context.ints("supplementalGroups")?;
+            if let Some(fs_group) = context.int("fsGroup")? {
+                security.supplemental_groups.push(fs_group);
+                self.unsupported(
+                    context.path("fsGroup"),
+                    "added as a supplemental group, volume ownership is not changed",
+                );
+            }
+            for mut sysctl in context.list("sysctls")? {
+                let key = sysctl.string("name")?.unwrap_or_default();
+                let value = sysctl.string("value")?.unwrap_or_default();
+                sysctls.insert(key, value);
+                self.finish(sysctl);
+            }
+            security.seccomp = security_profile(context.object("seccompProfile")?)?;
+            security.apparmor = security_profile(context.object("appArmorProfile")?)?;
+            self.finish(context);
+        }
+        let sandbox_security = LinuxSandboxSecurityContext {
+            namespace_options: Some(host.options()),
+            run_as_user: security.run_as_user.map(|value| Int64Value { value }),
+            run_as_group: security.run_as_group.map(|value| Int64Value { value }),
+            supplemental_groups: security.supplemental_groups.clone(),
+            seccomp: security.seccomp.clone(),
+            apparmor: security.apparmor.clone(),
+            ..Default::default()
+        };
+        let linux = LinuxPodSandboxConfig {
+            security_context: Some(sandbox_security),
+            sysctls,
+            ..Default::default()
+        };
+        options.insert("linux".to_string(), to_value(&linux));
+
+        if let Some(policy) = spec.string("restartPolicy")? {
+            if policy != "Never" {
+                self.unsupported(
+                    spec.path("restartPolicy"),
+                    "containers are not restarted, the pod runs as with restartPolicy Never",
+                );
+            }
+        }
+        for key in ["initContainers", "ephemeralContainers"] {
+            if spec.take(key).is_some() {
+                self.unsupported(spec.path(key), "only regular containers are run");
+            }
+        }
+
+        let volumes = self.volumes(spec, &uid)?;
+        let mut ports = Vec::new();
+        let mut containers = Vec::new();
+        for mut container in spec.list("containers")? {
+            containers.push(self.container(
+                &mut container,
+                host,
+                &security,
+                &volumes,
+                &mut ports,
+            )?);
+            self.finish(container);
+        }
+
+        Ok(PodSpec {
+            name,
+            namespace,
+            uid: Some(uid),
+            labels,
+            annotations,
+            ports: Some(ports),
+            options: Some(options.into()),
+            containers,
+        })
+    }
+
+    fn dns(&mut self, spec: &mut Fields) -> Result<Option<DnsConfig>, String> {
+        match spec.string("dnsPolicy")?.as_deref() {
+            // the runtime copies the resolv.conf of the host unless dnsConfig replaces it
+            None | Some("None") | Some("Default") => {}
+            Some(_) => self.unsupported(
+                spec.path("dnsPolicy"),
+                "there is no cluster DNS, the pod uses the resolv.conf of the host",
+            ),
+        }
+        let Some(mut config) = spec.object("dnsConfig")? else {
+            return Ok(None);
+        };
+        let mut dns = DnsConfig {
+            servers: config.strings("nameservers")?.unwrap_or_default(),
+            searches: config.strings("searches")?.unwrap_or_default(),
+            options: Vec::new(),
+        };
+        for mut option in config.list("options")? {
+            let name = option.string("name")?.unwrap_or_default();
+            dns.options.push(match option.string("value")? {
+                Some(value) => format!("{}:{}", name, value),
+                None => name,
+            });
+            self.finish(option);
+        }
+        self.finish(config);
+        Ok(Some(dns))
+    }
+
+    fn volumes(&mut self, spec: &mut Fields, uid: &str) -> Result<HashMap<String, Volume>, String> {
+        let mut volumes = HashMap::new();
+        for mut volume in spec.list("volumes")? {
+            let name = volume
+                .string("name")?
+                .ok_or_else(|| format!("{} has no name", volume.path))?;
+            dns_label(&name, &volume.path("name"))?;
+            if let Some(mut host_path) = volume.object("hostPath")? {
+                let path = host_path
+                    .string("path")?
+                    .ok_or_else(|| format!("{} has no path", host_path.path))?;
+                if !Path::new(&path).is_absolute() {
+                    return Err(format!(
+                        "{} '{}' must be an absolute path",
+                        host_path.path("path"),
+                        path
+                    ));
+                }
+                if host_path.string("type")?.as_deref() == Some("DirectoryOrCreate") {
+                    self.host_dirs.push(path.clone());
+                }
+                self.finish(host_path);
+                volumes.insert(name, Volume { host_path: path });
+            } else if let Some(mut empty_dir) = volume.object("emptyDir")? {
+                if empty_dir.string("medium")?.is_some_and(|m| !m.is_empty()) {
+                    self.unsupported(
+                        empty_dir.path("medium"),
+                        "emptyDir volumes are always backed by disk",
+                    );
+                }
+                if empty_dir.take("sizeLimit").is_some() {
+                    self.unsupported(empty_dir.path("sizeLimit"), "emptyDir size is not limited");
+                }
+                self.finish(empty_dir);
+                let path = format!("{}/{}/volumes/{}", EMPTY_DIR_ROOT, uid, name);
+                self.host_dirs.push(path.clone());
+                volumes.insert(name, Volume { host_path: path });
+            }
+            // any other volume source is left unused, and reported as such
+            self.finish(volume);
+        }
+        Ok(volumes)
+    }
+
+    fn container(
+        &mut self,
+        container: &mut Fields,
+        host: HostNamespaces,
+        pod_security: &PodSecurity,
+        volumes: &HashMap<String, Volume>,
+        ports: &mut Vec<PortSpec>,
+    ) -> Result<ContainerSpec, String> {
+        let name = container
+            .string("name")?
+            .ok_or_else(|| format!("{} has no name", container.path))?;
+        let image = container
+            .string("image")?
+            .ok_or_else(|| format!("{} has no image", container.path))?;
+        match container.string("imagePullPolicy")?.as_deref() {
+            None | Some("IfNotPresent") => {}
+            Some(_) => self.unsupported(
+                container.path("imagePullPolicy"),
+                "images are only pulled when they are not present",
+            ),
+        }
+
+        let mut env = HashMap::new();
+        for mut var in container.list("env")? {
+            let key = var
+                .string("name")?
+                .ok_or_else(|| format!("{} has no name", var.path))?;
+            if let Some(value) = var.string("value")? {
+                env.insert(key, value);
+            }
+            self.finish(var);
+        }
+
+        let mut mounts = Vec::new();
+        for mut mount in container.list("volumeMounts")? {
+            let volume = mount
+                .string("name")?
+                .ok_or_else(|| format!("{} has no name", mount.path))?;
+            let container_path = mount
+                .string("mountPath")?
+                .ok_or_else(|| format!("{} has no mountPath", mount.path))?;
+            let readonly = mount.bool("readOnly")?;
+            let sub_path = mount.string("subPath")?;
+            if let Some(sub_path) = &sub_path {
+                relative_path(sub_path, &mount.path("subPath"))?;
+            }
+            let propagation = match mount.string("mountPropagation")?.as_deref() {
+                None | Some("None") => None,
+                Some("HostToContainer") => Some("host_to_container".to_string()),
+                Some("Bidirectional") => Some("bidirectional".to_string()),
+                Some(other) => {
+                    return Err(format!(
+                        "{} '{}' is not one of None, HostToContainer or Bidirectional",
+                        mount.path("mountPropagation"),
+                        other
+                    ))
+                }
+            };
+            match volumes.get(&volume) {
+                Some(source) => mounts.push(MountSpec {
+                    host_path: match sub_path {
+                        Some(sub_path) => format!("{}/{}", source.host_path, sub_path),
+                        None => source.host_path.clone(),
+                    },
+                    container_path,
+                    readonly,
+                    propagation,
+                }),
+                None => self.unsupported(
+                    mount.path.clone(),
+                    "the volume is not a hostPath or emptyDir volume of the pod",
+                ),
+            }
+            self.finish(mount);
+        }
+
+        for mut port in container.list("ports")? {
+            let container_port = port
+                .int("containerPort")?
+                .ok_or_else(|| format!("{} has no containerPort", port.path))?;
+            let host_port = port.int("hostPort")?;
+            let protocol = port.string("protocol")?;
+            let host_ip = port.string("hostIP")?;
+            // named ports only matter to services
+            port.take("name");
+            match host_port {
+                Some(host_port) => ports.push(PortSpec {
+                    container_port: container_port as i32,
+                    host_port: Some(host_port as i32),
+                    protocol,
+                    host_ip,
+                }),
+                None => self.unsupported(
+                    port.path.clone(),
+                    "only ports with a hostPort are mapped, the others are reachable on the pod IP",
+                ),
+            }
+            self.finish(port);
+        }
+
+        let mut options = Map::new();
+        for key in ["stdin", "stdinOnce", "tty"] {
+            if let Some(value) = container.bool(key)? {
+                let key = if key == "stdinOnce" {
+                    "stdin_once"
+                } else {
+                    key
+                };
+                options.insert(key.to_string(), Value::Bool(value));
+            }
+        }
+        let linux = LinuxContainerConfig {
+            resources: self.resources(container)?,
+            security_context: Some(self.security_context(container, host, pod_security)?),
+        };
+        options.insert("linux".to_string(), to_value(&linux));
+
+        Ok(ContainerSpec {
+            name,
+            image,
+            command: container.strings("command")?,
+            args: container.strings("args")?,
+            env: Some(env),
+            mounts: Some(mounts),
+            working_dir: container.string("workingDir")?,
+            options: Some(options.into()),
+        })
+    }
+
+    /// Convert cpu and memory requests and limits the way kubelet does
+    fn resources(
+        &mut self,
+        container: &mut Fields,
+    ) -> Result<Option<LinuxContainerResources>, String> {
+        let Some(mut resources) = container.object("resources")? else {
+            return Ok(None);
+        };
+        let mut quantities = HashMap::new();
+        for kind in ["requests", "limits"] {
+            let Some(mut values) = resources.object(kind)? else {
+                continue;
+            };
+            for resource in ["cpu", "memory"] {
+                if let Some(value) = values.take(resource) {
+                    let quantity = parse_quantity(value)
+                        .ok_or_else(|| format!("{} is not a quantity", values.path(resource)))?;
+                    if (kind, resource) == ("requests", "memory") {
+                        self.unsupported(
+                            values.path(resource),
+                            "memory requests only matter to scheduling, the limit is applied",
+                        );
+                    }
+                    quantities.insert((kind, resource), quantity);
+                }
+            }
+            self.finish(values);
+        }
+        self.finish(resources);
+
+        let mut linux = LinuxContainerResources::default();
+        // without a request kubernetes requests the limit
+        let cpu_request = quantities
+            .get(&("requests", "cpu"))
+            .or(quantities.get(&("limits", "cpu")));
+        if let Some(cpu) = cpu_request {
+            linux.cpu_shares = ((cpu * 1024.0).round() as i64).max(2);
+        }
+        if let Some(cpu) = quantities.get(&("limits", "cpu")) {
+            linux.cpu_period = CPU_PERIOD;
+            linux.cpu_quota = ((cpu * CPU_PERIOD as f64).round() as i64).max(1000);
+        }
+        if let Some(memory) = quantities.get(&("limits", "memory")) {
+            linux.memory_limit_in_bytes = memory.round() as i64;
+        }
+        Ok(Some(linux))
+    }
+
+    fn security_context(
+        &mut self,
+        container: &mut Fields,
+        host: HostNamespaces,
+        pod: &PodSecurity,
+    ) -> Result<LinuxContainerSecurityContext, String> {
+        let mut security = LinuxContainerSecurityContext {
+            namespace_options: Some(host.options()),
+            run_as_user: pod.run_as_user.map(|value| Int64Value { value }),
+            run_as_group: pod.run_as_group.map(|value| Int64Value { value }),
+            supplemental_groups: pod.supplemental_groups.clone(),
+            seccomp: pod.seccomp.clone(),
+            apparmor: pod.apparmor.clone(),
+            ..Default::default()
+        };
+        let mut run_as_non_root = pod.run_as_non_root;
+        let path = container.path("securityContext");
+        if let Some(mut context) = container.object("securityContext")? {
+            if let Some(value) = context.int("runAsUser")? {
+                security.run_as_user = Some(Int64Value { value });
+            }
+            if let Some(value) = context.int("runAsGroup")? {
+                security.run_as_group = Some(Int64Value { value });
+            }
+            if let Some(value) = context.bool("runAsNonRoot")? {
+                run_as_non_root = value;
+            }
+            security.privileged = context.bool("privileged")?.unwrap_or(false);
+            security.readonly_rootfs = context.bool("readOnlyRootFilesystem")?.unwrap_or(false);
+            if let Some(allow) = context.bool("allowPrivilegeEscalation")? {
+                security.no_new_privs = !allow;
+            }
+            if let Some(mut capabilities) = context.object("capabilities")? {
+                security.capabilities = Some(Capability {
+                    add_capabilities: capabilities.strings("add")?.unwrap_or_default(),
+                    drop_capabilities: capabilities.strings("drop")?.unwrap_or_default(),
+                    ..Default::default()
+                });
+                self.finish(capabilities);
+            }
+            if let Some(seccomp) = security_profile(context.object("seccompProfile")?)? {
+                security.seccomp = Some(seccomp);
+            }
+            if let Some(apparmor) = security_profile(context.object("appArmorProfile")?)? {
+                security.apparmor = Some(apparmor);
+            }
+            self.finish(context);
+        }
+        if run_as_non_root {
+            match &security.run_as_user {
+                Some(user) if user.value == 0 => {
+                    return Err(format!("{} runs as root with runAsNonRoot set", path))
+                }
+                Some(_) => {}
+                None => self.unsupported(
+                    format!("{}.runAsNonRoot", path),
+                    "the user of the image is not checked, set runAsUser to enforce it",
+                ),
+            }
+        }
+        Ok(security)
+    }
+}
+
+/// Check that a value is a DNS label, as Kubernetes requires of uids and volume names
+fn dns_label(value: &str, field: &str) -> Result<(), String> {
+    let valid = !value.is_empty()
+        && value.len() <= 63
+        && value
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-')
+        && !value.starts_with('-')
+        && !value.ends_with('-');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} '{}' must be a DNS label: at most 63 lowercase letters, digits or '-', starting and ending with a letter or digit",
+            field, value
+        ))
+    }
+}
+
+/// Check that a subPath is relative and stays inside its volume, as Kubernetes requires
+fn relative_path(value: &str, field: &str) -> Result<(), String> {
+    let path = Path::new(value);
+    if path.is_absolute() {
+        return Err(format!("{} '{}' must be a relative path", field, value));
+    }
+    if path.components().any(|c| c == Component::ParentDir) {
+        return Err(format!("{} '{}' must not contain '..'", field, value));
+    }
+    Ok(())
+}
+
+/// Convert a seccompProfile or appArmorProfile into a CRI security profile
+fn security_profile(profile: Option<Fields>) -> Result<Option<SecurityProfile>, String> {
+    let Some(mut profile) = profile else {
+        return Ok(None);
+    };
+    let profile_type = match profile.string("type")?.as_deref() {
+        Some("RuntimeDefault") => ProfileType::RuntimeDefault,
+        Some("Unconfined") => ProfileType::Unconfined,
+        Some("Localhost") => ProfileType::Localhost,
+        other => {
+            return Err(format!(
+                "{} {:?} is not one of RuntimeDefault, Unconfined or Localhost",
+                profile.path("type"),
+                other.unwrap_or_default()
+            ))
+        }
+    };
+    Ok(Some(SecurityProfile {
+        profile_type: profile_type as i32,
+        localhost_ref: profile.string("localhostProfile")?.unwrap_or_default(),
+    }))
+}
+
+/// Parse a Kubernetes quantity such as 250m, 0.5, 128Mi or 1G into its plain value
+fn parse_quantity(value: &Value) -> Option<f64> {
+    let text = match value {
+        Value::Number(n) => return n.as_f64(),
+        Value::String(s) => s.trim(),
+        _ => return None,
+    };
+    // binary suffixes come first so that Mi is not read as M
+    let suffixes: [(&str, f64); 15] = [
+        ("Ki", 1024f64),
+        ("Mi", 1024f64.powi(2)),
+        ("Gi", 1024f64.powi(3)),
+        ("Ti", 1024f64.powi(4)),
+        ("Pi", 1024f64.powi(5)),
+        ("Ei", 1024f64.powi(6)),
+        ("n", 1e-9),
+        ("u", 1e-6),
+        ("m", 1e-3),
+        ("k", 1e3),
+        ("M", 1e6),
+        ("G", 1e9),
+        ("T", 1e12),
+        ("P", 1e15),
+        ("E", 1e18),
+    ];
+    for (suffix, factor) in suffixes {
+        if let Some(number) = text.strip_suffix(suffix) {
+            return number.parse::<f64>().ok().map(|n| n * factor);
+        }
+    }
+    // plain numbers, including exponents such as 1e3
+    text.parse::<f64>().ok()
+}
+
+fn to_value<T: serde::Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn pod(spec: Value) -> Value {
+        json!({
+            "apiVersion": "v1",
+            "kind": "Pod",
+            "metadata": { "name": "web", "uid": "web-1" },
+            "spec": spec,
+        })
+    }
+
+    fn resources(resources: Value) -> (LinuxContainerResources, Vec<UnsupportedField>) {
+        let container = json!({ "resources": resources });
+        let mut fields = Fields::new("spec.containers[0]".to_string(), &container).unwrap();
+        let mut translator = Translator::default();
+        let linux = translator.resources(&mut fields).unwrap().unwrap();
+        (linux, translator.unsupported)
+    }
+
+    fn unsupported(manifest: &PodManifest) -> Vec<&str> {
+        manifest
+            .unsupported
+            .iter()
+            .map(|f| f.field.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn parses_quantities() {
+        let cases = [
+            (json!("250m"), 0.25),
+            (json!("1Gi"), 1024f64.powi(3)),
+            (json!("128Mi"), 128.0 * 1024f64.powi(2)),
+            (json!("1G"), 1e9),
+            (json!("1e3"), 1000.0),
+            (json!("0.5"), 0.5),
+            (json!(2), 2.0),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(parse_quantity(&value), Some(expected), "{}", value);
+        }
+        for value in [json!("lots"), json!("1Xi"), json!(true)] {
+            assert_eq!(parse_quantity(&value), None, "{}", value);
+        }
+    }
+
+    #[test]
+    fn converts_cpu_like_kubelet() {
+        let (linux, _) = resources(json!({
+            "requests": { "cpu": "250m" },
+            "limits": { "cpu": "500m", "memory": "64Mi" },
+        }));
+        assert_eq!(linux.cpu_shares, 256);
+        assert_eq!(linux.cpu_period, CPU_PERIOD);
+        assert_eq!(linux.cpu_quota, 50_000);
+        assert_eq!(linux.memory_limit_in_bytes, 64 * 1024 * 1024);
+
+        // the limit is requested when there is no request, and tiny values are clamped
+        let (linux, _) = resources(json!({ "limits": { "cpu": "1m" } }));
+        assert_eq!(linux.cpu_shares, 2);
+        assert_eq!(linux.cpu_quota, 1000);
+    }
+
+    #[test]
+    fn reports_memory_requests() {
+        let (linux, unsupported) = resources(json!({ "requests": { "memory": "1Gi" } }));
+        assert_eq!(linux.memory_limit_in_bytes, 0);
+        let fields: Vec<&str> = unsupported.iter().map(|f| f.field.as_str()).collect();
+        assert_eq!(fields, ["spec.containers[0].resources.requests.memory"]);
+    }
+
+    #[test]
+    fn rejects_uids_that_are_not_dns_labels() {
+        for uid in ["Web_1", "-web", "web/../etc", ""] {
+            let mut manifest = pod(json!({ "containers": [{ "name": "c", "image": "nginx" }] }));
+            manifest["metadata"]["uid"] = json!(uid);
+            assert!(translate_pod(&manifest).is_err(), "{}", uid);
+        }
+    }
+
+    #[test]
+    fn rejects_sub_paths_outside_the_volume() {
+        for sub_path in ["../etc", "a/../../etc", "/etc"] {
+            let manifest = pod(json!({
+                "volumes": [{ "name": "data", "emptyDir": {} }],
+                "containers": [{
+                    "name": "c",
+                    "image": "nginx",
+                    "volumeMounts": [{ "name": "data", "mountPath": "/data", "subPath": sub_path }],
+                }],
+            }));
+            let error = translate_pod(&manifest).unwrap_err();
+            assert!(error.contains("subPath"), "{}: {}", sub_path, error);
+        }
+    }
+
+    #[test]
+    fn reports_unsupported_fields() {
+        let manifest = translate_pod(&pod(json!({
+            "restartPolicy": "Always",
+            "nodeSelector": { "disk": "ssd" },
+            "volumes": [
+                { "name": "data", "emptyDir": { "medium": "Memory" } },
+                { "name": "config", "configMap": { "name": "web" } },
+            ],
+            "containers": [{
+                "name": "web",
+                "image": "nginx",
+                "ports": [
+                    { "name": "http", "containerPort": 80, "hostPort": 8080 },
+                    { "containerPort": 443 },
+                ],
+                "volumeMounts": [
+                    { "name": "data", "mountPath": "/data" },
+                    { "name": "config", "mountPath": "/etc/web" },
+                ],
+                "livenessProbe": { "httpGet": { "port": 80 } },
+            }],
+        })))
+        .unwrap();
+        assert_eq!(
+            unsupported(&manifest),
+            [
+                "spec.restartPolicy",
+                "spec.volumes[0].emptyDir.medium",
+                "spec.volumes[1].configMap",
+                "spec.containers[0].volumeMounts[1]",
+                "spec.containers[0].ports[1]",
+                "spec.containers[0].livenessProbe",
+                "spec.nodeSelector",
+            ]
+        );
+        let ports = manifest.pod.ports.unwrap();
+        assert_eq!(ports.len(), 1);
+        assert_eq!(ports[0].host_port, Some(8080));
+        assert_eq!(
+            manifest.host_dirs,
+            [format!("{}/web-1/volumes/data", EMPTY_DIR_ROOT)]
+        );
+    }
+}
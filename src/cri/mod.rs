@@ -1,5 +1,13 @@
+pub mod auth;
+pub mod checkpoint;
 pub mod config;
 pub mod container;
+pub mod events;
+pub mod exec;
 pub mod image;
+pub mod manifest;
 pub mod pod;
+pub mod prune;
 pub mod runtime;
+pub mod security;
+pub mod workload;
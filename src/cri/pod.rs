@@ -1,9 +1,16 @@
 use crate::api::runtime::v1::{
-    ListPodSandboxRequest, ListPodSandboxResponse, ListPodSandboxStatsRequest, PodSandboxConfig,
-    RemovePodSandboxRequest, RunPodSandboxRequest, StopPodSandboxRequest,
+    LinuxPodSandboxConfig, LinuxSandboxSecurityContext, ListPodSandboxRequest,
+    ListPodSandboxResponse, ListPodSandboxStatsRequest, ListPodSandboxStatsResponse,
+    PodSandboxConfig, PodSandboxFilter, PodSandboxState, PodSandboxStatsFilter,
+    PodSandboxStatusRequest, PodSandboxStatusResponse, RemovePodSandboxRequest,
+    RunPodSandboxRequest, StopPodSandboxRequest,
 };
-use crate::cri::config::parse_pod_config;
+use crate::cri::config::{parse_pod_config, ConfigOptions, CreateError, ParseMode, Parsed};
+use crate::cri::runtime::parse_info;
+use crate::cri::security::Security;
 use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+use tokio::sync::Mutex;
 use tonic::transport::Channel;
 use tracing::debug;
 
@@ -12,13 +19,44 @@ pub async fn create_pod(
     name: String,
     namespace: String,
     uid: String,
-    options: String,
-) -> Result<(String, PodSandboxConfig), tonic::Status> {
+    options: &ConfigOptions<PodSandboxConfig>,
+    mode: ParseMode,
+    security: &Security,
+) -> Result<Parsed<(String, PodSandboxConfig)>, CreateError> {
     debug!(
         "Create pod request - name: {}, namespace: {}, uid: {}, options: {:?}",
         name, namespace, uid, options
     );
+    let Parsed {
+        value: pod_config,
+        warnings,
+    } = pod_config(name, namespace, uid, options, mode, security)?;
 
+    let request = RunPodSandboxRequest {
+        config: Some(pod_config.clone()),
+        runtime_handler: "".to_string(),
+    };
+    debug!("run pod sandbox request: {:?}", request);
+
+    let response = client.run_pod_sandbox(request).await?;
+    let pod_id = response.into_inner().pod_sandbox_id;
+
+    Ok(Parsed {
+        value: (pod_id, pod_config),
+        warnings,
+    })
+}
+
+/// Build the sandbox config of a pod from its options, with the security preset applied
+/// and checked against the minimum level
+pub fn pod_config(
+    name: String,
+    namespace: String,
+    uid: String,
+    options: &ConfigOptions<PodSandboxConfig>,
+    mode: ParseMode,
+    security: &Security,
+) -> Result<Parsed<PodSandboxConfig>, CreateError> {
     // Create a base config with required fields
     let mut pod_config_value = serde_json::json!({
         "metadata": {
@@ -32,25 +70,19 @@ pub async fn create_pod(
 
     // Merge the options
     if let Some(pod_obj) = pod_config_value.as_object_mut() {
-        let options_value = serde_json::from_str::<serde_json::Value>(&options).unwrap();
-        for (key, value) in options_value.as_object().unwrap() {
-            pod_obj.insert(key.clone(), value.clone());
-        }
+        pod_obj.extend(options.to_object()?);
     }
 
     // Parse pod configuration with defaults
-    let pod_config = parse_pod_config(pod_config_value);
-
-    let request = RunPodSandboxRequest {
-        config: Some(pod_config.clone()),
-        runtime_handler: "".to_string(),
-    };
-    debug!("run pod sandbox request: {:?}", request);
-
-    let response = client.run_pod_sandbox(request).await?;
-    let pod_id = response.into_inner().pod_sandbox_id;
-
-    Ok((pod_id, pod_config))
+    let Parsed {
+        value: mut pod_config,
+        warnings,
+    } = parse_pod_config(pod_config_value, mode)?;
+    security.apply_to_pod(&mut pod_config)?;
+    Ok(Parsed {
+        value: pod_config,
+        warnings,
+    })
 }
 
 pub async fn remove_pod(
@@ -79,23 +111,152 @@ pub async fn stop_pod(
 
 pub async fn list_pods(
     client: &mut crate::api::runtime::v1::RuntimeServiceClient<Channel>,
+    filter: Option<PodSandboxFilter>,
 ) -> Result<ListPodSandboxResponse, tonic::Status> {
-    let request = ListPodSandboxRequest { filter: None };
+    let request = ListPodSandboxRequest { filter };
+    debug!("list pod sandbox request: {:?}", request);
     let response = client.list_pod_sandbox(request).await?;
     Ok(response.into_inner())
 }
 
-pub async fn pod_stats(
+/// IDs of the pods whose name and namespace contain the given patterns
+pub async fn matching_pod_ids(
     client: &mut crate::api::runtime::v1::RuntimeServiceClient<Channel>,
-    pod_id: Option<String>,
-) -> Result<crate::api::runtime::v1::ListPodSandboxStatsResponse, tonic::Status> {
-    // Create filter if pod_id is provided
-    let filter = pod_id.map(|id| crate::api::runtime::v1::PodSandboxStatsFilter {
-        id,
-        label_selector: std::collections::HashMap::new(),
-    });
+    name: Option<&str>,
+    namespace: Option<&str>,
+) -> Result<HashSet<String>, tonic::Status> {
+    let response = list_pods(client, None).await?;
+    Ok(response
+        .items
+        .into_iter()
+        .filter(|pod| {
+            let metadata = pod.metadata.clone().unwrap_or_default();
+            contains(&metadata.name, name) && contains(&metadata.namespace, namespace)
+        })
+        .map(|pod| pod.id)
+        .collect())
+}
 
+/// Client-side substring match, a missing pattern matches everything
+pub fn contains(value: &str, pattern: Option<&str>) -> bool {
+    pattern.is_none_or(|pattern| value.contains(pattern))
+}
+
+/// Parse a pod state such as "ready", "notready" or "SANDBOX_NOTREADY"
+pub fn parse_pod_state(state: &str) -> Option<PodSandboxState> {
+    let name = state.trim().to_uppercase().replace(['-', '_'], "");
+    match name.strip_prefix("SANDBOX").unwrap_or(&name) {
+        "READY" => Some(PodSandboxState::SandboxReady),
+        "NOTREADY" => Some(PodSandboxState::SandboxNotready),
+        _ => None,
+    }
+}
+
+pub async fn pod_status(
+    client: &mut crate::api::runtime::v1::RuntimeServiceClient<Channel>,
+    pod_id: String,
+    verbose: bool,
+) -> Result<PodSandboxStatusResponse, tonic::Status> {
+    let request = PodSandboxStatusRequest {
+        pod_sandbox_id: pod_id,
+        verbose,
+    };
+    let response = client.pod_sandbox_status(request).await?;
+    Ok(response.into_inner())
+}
+
+/// Human readable name of a raw pod sandbox state value
+pub fn pod_state_name(state: i32) -> String {
+    PodSandboxState::try_from(state)
+        .map(|s| s.as_str_name().to_string())
+        .unwrap_or_else(|_| state.to_string())
+}
+
+pub async fn pod_stats(
+    client: &mut crate::api::runtime::v1::RuntimeServiceClient<Channel>,
+    filter: Option<PodSandboxStatsFilter>,
+) -> Result<ListPodSandboxStatsResponse, tonic::Status> {
     let request = ListPodSandboxStatsRequest { filter };
     let response = client.list_pod_sandbox_stats(request).await?;
     Ok(response.into_inner())
 }
+
+/// Sandbox configs by pod id. CreateContainer needs the config of the pod again for
+/// every container, so the configs of pods created here are kept, and those of pods
+/// created elsewhere are rebuilt from their status on first use.
+#[derive(Debug, Default)]
+pub struct PodConfigs {
+    configs: Mutex<HashMap<String, PodSandboxConfig>>,
+}
+
+impl PodConfigs {
+    pub async fn insert(&self, pod_id: String, config: PodSandboxConfig) {
+        self.configs.lock().await.insert(pod_id, config);
+    }
+
+    pub async fn remove(&self, pod_id: &str) {
+        self.configs.lock().await.remove(pod_id);
+    }
+
+    /// The sandbox config to create a container of the pod with: the one the caller gave,
+    /// else the known config of the pod, else the one rebuilt from its status
+    pub async fn resolve(
+        &self,
+        client: &mut crate::api::runtime::v1::RuntimeServiceClient<Channel>,
+        pod_id: &str,
+        given: Option<&ConfigOptions<PodSandboxConfig>>,
+        mode: ParseMode,
+    ) -> Result<Parsed<PodSandboxConfig>, CreateError> {
+        if let Some(given) = given {
+            let value = serde_json::Value::Object(given.to_object()?);
+            return Ok(parse_pod_config(value, mode)?);
+        }
+        if let Some(config) = self.configs.lock().await.get(pod_id) {
+            return Ok(Parsed {
+                value: config.clone(),
+                warnings: Vec::new(),
+            });
+        }
+        let status = pod_status(client, pod_id.to_string(), true).await?;
+        let config = config_from_status(&status);
+        debug!("rebuilt the sandbox config of pod {}: {:?}", pod_id, config);
+        self.insert(pod_id.to_string(), config.clone()).await;
+        Ok(Parsed {
+            value: config,
+            warnings: Vec::new(),
+        })
+    }
+}
+
+/// Rebuild the config of a pod from its verbose status.
+///
+/// containerd reports the config the pod was run with in the "info" entry. For other
+/// runtimes only the metadata, labels, annotations and namespace options are known.
+pub fn config_from_status(response: &PodSandboxStatusResponse) -> PodSandboxConfig {
+    let info = parse_info(&response.info);
+    if let Some(config) = info.get("info").and_then(|info| info.get("config")) {
+        match parse_pod_config(config.clone(), ParseMode::Lenient) {
+            Ok(parsed) => return parsed.value,
+            Err(e) => debug!("sandbox config in the pod status is not usable: {}", e),
+        }
+    }
+
+    let status = response.status.clone().unwrap_or_default();
+    let namespace_options = status
+        .linux
+        .and_then(|linux| linux.namespaces)
+        .and_then(|namespaces| namespaces.options);
+    PodSandboxConfig {
+        metadata: status.metadata,
+        labels: status.labels,
+        annotations: status.annotations,
+        linux: namespace_options.map(|options| LinuxPodSandboxConfig {
+            security_context: Some(LinuxSandboxSecurityContext {
+                namespace_options: Some(options),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
@@ -0,0 +1,305 @@
+use crate::api::runtime::v1::{
+    Container, ContainerState, Image, ImageServiceClient, PodSandboxState, RuntimeServiceClient,
+};
+use crate::cri::container::{
+    container_state_name, list_container_stats, list_containers, remove_container,
+};
+use crate::cri::image::{image_fs_info, list_images, remove_image};
+use crate::cri::pod::{list_pods, pod_state_name, remove_pod};
+use rmcp::schemars;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tonic::transport::Channel;
+use tracing::debug;
+
+/// Which images a prune removes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImagePrune {
+    None,
+    /// Images without any tag that no container uses
+    Dangling,
+    /// Every image no container uses
+    Unused,
+}
+
+/// Parse an image prune mode: none, dangling or unused
+pub fn parse_image_prune(mode: &str) -> Option<ImagePrune> {
+    match mode.trim().to_lowercase().as_str() {
+        "none" => Some(ImagePrune::None),
+        "dangling" => Some(ImagePrune::Dangling),
+        "unused" | "all" => Some(ImagePrune::Unused),
+        _ => None,
+    }
+}
+
+/// What a prune considers for removal
+#[derive(Debug)]
+pub struct PruneOptions {
+    pub containers: bool,
+    pub pods: bool,
+    pub images: ImagePrune,
+    /// Only prune containers and pods created longer ago than this
+    pub older_than: Option<Duration>,
+    /// Only prune containers and pods that have all of these labels
+    pub label_selector: HashMap<String, String>,
+}
+
+/// An exited container the prune removes
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct PrunedContainer {
+    pub id: String,
+    pub name: String,
+    pub pod_id: String,
+    pub image: String,
+    pub state: String,
+    /// Disk used by the writable layer of the container
+    pub writable_layer_bytes: u64,
+}
+
+/// A pod sandbox the prune removes, together with all of its containers
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct PrunedPod {
+    pub id: String,
+    pub name: String,
+    pub namespace: String,
+    pub uid: String,
+    pub state: String,
+    /// Containers of the pod, removed with it
+    pub containers: Vec<PrunedContainer>,
+}
+
+/// An image the prune removes
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct PrunedImage {
+    pub id: String,
+    pub tags: Vec<String>,
+    pub size: u64,
+}
+
+/// A removal of the plan that failed
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct PruneError {
+    /// Kind of the object: container, pod or image
+    pub kind: String,
+    pub id: String,
+    pub message: String,
+}
+
+/// The removals of a prune in execution order, and what they reclaim
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct PrunePlan {
+    /// Whether the plan was only computed, false when it was executed
+    pub dry_run: bool,
+    pub containers: Vec<PrunedContainer>,
+    pub pods: Vec<PrunedPod>,
+    pub images: Vec<PrunedImage>,
+    /// Writable layers and image sizes freed by the plan. Layers shared between removed
+    /// images are counted once per image, so this is an upper bound
+    pub reclaimable_bytes: u64,
+    /// Bytes used on the image filesystem before the prune
+    pub image_fs_used_bytes: Option<u64>,
+    /// Bytes used on the image filesystem after the prune, only set when it was executed
+    pub image_fs_used_bytes_after: Option<u64>,
+    /// Removals that failed, the rest of the plan is still executed
+    pub errors: Vec<PruneError>,
+}
+
+/// Work out which containers, pods and images to remove, and remove them unless `dry_run`.
+///
+/// Exited containers and not-ready pods go first, then the images no remaining container
+/// uses. Pods that still have a running container are kept. The age and label filters
+/// apply to containers and pods, CRI exposes neither for images.
+pub async fn prune(
+    runtime: &mut RuntimeServiceClient<Channel>,
+    images: &mut ImageServiceClient<Channel>,
+    options: &PruneOptions,
+    dry_run: bool,
+) -> Result<PrunePlan, tonic::Status> {
+    let pods = list_pods(runtime, None).await?.items;
+    let containers = list_containers(runtime, None).await?.containers;
+    let all_images = list_images(images, None).await?.images;
+    let writable_layers: HashMap<String, u64> = match list_container_stats(runtime, None).await {
+        Ok(response) => response
+            .stats
+            .into_iter()
+            .filter_map(|stats| {
+                let id = stats.attributes?.id;
+                let used = stats.writable_layer?.used_bytes?.value;
+                Some((id, used))
+            })
+            .collect(),
+        Err(e) => {
+            debug!("no writable layer sizes for prune: {}", e);
+            HashMap::new()
+        }
+    };
+    let pruned_container = |container: &Container| {
+        let metadata = container.metadata.clone().unwrap_or_default();
+        PrunedContainer {
+            id: container.id.clone(),
+            name: metadata.name,
+            pod_id: container.pod_sandbox_id.clone(),
+            image: container
+                .image
+                .as_ref()
+                .map(|image| image.image.clone())
+                .unwrap_or_default(),
+            state: container_state_name(container.state),
+            writable_layer_bytes: writable_layers.get(&container.id).copied().unwrap_or(0),
+        }
+    };
+
+    let cutoff = options.older_than.map(|older_than| {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        now.saturating_sub(older_than).as_nanos() as i64
+    });
+    let selected = |labels: &HashMap<String, String>, created_at: i64| {
+        cutoff.is_none_or(|cutoff| created_at < cutoff)
+            && options
+                .label_selector
+                .iter()
+                .all(|(key, value)| labels.get(key) == Some(value))
+    };
+
+    let mut pruned_pods = Vec::new();
+    if options.pods {
+        for pod in &pods {
+            let pod_containers: Vec<&Container> = containers
+                .iter()
+                .filter(|c| c.pod_sandbox_id == pod.id)
+                .collect();
+            let running = pod_containers
+                .iter()
+                .any(|c| c.state == ContainerState::ContainerRunning as i32);
+            if pod.state != PodSandboxState::SandboxNotready as i32
+                || running
+                || !selected(&pod.labels, pod.created_at)
+            {
+                continue;
+            }
+            let metadata = pod.metadata.clone().unwrap_or_default();
+            pruned_pods.push(PrunedPod {
+                id: pod.id.clone(),
+                name: metadata.name,
+                namespace: metadata.namespace,
+                uid: metadata.uid,
+                state: pod_state_name(pod.state),
+                containers: pod_containers.into_iter().map(&pruned_container).collect(),
+            });
+        }
+    }
+    let pruned_pod_ids: HashSet<&str> = pruned_pods.iter().map(|p| p.id.as_str()).collect();
+
+    let mut pruned_containers = Vec::new();
+    if options.containers {
+        pruned_containers = containers
+            .iter()
+            .filter(|c| {
+                c.state == ContainerState::ContainerExited as i32
+                    && !pruned_pod_ids.contains(c.pod_sandbox_id.as_str())
+                    && selected(&c.labels, c.created_at)
+            })
+            .map(&pruned_container)
+            .collect();
+    }
+
+    let mut pruned_images = Vec::new();
+    if options.images != ImagePrune::None {
+        let removed: HashSet<&str> = pruned_containers
+            .iter()
+            .chain(pruned_pods.iter().flat_map(|p| p.containers.iter()))
+            .map(|c| c.id.as_str())
+            .collect();
+        let mut used = HashSet::new();
+        for container in containers
+            .iter()
+            .filter(|c| !removed.contains(c.id.as_str()))
+        {
+            used.insert(container.image_ref.as_str());
+            if let Some(image) = &container.image {
+                used.insert(image.image.as_str());
+            }
+        }
+        let in_use = |image: &Image| {
+            used.contains(image.id.as_str())
+                || image
+                    .repo_tags
+                    .iter()
+                    .chain(image.repo_digests.iter())
+                    .any(|name| used.contains(name.as_str()))
+        };
+        pruned_images = all_images
+            .iter()
+            .filter(|image| {
+                !image.pinned
+                    && !in_use(image)
+                    && (options.images == ImagePrune::Unused || image.repo_tags.is_empty())
+            })
+            .map(|image| PrunedImage {
+                id: image.id.clone(),
+                tags: image.repo_tags.clone(),
+                size: image.size,
+            })
+            .collect();
+    }
+
+    let reclaimable_bytes = pruned_containers
+        .iter()
+        .chain(pruned_pods.iter().flat_map(|p| p.containers.iter()))
+        .map(|c| c.writable_layer_bytes)
+        .chain(pruned_images.iter().map(|i| i.size))
+        .sum();
+    let mut plan = PrunePlan {
+        dry_run,
+        containers: pruned_containers,
+        pods: pruned_pods,
+        images: pruned_images,
+        reclaimable_bytes,
+        image_fs_used_bytes: image_fs_used_bytes(images).await,
+        image_fs_used_bytes_after: None,
+        errors: Vec::new(),
+    };
+    if dry_run {
+        return Ok(plan);
+    }
+
+    let mut errors = Vec::new();
+    for container in &plan.containers {
+        if let Err(e) = remove_container(runtime, container.id.clone()).await {
+            errors.push(prune_error("container", &container.id, e));
+        }
+    }
+    for pod in &plan.pods {
+        if let Err(e) = remove_pod(runtime, pod.id.clone()).await {
+            errors.push(prune_error("pod", &pod.id, e));
+        }
+    }
+    for image in &plan.images {
+        if let Err(e) = remove_image(images, image.id.clone()).await {
+            errors.push(prune_error("image", &image.id, e));
+        }
+    }
+    plan.errors = errors;
+    plan.image_fs_used_bytes_after = image_fs_used_bytes(images).await;
+    Ok(plan)
+}
+
+fn prune_error(kind: &str, id: &str, e: tonic::Status) -> PruneError {
+    PruneError {
+        kind: kind.to_string(),
+        id: id.to_string(),
+        message: e.message().to_string(),
+    }
+}
+
+/// Total bytes used on the image filesystems, if the runtime reports them
+async fn image_fs_used_bytes(images: &mut ImageServiceClient<Channel>) -> Option<u64> {
+    let response = image_fs_info(images).await.ok()?;
+    response
+        .image_filesystems
+        .iter()
+        .map(|fs| fs.used_bytes.as_ref().map(|used| used.value))
+        .sum()
+}
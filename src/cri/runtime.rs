@@ -1,5 +1,10 @@
-use crate::api::runtime::v1::{VersionRequest, VersionResponse};
+use crate::api::runtime::v1::{
+    CgroupDriver, NetworkConfig, RuntimeConfig, RuntimeConfigRequest, RuntimeConfigResponse,
+    RuntimeStatus, StatusRequest, StatusResponse, UpdateRuntimeConfigRequest, VersionRequest,
+    VersionResponse,
+};
 use anyhow::Result;
+use std::collections::HashMap;
 use tonic::transport::Channel;
 
 pub async fn version(
@@ -11,3 +16,86 @@ pub async fn version(
     let response = client.version(request).await?;
     Ok(response.into_inner())
 }
+
+/// Conditions a runtime must report as true to accept containers
+pub const REQUIRED_CONDITIONS: [&str; 2] = ["RuntimeReady", "NetworkReady"];
+
+pub async fn status(
+    client: &mut crate::api::runtime::v1::RuntimeServiceClient<Channel>,
+    verbose: bool,
+) -> Result<StatusResponse, tonic::Status> {
+    let response = client.status(StatusRequest { verbose }).await?;
+    Ok(response.into_inner())
+}
+
+/// Whether every required runtime condition is present and true
+pub fn is_ready(status: &RuntimeStatus) -> bool {
+    REQUIRED_CONDITIONS.iter().all(|required| {
+        status
+            .conditions
+            .iter()
+            .any(|c| c.r#type == *required && c.status)
+    })
+}
+
+pub async fn runtime_config(
+    client: &mut crate::api::runtime::v1::RuntimeServiceClient<Channel>,
+) -> Result<RuntimeConfigResponse, tonic::Status> {
+    let response = client.runtime_config(RuntimeConfigRequest {}).await?;
+    Ok(response.into_inner())
+}
+
+/// Human readable name of a raw cgroup driver value
+pub fn cgroup_driver_name(driver: i32) -> String {
+    CgroupDriver::try_from(driver)
+        .map(|d| d.as_str_name().to_string())
+        .unwrap_or_else(|_| driver.to_string())
+}
+
+pub async fn update_runtime_config(
+    client: &mut crate::api::runtime::v1::RuntimeServiceClient<Channel>,
+    pod_cidr: String,
+) -> Result<(), tonic::Status> {
+    if !is_valid_cidr(&pod_cidr) {
+        return Err(tonic::Status::invalid_argument(format!(
+            "Invalid pod CIDR: {}, expected a network such as 10.244.0.0/16",
+            pod_cidr
+        )));
+    }
+
+    let request = UpdateRuntimeConfigRequest {
+        runtime_config: Some(RuntimeConfig {
+            network_config: Some(NetworkConfig { pod_cidr }),
+        }),
+    };
+    client.update_runtime_config(request).await?;
+    Ok(())
+}
+
+/// Accept one or more comma separated CIDRs, as dual-stack clusters pass both families
+fn is_valid_cidr(value: &str) -> bool {
+    value.split(',').all(|cidr| {
+        let Some((ip, prefix)) = cidr.trim().split_once('/') else {
+            return false;
+        };
+        match (ip.parse::<std::net::IpAddr>(), prefix.parse::<u8>()) {
+            (Ok(std::net::IpAddr::V4(_)), Ok(prefix)) => prefix <= 32,
+            (Ok(std::net::IpAddr::V6(_)), Ok(prefix)) => prefix <= 128,
+            _ => false,
+        }
+    })
+}
+
+/// Parse the verbose `info` map returned by CRI status calls.
+///
+/// Runtimes encode each value as a JSON document (containerd puts the runtime spec,
+/// sandbox pid, snapshot key, etc. there), values that are not valid JSON are kept as strings.
+pub fn parse_info(info: &HashMap<String, String>) -> serde_json::Map<String, serde_json::Value> {
+    info.iter()
+        .map(|(key, value)| {
+            let parsed = serde_json::from_str(value)
+                .unwrap_or_else(|_| serde_json::Value::String(value.clone()));
+            (key.clone(), parsed)
+        })
+        .collect()
+}
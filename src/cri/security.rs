@@ -0,0 +1,639 @@
+use crate::api::runtime::v1::{
+    security_profile::ProfileType, Capability, ContainerConfig, Int64Value,
+    LinuxContainerSecurityContext, LinuxSandboxSecurityContext, NamespaceMode, NamespaceOption,
+    PodSandboxConfig, SeLinuxOption, SecurityProfile,
+};
+use crate::cri::manifest::EMPTY_DIR_ROOT;
+use rmcp::schemars;
+use serde_json::{Map, Value};
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+
+/// Kubernetes Pod Security Standards level, from the least to the most restrictive. As a
+/// preset it fills the security fields a config leaves unset; configs below the minimum
+/// level of the server are rejected
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    serde::Deserialize,
+    schemars::JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum SecurityLevel {
+    /// No restrictions, the preset fills nothing
+    #[default]
+    Privileged,
+    /// No host namespaces, host ports, host paths, host or CDI devices, privileged mode or
+    /// extra capabilities. The preset sets the runtime default seccomp and AppArmor profiles
+    /// and the default masked /proc paths
+    Baseline,
+    /// Baseline, and running as non-root without privilege escalation, with every
+    /// capability dropped and a seccomp profile. The preset also sets a non-root user,
+    /// no_new_privs, drops every capability and makes the root filesystem read-only, each
+    /// unless the config sets it
+    Restricted,
+}
+
+impl SecurityLevel {
+    pub fn name(self) -> &'static str {
+        match self {
+            SecurityLevel::Privileged => "privileged",
+            SecurityLevel::Baseline => "baseline",
+            SecurityLevel::Restricted => "restricted",
+        }
+    }
+}
+
+impl fmt::Display for SecurityLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Parse a security level: privileged, baseline or restricted
+pub fn parse_security_level(level: &str) -> Option<SecurityLevel> {
+    match level.trim().to_lowercase().as_str() {
+        "privileged" => Some(SecurityLevel::Privileged),
+        "baseline" => Some(SecurityLevel::Baseline),
+        "restricted" => Some(SecurityLevel::Restricted),
+        _ => None,
+    }
+}
+
+/// Capabilities the baseline level allows adding, the default set of container runtimes
+const BASELINE_CAPABILITIES: &[&str] = &[
+    "AUDIT_WRITE",
+    "CHOWN",
+    "DAC_OVERRIDE",
+    "FOWNER",
+    "FSETID",
+    "KILL",
+    "MKNOD",
+    "NET_BIND_SERVICE",
+    "SETFCAP",
+    "SETGID",
+    "SETPCAP",
+    "SETUID",
+    "SYS_CHROOT",
+];
+
+/// Capabilities the restricted level allows adding
+const RESTRICTED_CAPABILITIES: &[&str] = &["NET_BIND_SERVICE"];
+
+/// Sysctls the baseline level allows, they are namespaced and cannot affect the host
+const SAFE_SYSCTLS: &[&str] = &[
+    "kernel.shm_rmid_forced",
+    "net.ipv4.ip_local_port_range",
+    "net.ipv4.ip_local_reserved_ports",
+    "net.ipv4.ip_unprivileged_port_start",
+    "net.ipv4.ping_group_range",
+    "net.ipv4.tcp_fin_timeout",
+    "net.ipv4.tcp_keepalive_intvl",
+    "net.ipv4.tcp_keepalive_probes",
+    "net.ipv4.tcp_keepalive_time",
+    "net.ipv4.tcp_syncookies",
+];
+
+/// SELinux types the baseline level allows
+const SELINUX_TYPES: &[&str] = &["", "container_t", "container_init_t", "container_kvm_t"];
+
+/// Paths the kubelet masks in containers that are not privileged
+const MASKED_PATHS: &[&str] = &[
+    "/proc/acpi",
+    "/proc/asound",
+    "/proc/interrupts",
+    "/proc/kcore",
+    "/proc/keys",
+    "/proc/latency_stats",
+    "/proc/sched_debug",
+    "/proc/scsi",
+    "/proc/timer_list",
+    "/proc/timer_stats",
+    "/sys/devices/virtual/powercap",
+    "/sys/firmware",
+];
+
+/// Paths the kubelet makes read-only in containers that are not privileged
+const READONLY_PATHS: &[&str] = &[
+    "/proc/bus",
+    "/proc/fs",
+    "/proc/irq",
+    "/proc/sys",
+    "/proc/sysrq-trigger",
+];
+
+/// User the restricted preset runs as when the config sets none, nobody
+const NON_ROOT_USER: i64 = 65534;
+
+/// A pod or container config that does not meet the required security level
+#[derive(Debug, Clone)]
+pub struct PolicyViolation {
+    pub level: SecurityLevel,
+    pub violations: Vec<String>,
+}
+
+impl fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "does not meet the {} security level: {}",
+            self.level,
+            self.violations.join("; ")
+        )
+    }
+}
+
+impl std::error::Error for PolicyViolation {}
+
+/// Container security fields the options of a config set explicitly, which the preset keeps
+/// even when they hold the protobuf default
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExplicitFields {
+    pub no_new_privs: bool,
+    pub readonly_rootfs: bool,
+}
+
+impl ExplicitFields {
+    /// The fields the `linux.security_context` of container options sets
+    pub fn of(options: &Map<String, Value>) -> Self {
+        let context = options
+            .get("linux")
+            .and_then(|linux| linux.get("security_context"));
+        let set = |key: &str| {
+            context
+                .and_then(|c| c.get(key))
+                .is_some_and(|v| !v.is_null())
+        };
+        Self {
+            no_new_privs: set("no_new_privs"),
+            readonly_rootfs: set("readonly_rootfs"),
+        }
+    }
+}
+
+/// Security of a pod or container to create: the preset filling the security fields its
+/// config leaves unset, and the level the result must meet
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Security {
+    /// Preset to apply, the minimum level when none is given
+    pub preset: Option<SecurityLevel>,
+    pub minimum: SecurityLevel,
+}
+
+impl Security {
+    fn preset(&self) -> SecurityLevel {
+        self.preset.unwrap_or(self.minimum)
+    }
+
+    /// Apply the preset to a sandbox config and check it against the minimum level
+    pub fn apply_to_pod(&self, config: &mut PodSandboxConfig) -> Result<(), PolicyViolation> {
+        let preset = self.preset();
+        if preset > SecurityLevel::Privileged {
+            let linux = config.linux.get_or_insert_with(Default::default);
+            let context = linux.security_context.get_or_insert_with(Default::default);
+            fill_sandbox_context(context, preset);
+        }
+        check(self.minimum, pod_violations(config, self.minimum))
+    }
+
+    /// Apply the preset to a container config of the pod with `pod_uid`, keeping the
+    /// `explicit` fields, and check it against the minimum level
+    pub async fn apply_to_container(
+        &self,
+        config: &mut ContainerConfig,
+        explicit: ExplicitFields,
+        pod_uid: &str,
+    ) -> Result<(), PolicyViolation> {
+        let preset = self.preset();
+        if preset > SecurityLevel::Privileged {
+            let linux = config.linux.get_or_insert_with(Default::default);
+            let context = linux.security_context.get_or_insert_with(Default::default);
+            fill_container_context(context, preset, explicit);
+        }
+        let mut violations = container_violations(config, self.minimum);
+        if self.minimum > SecurityLevel::Privileged {
+            for mount in config.mounts.iter().filter(|m| !m.host_path.is_empty()) {
+                if !in_pod_volumes(&mount.host_path, Path::new(EMPTY_DIR_ROOT), pod_uid).await {
+                    violations.push(format!("host path {} is mounted", mount.host_path));
+                }
+            }
+        }
+        check(self.minimum, violations)
+    }
+}
+
+fn check(level: SecurityLevel, violations: Vec<String>) -> Result<(), PolicyViolation> {
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(PolicyViolation { level, violations })
+    }
+}
+
+fn runtime_default() -> Option<SecurityProfile> {
+    Some(SecurityProfile {
+        profile_type: ProfileType::RuntimeDefault as i32,
+        localhost_ref: String::new(),
+    })
+}
+
+// seccomp_profile_path and apparmor_profile are deprecated, but runtimes still honor them
+#[allow(deprecated)]
+fn fill_sandbox_context(context: &mut LinuxSandboxSecurityContext, preset: SecurityLevel) {
+    if context.seccomp.is_none() && context.seccomp_profile_path.is_empty() {
+        context.seccomp = runtime_default();
+    }
+    if context.apparmor.is_none() {
+        context.apparmor = runtime_default();
+    }
+    if preset == SecurityLevel::Restricted && context.run_as_user.is_none() {
+        context.run_as_user = Some(Int64Value {
+            value: NON_ROOT_USER,
+        });
+    }
+}
+
+#[allow(deprecated)]
+fn fill_container_context(
+    context: &mut LinuxContainerSecurityContext,
+    preset: SecurityLevel,
+    explicit: ExplicitFields,
+) {
+    if context.seccomp.is_none() && context.seccomp_profile_path.is_empty() {
+        context.seccomp = runtime_default();
+    }
+    if context.apparmor.is_none() && context.apparmor_profile.is_empty() {
+        context.apparmor = runtime_default();
+    }
+    if context.masked_paths.is_empty() {
+        context.masked_paths = MASKED_PATHS.iter().map(|p| p.to_string()).collect();
+    }
+    if context.readonly_paths.is_empty() {
+        context.readonly_paths = READONLY_PATHS.iter().map(|p| p.to_string()).collect();
+    }
+    if preset < SecurityLevel::Restricted {
+        return;
+    }
+    // an explicit no_new_privs of false is left for the check to reject
+    if !explicit.no_new_privs {
+        context.no_new_privs = true;
+    }
+    if !explicit.readonly_rootfs {
+        context.readonly_rootfs = true;
+    }
+    let capabilities = context.capabilities.get_or_insert_with(Capability::default);
+    if capabilities.drop_capabilities.is_empty() {
+        capabilities.drop_capabilities = vec!["ALL".to_string()];
+    }
+    if context.run_as_user.is_none() && context.run_as_username.is_empty() {
+        context.run_as_user = Some(Int64Value {
+            value: NON_ROOT_USER,
+        });
+    }
+}
+
+/// Why a sandbox config does not meet `level`
+#[allow(deprecated)]
+fn pod_violations(config: &PodSandboxConfig, level: SecurityLevel) -> Vec<String> {
+    let mut violations = Vec::new();
+    if level == SecurityLevel::Privileged {
+        return violations;
+    }
+    let linux = config.linux.clone().unwrap_or_default();
+    let context = linux.security_context.unwrap_or_default();
+    if context.privileged {
+        violations.push("the sandbox is privileged".to_string());
+    }
+    host_namespaces(&context.namespace_options, "the sandbox", &mut violations);
+    if is_unconfined(&context.seccomp, &context.seccomp_profile_path) {
+        violations.push("the sandbox seccomp profile is unconfined".to_string());
+    }
+    if is_unconfined(&context.apparmor, "") {
+        violations.push("the sandbox AppArmor profile is unconfined".to_string());
+    }
+    selinux(&context.selinux_options, "the sandbox", &mut violations);
+    let mut sysctls: Vec<&String> = linux
+        .sysctls
+        .keys()
+        .filter(|name| !SAFE_SYSCTLS.contains(&name.as_str()))
+        .collect();
+    sysctls.sort();
+    for name in sysctls {
+        violations.push(format!("sysctl {} is not in the safe set", name));
+    }
+    for port in config.port_mappings.iter().filter(|p| p.host_port != 0) {
+        violations.push(format!("host port {} is used", port.host_port));
+    }
+
+    if level == SecurityLevel::Restricted && context.run_as_user.is_some_and(|u| u.value == 0) {
+        violations.push("the sandbox runs as root".to_string());
+    }
+    violations
+}
+
+/// Why a container config does not meet `level`
+#[allow(deprecated)]
+fn container_violations(config: &ContainerConfig, level: SecurityLevel) -> Vec<String> {
+    let mut violations = Vec::new();
+    if level == SecurityLevel::Privileged {
+        return violations;
+    }
+    let context = config
+        .linux
+        .clone()
+        .and_then(|linux| linux.security_context)
+        .unwrap_or_default();
+    let capabilities = context.capabilities.clone().unwrap_or_default();
+    if context.privileged {
+        violations.push("the container is privileged".to_string());
+    }
+    host_namespaces(&context.namespace_options, "the container", &mut violations);
+    if is_unconfined(&context.seccomp, &context.seccomp_profile_path) {
+        violations.push("the seccomp profile is unconfined".to_string());
+    }
+    if is_unconfined(&context.apparmor, &context.apparmor_profile) {
+        violations.push("the AppArmor profile is unconfined".to_string());
+    }
+    selinux(&context.selinux_options, "the container", &mut violations);
+    if !context.privileged {
+        // the default proc mount, anything less unmasks /proc/kcore and friends
+        for (kind, defaults, paths) in [
+            ("masked", MASKED_PATHS, &context.masked_paths),
+            ("read-only", READONLY_PATHS, &context.readonly_paths),
+        ] {
+            let missing: Vec<&str> = defaults
+                .iter()
+                .copied()
+                .filter(|path| !paths.iter().any(|p| p == path))
+                .collect();
+            if !missing.is_empty() {
+                violations.push(format!(
+                    "default {} paths are missing: {}",
+                    kind,
+                    missing.join(", ")
+                ));
+            }
+        }
+    }
+    let allowed = match level {
+        SecurityLevel::Restricted => RESTRICTED_CAPABILITIES,
+        _ => BASELINE_CAPABILITIES,
+    };
+    for capability in capabilities
+        .add_capabilities
+        .iter()
+        .chain(capabilities.add_ambient_capabilities.iter())
+    {
+        if !allowed.contains(&capability_name(capability).as_str()) {
+            violations.push(format!("capability {} is added", capability));
+        }
+    }
+    for device in &config.devices {
+        violations.push(format!("host device {} is exposed", device.host_path));
+    }
+    for device in &config.cdi_devices {
+        violations.push(format!("CDI device {} is exposed", device.name));
+    }
+    if level < SecurityLevel::Restricted {
+        return violations;
+    }
+
+    if !context.no_new_privs {
+        violations.push("privilege escalation is allowed, no_new_privs is not set".to_string());
+    }
+    match (&context.run_as_user, context.run_as_username.as_str()) {
+        (Some(user), _) if user.value == 0 => violations.push("runs as root".to_string()),
+        (None, "root") => violations.push("runs as root".to_string()),
+        (None, "") => violations.push("no non-root user is set".to_string()),
+        _ => {}
+    }
+    let seccomp_set = context.seccomp.is_some() || !context.seccomp_profile_path.is_empty();
+    if !seccomp_set {
+        violations.push("no seccomp profile is set".to_string());
+    }
+    if !capabilities
+        .drop_capabilities
+        .iter()
+        .any(|capability| capability_name(capability) == "ALL")
+    {
+        violations.push("not every capability is dropped, drop ALL".to_string());
+    }
+    violations
+}
+
+fn host_namespaces(options: &Option<NamespaceOption>, what: &str, violations: &mut Vec<String>) {
+    let Some(options) = options else {
+        return;
+    };
+    let node = NamespaceMode::Node as i32;
+    for (namespace, mode) in [
+        ("network", options.network),
+        ("PID", options.pid),
+        ("IPC", options.ipc),
+    ] {
+        if mode == node {
+            violations.push(format!("{} uses the host {} namespace", what, namespace));
+        }
+    }
+}
+
+fn selinux(options: &Option<SeLinuxOption>, what: &str, violations: &mut Vec<String>) {
+    let Some(options) = options else {
+        return;
+    };
+    if !SELINUX_TYPES.contains(&options.r#type.as_str()) {
+        violations.push(format!("{} uses SELinux type {}", what, options.r#type));
+    }
+    if !options.user.is_empty() || !options.role.is_empty() {
+        violations.push(format!("{} sets a custom SELinux user or role", what));
+    }
+}
+
+/// Whether a host path resolves, symlinks included, into an emptyDir volume the server
+/// created under `root` for the pod with `uid`. A path with a `..` component never does.
+///
+/// The runtime resolves the path again when it mounts it. A running container of the pod
+/// that replaces a directory of its volume with a symlink in between can still redirect the
+/// mount of a container created later, the check does not hold against such a race.
+async fn in_pod_volumes(host_path: &str, root: &Path, uid: &str) -> bool {
+    let mut components = Path::new(uid).components();
+    if !matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(_)), None)
+    ) {
+        return false;
+    }
+    let (Some(volumes), Some(path)) = (
+        resolve(&root.join(uid).join("volumes")).await,
+        resolve(Path::new(host_path)).await,
+    ) else {
+        return false;
+    };
+    path.strip_prefix(&volumes)
+        .is_ok_and(|inside| inside.components().next().is_some())
+}
+
+/// Resolve the symlinks of an absolute path without `..` components. The part of the path
+/// that does not exist yet is kept as it is, a dangling symlink fails since the runtime
+/// would create its target
+async fn resolve(path: &Path) -> Option<PathBuf> {
+    if !path.is_absolute() || path.components().any(|c| c == Component::ParentDir) {
+        return None;
+    }
+    let mut existing = path.to_path_buf();
+    let mut missing = Vec::new();
+    loop {
+        match tokio::fs::canonicalize(&existing).await {
+            Ok(mut resolved) => {
+                resolved.extend(missing.iter().rev());
+                return Some(resolved);
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                if tokio::fs::symlink_metadata(&existing).await.is_ok() {
+                    return None;
+                }
+                missing.push(existing.file_name()?.to_os_string());
+                existing.pop();
+            }
+            Err(_) => return None,
+        }
+    }
+}
+
+/// Whether a profile, or its deprecated path form, is explicitly unconfined
+fn is_unconfined(profile: &Option<SecurityProfile>, path: &str) -> bool {
+    match profile {
+        Some(profile) => profile.profile_type == ProfileType::Unconfined as i32,
+        None => path == "unconfined",
+    }
+}
+
+/// Capability name without the CAP_ prefix, in upper case
+fn capability_name(capability: &str) -> String {
+    let name = capability.trim().to_uppercase();
+    name.strip_prefix("CAP_").unwrap_or(&name).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A volume root holding a `data` volume for the pods `pod-a` and `pod-b`
+    fn volume_root() -> PathBuf {
+        let root = std::env::temp_dir().join(format!("mcp-containerd-{}", uuid::Uuid::new_v4()));
+        for uid in ["pod-a", "pod-b"] {
+            std::fs::create_dir_all(root.join(uid).join("volumes/data")).unwrap();
+        }
+        root
+    }
+
+    fn path(root: &Path, rest: &str) -> String {
+        format!("{}/{}", root.display(), rest)
+    }
+
+    #[test]
+    fn keeps_explicit_fields_in_the_restricted_preset() {
+        let options = serde_json::json!({
+            "linux": { "security_context": { "readonly_rootfs": false } }
+        });
+        let explicit = ExplicitFields::of(options.as_object().unwrap());
+        assert!(explicit.readonly_rootfs && !explicit.no_new_privs);
+
+        let mut context = LinuxContainerSecurityContext::default();
+        fill_container_context(&mut context, SecurityLevel::Restricted, explicit);
+        assert!(!context.readonly_rootfs);
+        assert!(context.no_new_privs);
+
+        let mut context = LinuxContainerSecurityContext::default();
+        fill_container_context(&mut context, SecurityLevel::Restricted, Default::default());
+        assert!(context.readonly_rootfs);
+    }
+
+    #[tokio::test]
+    async fn allows_the_volumes_of_the_pod() {
+        let root = volume_root();
+        for rest in [
+            "pod-a/volumes/data",
+            "pod-a/volumes/data/sub",
+            "pod-a/./volumes/data",
+        ] {
+            assert!(
+                in_pod_volumes(&path(&root, rest), &root, "pod-a").await,
+                "{}",
+                rest
+            );
+        }
+        assert!(!in_pod_volumes(&path(&root, "pod-a/volumes"), &root, "pod-a").await);
+        assert!(!in_pod_volumes(&path(&root, "pod-a"), &root, "pod-a").await);
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn rejects_parent_components() {
+        let root = volume_root();
+        for rest in [
+            "pod-a/volumes/data/..",
+            "pod-a/volumes/data/../data",
+            "pod-a/volumes/data/../../../pod-b/volumes/data",
+            "pod-a/volumes/data/../../../../etc",
+        ] {
+            assert!(
+                !in_pod_volumes(&path(&root, rest), &root, "pod-a").await,
+                "{}",
+                rest
+            );
+        }
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn follows_symlinks() {
+        let root = volume_root();
+        let data = root.join("pod-a/volumes/data");
+        std::os::unix::fs::symlink("/", data.join("host")).unwrap();
+        std::os::unix::fs::symlink(root.join("pod-b/volumes/data"), data.join("other")).unwrap();
+        std::os::unix::fs::symlink("/nonexistent", data.join("dangling")).unwrap();
+        std::fs::create_dir(data.join("sub")).unwrap();
+        std::os::unix::fs::symlink(data.join("sub"), data.join("local")).unwrap();
+        for rest in [
+            "pod-a/volumes/data/host",
+            "pod-a/volumes/data/host/etc",
+            "pod-a/volumes/data/other",
+            "pod-a/volumes/data/dangling",
+            "pod-a/volumes/data/dangling/sub",
+        ] {
+            assert!(
+                !in_pod_volumes(&path(&root, rest), &root, "pod-a").await,
+                "{}",
+                rest
+            );
+        }
+        assert!(in_pod_volumes(&path(&root, "pod-a/volumes/data/local"), &root, "pod-a").await);
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn rejects_the_volumes_of_other_pods() {
+        let root = volume_root();
+        let other = path(&root, "pod-b/volumes/data");
+        assert!(!in_pod_volumes(&other, &root, "pod-a").await);
+        assert!(in_pod_volumes(&other, &root, "pod-b").await);
+        for uid in ["", ".", "..", "pod-b/volumes/..", "pod-c"] {
+            assert!(!in_pod_volumes(&other, &root, uid).await, "{}", uid);
+        }
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn rejects_relative_paths() {
+        let root = volume_root();
+        assert!(!in_pod_volumes("pod-a/volumes/data", &root, "pod-a").await);
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,454 @@
+use crate::api::runtime::v1::{
+    AuthConfig, ContainerConfig, ImageServiceClient, KeyValue, Mount, MountPropagation,
+    PodSandboxConfig, PortMapping, Protocol, RuntimeServiceClient,
+};
+use crate::cri::config::{ConfigIssue, ConfigOptions, ParseMode, Parsed};
+use crate::cri::container::{
+    container_config, create_container, remove_container, start_container,
+};
+use crate::cri::image::{image_status, pull_image};
+use crate::cri::pod::{create_pod, pod_config, remove_pod, stop_pod, PodConfigs};
+use crate::cri::security::Security;
+use rmcp::schemars;
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use tonic::transport::Channel;
+use tracing::{debug, warn};
+
+/// A pod sandbox and the containers to run in it
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct PodSpec {
+    #[schemars(description = "Name of the pod")]
+    pub name: String,
+    #[schemars(description = "Namespace of the pod (default \"default\")")]
+    pub namespace: Option<String>,
+    #[schemars(description = "UID of the pod (default a random UUID)")]
+    pub uid: Option<String>,
+    #[schemars(description = "Labels of the pod")]
+    pub labels: Option<HashMap<String, String>>,
+    #[schemars(description = "Annotations of the pod")]
+    pub annotations: Option<HashMap<String, String>>,
+    #[schemars(description = "Ports of the pod to publish on the host")]
+    pub ports: Option<Vec<PortSpec>>,
+    #[schemars(
+        description = "Further PodSandboxConfig fields such as hostname, dns_config or linux, merged over the ones above"
+    )]
+    pub options: Option<ConfigOptions<PodSandboxConfig>>,
+    #[schemars(description = "Containers to run in the pod, started in this order")]
+    pub containers: Vec<ContainerSpec>,
+}
+
+/// A port of the pod published on the host
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct PortSpec {
+    #[schemars(description = "Port inside the pod")]
+    pub container_port: i32,
+    #[schemars(description = "Port on the host (default the container port)")]
+    pub host_port: Option<i32>,
+    #[schemars(description = "Protocol: TCP, UDP or SCTP (default TCP)")]
+    pub protocol: Option<String>,
+    #[schemars(description = "Host IP to bind to (default all addresses)")]
+    pub host_ip: Option<String>,
+}
+
+/// A container of a pod
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct ContainerSpec {
+    #[schemars(description = "Name of the container, unique within the pod")]
+    pub name: String,
+    #[schemars(description = "Image reference, pulled when it is not present")]
+    pub image: String,
+    #[schemars(description = "Entrypoint, replacing the one of the image")]
+    pub command: Option<Vec<String>>,
+    #[schemars(description = "Arguments to the entrypoint, replacing the cmd of the image")]
+    pub args: Option<Vec<String>>,
+    #[schemars(description = "Environment variables")]
+    pub env: Option<HashMap<String, String>>,
+    #[schemars(description = "Host paths to mount into the container")]
+    pub mounts: Option<Vec<MountSpec>>,
+    #[schemars(description = "Working directory (default the one of the image)")]
+    pub working_dir: Option<String>,
+    #[schemars(
+        description = "Further ContainerConfig fields such as labels or linux, merged over the ones above"
+    )]
+    pub options: Option<ConfigOptions<ContainerConfig>>,
+}
+
+/// A host path mounted into a container
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct MountSpec {
+    #[schemars(description = "Path on the host")]
+    pub host_path: String,
+    #[schemars(description = "Path inside the container")]
+    pub container_path: String,
+    #[schemars(description = "Mount read only (default false)")]
+    pub readonly: Option<bool>,
+    #[schemars(
+        description = "Mount propagation: private, host_to_container or bidirectional (default private)"
+    )]
+    pub propagation: Option<String>,
+}
+
+/// A container started by run_pod
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct StartedContainer {
+    pub name: String,
+    pub id: String,
+    pub image: String,
+}
+
+/// A pod started by run_pod
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct RunningPod {
+    pub pod_id: String,
+    pub name: String,
+    pub namespace: String,
+    pub uid: String,
+    /// Images that were not present and got pulled
+    pub pulled_images: Vec<String>,
+    pub containers: Vec<StartedContainer>,
+    /// Unknown keys in the options of the pod and its containers, which were ignored
+    pub warnings: Vec<ConfigIssue>,
+}
+
+/// Why run_pod failed, and what it removed again
+#[derive(Debug, serde::Serialize)]
+pub struct RunPodFailure {
+    pub message: String,
+    /// Containers, the pod sandbox and the volume directories removed by the rollback. Pulled
+    /// images are kept
+    pub rolled_back: Vec<String>,
+    /// Removals of the rollback that failed, these objects have to be removed by hand
+    pub rollback_errors: Vec<String>,
+}
+
+impl RunPodFailure {
+    pub fn new(message: String) -> Self {
+        Self {
+            message,
+            rolled_back: Vec::new(),
+            rollback_errors: Vec::new(),
+        }
+    }
+}
+
+/// Check a pod spec before anything is created
+pub fn validate_pod_spec(spec: &PodSpec) -> Result<(), String> {
+    if spec.name.is_empty() {
+        return Err("Pod name must not be empty".to_string());
+    }
+    if spec.containers.is_empty() {
+        return Err(format!("Pod '{}' has no containers", spec.name));
+    }
+    let mut names = HashSet::new();
+    for container in &spec.containers {
+        if container.name.is_empty() {
+            return Err("Container name must not be empty".to_string());
+        }
+        if container.image.is_empty() {
+            return Err(format!("Container '{}' has no image", container.name));
+        }
+        if !names.insert(container.name.as_str()) {
+            return Err(format!("Container name '{}' is used twice", container.name));
+        }
+        for mount in container.mounts.iter().flatten() {
+            parse_propagation(mount.propagation.as_deref())?;
+        }
+    }
+    for port in spec.ports.iter().flatten() {
+        parse_protocol(port.protocol.as_deref())?;
+    }
+    Ok(())
+}
+
+/// Check that the pod and every container of a spec can be built and meet the security
+/// level, without creating anything. The spec needs a uid, which host paths are checked with
+pub async fn check_pod_spec(
+    spec: &PodSpec,
+    mode: ParseMode,
+    security: &Security,
+) -> Result<(), String> {
+    validate_pod_spec(spec)?;
+    let uid = spec.uid.clone().unwrap_or_default();
+    pod_config(
+        spec.name.clone(),
+        spec.namespace
+            .clone()
+            .unwrap_or_else(|| "default".to_string()),
+        uid.clone(),
+        &pod_options(spec)?,
+        mode,
+        security,
+    )
+    .map_err(|e| format!("Invalid pod sandbox: {}", e))?;
+    for container in &spec.containers {
+        container_config(
+            container.name.clone(),
+            container.image.clone(),
+            &container_options(container)?,
+            &uid,
+            mode,
+            security,
+        )
+        .await
+        .map_err(|e| format!("Invalid container {}: {}", container.name, e))?;
+    }
+    Ok(())
+}
+
+fn parse_protocol(protocol: Option<&str>) -> Result<Protocol, String> {
+    match protocol.map(|p| p.to_uppercase()).as_deref() {
+        None | Some("TCP") => Ok(Protocol::Tcp),
+        Some("UDP") => Ok(Protocol::Udp),
+        Some("SCTP") => Ok(Protocol::Sctp),
+        Some(other) => Err(format!(
+            "Invalid protocol '{}', expected TCP, UDP or SCTP",
+            other
+        )),
+    }
+}
+
+fn parse_propagation(propagation: Option<&str>) -> Result<MountPropagation, String> {
+    match propagation.map(|p| p.to_lowercase()).as_deref() {
+        None | Some("private") => Ok(MountPropagation::PropagationPrivate),
+        Some("host_to_container") => Ok(MountPropagation::PropagationHostToContainer),
+        Some("bidirectional") => Ok(MountPropagation::PropagationBidirectional),
+        Some(other) => Err(format!(
+            "Invalid mount propagation '{}', expected private, host_to_container or bidirectional",
+            other
+        )),
+    }
+}
+
+/// Options for `create_pod` built from the spec, with its own options merged over them
+fn pod_options(spec: &PodSpec) -> Result<ConfigOptions<PodSandboxConfig>, String> {
+    let mut port_mappings = Vec::new();
+    for port in spec.ports.iter().flatten() {
+        port_mappings.push(PortMapping {
+            protocol: parse_protocol(port.protocol.as_deref())? as i32,
+            container_port: port.container_port,
+            host_port: port.host_port.unwrap_or(port.container_port),
+            host_ip: port.host_ip.clone().unwrap_or_default(),
+        });
+    }
+    let options = serde_json::json!({
+        "labels": spec.labels.clone().unwrap_or_default(),
+        "annotations": spec.annotations.clone().unwrap_or_default(),
+        "port_mappings": port_mappings,
+    });
+    merge(options, spec.options.as_ref())
+}
+
+/// Options for `create_container` built from the spec, with its own options merged over them
+fn container_options(spec: &ContainerSpec) -> Result<ConfigOptions<ContainerConfig>, String> {
+    let mut env: Vec<(&String, &String)> = spec.env.iter().flatten().collect();
+    env.sort();
+    let envs: Vec<KeyValue> = env
+        .into_iter()
+        .map(|(key, value)| KeyValue {
+            key: key.clone(),
+            value: value.clone(),
+        })
+        .collect();
+    let mut mounts = Vec::new();
+    for mount in spec.mounts.iter().flatten() {
+        mounts.push(Mount {
+            container_path: mount.container_path.clone(),
+            host_path: mount.host_path.clone(),
+            readonly: mount.readonly.unwrap_or(false),
+            propagation: parse_propagation(mount.propagation.as_deref())? as i32,
+            ..Default::default()
+        });
+    }
+    let mut options = serde_json::json!({
+        "envs": envs,
+        "mounts": mounts,
+    });
+    let object = options.as_object_mut().expect("options are an object");
+    if let Some(command) = &spec.command {
+        object.insert("command".to_string(), serde_json::json!(command));
+    }
+    if let Some(args) = &spec.args {
+        object.insert("args".to_string(), serde_json::json!(args));
+    }
+    // an empty working dir lets the runtime use the one of the image
+    object.insert(
+        "working_dir".to_string(),
+        serde_json::json!(spec.working_dir.clone().unwrap_or_default()),
+    );
+    merge(options, spec.options.as_ref())
+}
+
+/// Merge the options given in the spec over the ones built from its other fields
+fn merge<T>(built: Value, options: Option<&ConfigOptions<T>>) -> Result<ConfigOptions<T>, String> {
+    let mut built = match built {
+        Value::Object(map) => map,
+        _ => Map::new(),
+    };
+    if let Some(options) = options {
+        built.extend(options.to_object().map_err(|e| e.to_string())?);
+    }
+    Ok(built.into())
+}
+
+/// Run a pod with all of its containers.
+///
+/// Missing images are pulled first, with the credentials `auth` finds for them. Then the
+/// sandbox is run and each container is created and started in order. When a step fails,
+/// the containers created so far and the sandbox are removed again. `mode` decides whether
+/// unknown keys in the options are errors or warnings, `security` the preset applied to
+/// the pod and its containers and the level they must meet. The config of a running pod
+/// is added to `pod_configs`.
+pub async fn run_pod(
+    runtime: &mut RuntimeServiceClient<Channel>,
+    images: &mut ImageServiceClient<Channel>,
+    pod_configs: &PodConfigs,
+    spec: &PodSpec,
+    mode: ParseMode,
+    security: &Security,
+    auth: impl Fn(&str) -> Option<AuthConfig>,
+) -> Result<RunningPod, RunPodFailure> {
+    validate_pod_spec(spec).map_err(RunPodFailure::new)?;
+    let options = pod_options(spec).map_err(RunPodFailure::new)?;
+
+    let mut pulled_images = Vec::new();
+    for container in &spec.containers {
+        let image = &container.image;
+        if pulled_images.contains(image) {
+            continue;
+        }
+        let present = image_status(images, image.clone(), false)
+            .await
+            .map_err(|e| RunPodFailure::new(format!("Failed to inspect image {}: {}", image, e)))?
+            .image
+            .is_some();
+        if present {
+            continue;
+        }
+        debug!("pulling image {} for pod {}", image, spec.name);
+        pull_image(images, image.clone(), auth(image))
+            .await
+            .map_err(|e| RunPodFailure::new(format!("Failed to pull image {}: {}", image, e)))?;
+        pulled_images.push(image.clone());
+    }
+
+    let namespace = spec
+        .namespace
+        .clone()
+        .unwrap_or_else(|| "default".to_string());
+    let uid = spec
+        .uid
+        .clone()
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let Parsed {
+        value: (pod_id, pod_config),
+        warnings,
+    } = create_pod(
+        runtime,
+        spec.name.clone(),
+        namespace.clone(),
+        uid.clone(),
+        &options,
+        mode,
+        security,
+    )
+    .await
+    .map_err(|e| RunPodFailure::new(format!("Failed to run pod sandbox: {}", e)))?;
+    let mut warnings: Vec<ConfigIssue> = warnings
+        .into_iter()
+        .map(|w| relocate(w, "$.options"))
+        .collect();
+
+    let mut containers: Vec<StartedContainer> = Vec::new();
+    for (index, container) in spec.containers.iter().enumerate() {
+        let options = match container_options(container) {
+            Ok(options) => options,
+            Err(message) => return Err(rollback(runtime, &pod_id, &containers, message).await),
+        };
+        let created = create_container(
+            runtime,
+            pod_id.clone(),
+            container.name.clone(),
+            container.image.clone(),
+            &options,
+            pod_config.clone(),
+            mode,
+            security,
+        )
+        .await;
+        let id = match created {
+            Ok(created) => {
+                let prefix = format!("$.containers[{}].options", index);
+                warnings.extend(created.warnings.into_iter().map(|w| relocate(w, &prefix)));
+                created.value
+            }
+            Err(e) => {
+                let message = format!("Failed to create container {}: {}", container.name, e);
+                return Err(rollback(runtime, &pod_id, &containers, message).await);
+            }
+        };
+        containers.push(StartedContainer {
+            name: container.name.clone(),
+            id: id.clone(),
+            image: container.image.clone(),
+        });
+        if let Err(e) = start_container(runtime, id).await {
+            let message = format!("Failed to start container {}: {}", container.name, e);
+            return Err(rollback(runtime, &pod_id, &containers, message).await);
+        }
+    }
+
+    pod_configs.insert(pod_id.clone(), pod_config).await;
+    Ok(RunningPod {
+        pod_id,
+        name: spec.name.clone(),
+        namespace,
+        uid,
+        pulled_images,
+        containers,
+        warnings,
+    })
+}
+
+/// Point an issue found in the options built from the spec at the spec field it came from
+fn relocate(mut issue: ConfigIssue, prefix: &str) -> ConfigIssue {
+    if let Some(rest) = issue.path.strip_prefix('$') {
+        issue.path = format!("{}{}", prefix, rest);
+    }
+    issue
+}
+
+/// Remove the containers created so far and the pod sandbox, in that order
+async fn rollback(
+    runtime: &mut RuntimeServiceClient<Channel>,
+    pod_id: &str,
+    containers: &[StartedContainer],
+    message: String,
+) -> RunPodFailure {
+    warn!("{}, rolling back pod {}", message, pod_id);
+    let mut failure = RunPodFailure::new(message);
+    for container in containers.iter().rev() {
+        match remove_container(runtime, container.id.clone()).await {
+            Ok(()) => failure
+                .rolled_back
+                .push(format!("container {} ({})", container.name, container.id)),
+            Err(e) => failure.rollback_errors.push(format!(
+                "container {} ({}): {}",
+                container.name,
+                container.id,
+                e.message()
+            )),
+        }
+    }
+    let removed = match stop_pod(runtime, pod_id.to_string()).await {
+        Ok(()) => remove_pod(runtime, pod_id.to_string()).await,
+        Err(e) => Err(e),
+    };
+    match removed {
+        Ok(()) => failure.rolled_back.push(format!("pod {}", pod_id)),
+        Err(e) => failure
+            .rollback_errors
+            .push(format!("pod {}: {}", pod_id, e.message())),
+    }
+    failure
+}
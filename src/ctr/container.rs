@@ -0,0 +1,616 @@
+use crate::api::containerd::services::containers::v1::{
+    container::Runtime, containers_client::ContainersClient, Container, CreateContainerRequest,
+    DeleteContainerRequest, GetContainerRequest, ListContainersRequest,
+};
+use crate::api::containerd::types::Mount;
+use crate::api::google::protobuf::Any;
+use crate::ctr::image::{
+    image_runtime_config, unix_seconds, ImageRuntimeConfig, DEFAULT_SNAPSHOTTER,
+};
+use crate::ctr::lease::{create_lease, delete_lease, leased};
+use crate::ctr::namespace::request;
+use crate::ctr::snapshot::{prepare_snapshot, remove_snapshot};
+use crate::ctr::task::{delete_task, start_task};
+use rmcp::schemars;
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::io::Read;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::{FileTypeExt, MetadataExt, OpenOptionsExt};
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+use tonic::transport::Channel;
+use tracing::{debug, warn};
+
+/// Runtime shim used for containers started by `run_container`
+pub const DEFAULT_RUNTIME: &str = "io.containerd.runc.v2";
+
+/// Expiry of the lease held while a container is created, in case the server dies meanwhile
+const LEASE_EXPIRY: Duration = Duration::from_secs(24 * 60 * 60);
+
+const SPEC_TYPE_URL: &str = "types.containerd.io/opencontainers/runtime-spec/1/Spec";
+const DEFAULT_PATH_ENV: &str = "PATH=/usr/local/sbin:/usr/local/bin:/usr/sbin:/usr/bin:/sbin:/bin";
+const DEFAULT_CAPABILITIES: &[&str] = &[
+    "CAP_CHOWN",
+    "CAP_DAC_OVERRIDE",
+    "CAP_FSETID",
+    "CAP_FOWNER",
+    "CAP_MKNOD",
+    "CAP_NET_RAW",
+    "CAP_SETGID",
+    "CAP_SETUID",
+    "CAP_SETFCAP",
+    "CAP_SETPCAP",
+    "CAP_NET_BIND_SERVICE",
+    "CAP_SYS_CHROOT",
+    "CAP_KILL",
+    "CAP_AUDIT_WRITE",
+];
+
+/// A container in a containerd namespace
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct ContainerSummary {
+    pub id: String,
+    pub image: String,
+    pub runtime: String,
+    pub snapshotter: String,
+    pub snapshot_key: String,
+    pub labels: HashMap<String, String>,
+    /// Creation time in seconds since the unix epoch
+    pub created_at: Option<i64>,
+    /// Last update time in seconds since the unix epoch
+    pub updated_at: Option<i64>,
+}
+
+impl From<Container> for ContainerSummary {
+    fn from(container: Container) -> Self {
+        Self {
+            id: container.id,
+            image: container.image,
+            runtime: container.runtime.map(|r| r.name).unwrap_or_default(),
+            snapshotter: container.snapshotter,
+            snapshot_key: container.snapshot_key,
+            labels: container.labels,
+            created_at: unix_seconds(container.created_at),
+            updated_at: unix_seconds(container.updated_at),
+        }
+    }
+}
+
+/// A container started by `run_container`
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct RunningContainer {
+    pub container: ContainerSummary,
+    pub pid: u32,
+}
+
+pub async fn list_containers(
+    channel: &Channel,
+    namespace: &str,
+) -> Result<Vec<ContainerSummary>, tonic::Status> {
+    let mut client = ContainersClient::new(channel.clone());
+    let response = client
+        .list(request(
+            namespace,
+            ListContainersRequest { filters: vec![] },
+        )?)
+        .await?;
+    Ok(response
+        .into_inner()
+        .containers
+        .into_iter()
+        .map(ContainerSummary::from)
+        .collect())
+}
+
+pub async fn get_container(
+    channel: &Channel,
+    namespace: &str,
+    id: &str,
+) -> Result<Container, tonic::Status> {
+    let mut client = ContainersClient::new(channel.clone());
+    let message = GetContainerRequest { id: id.to_string() };
+    let response = client.get(request(namespace, message)?).await?;
+    response
+        .into_inner()
+        .container
+        .ok_or_else(|| tonic::Status::not_found(format!("Container {} not found", id)))
+}
+
+/// Create a container from a local image and start its task, like `ctr run -d`.
+///
+/// `args` replaces the entrypoint and command of the image when not empty. Anything
+/// created before a failing step is removed again.
+pub async fn run_container(
+    channel: &Channel,
+    namespace: &str,
+    image: &str,
+    id: &str,
+    args: Vec<String>,
+) -> Result<RunningContainer, tonic::Status> {
+    let config = image_runtime_config(channel, namespace, image).await?;
+
+    // nothing refers to the snapshot until the container is created, the lease keeps the
+    // garbage collector from removing it in between, as `ctr run` does
+    let lease = create_lease(channel, namespace, None, HashMap::new(), Some(LEASE_EXPIRY)).await?;
+    let created = create_leased(channel, namespace, image, id, args, &config, &lease.id).await;
+    if let Err(e) = delete_lease(channel, namespace, &lease.id, false).await {
+        warn!("failed to delete lease {}: {}", lease.id, e);
+    }
+    let (created, rootfs) = created?;
+
+    match start_task(channel, namespace, id, rootfs).await {
+        Ok(pid) => Ok(RunningContainer {
+            container: ContainerSummary::from(created),
+            pid,
+        }),
+        Err(e) => {
+            let _ = delete_task(channel, namespace, id).await;
+            if let Err(e) = delete_container(channel, namespace, id).await {
+                warn!("failed to clean up container {}: {}", id, e);
+            }
+            cleanup_snapshot(channel, namespace, DEFAULT_SNAPSHOTTER, id).await;
+            Err(e)
+        }
+    }
+}
+
+/// Prepare the snapshot of a container and create it under `lease`, returning the container
+/// and the mounts of its rootfs
+async fn create_leased(
+    channel: &Channel,
+    namespace: &str,
+    image: &str,
+    id: &str,
+    args: Vec<String>,
+    config: &ImageRuntimeConfig,
+    lease: &str,
+) -> Result<(Container, Vec<Mount>), tonic::Status> {
+    let chain_id = config.chain_id().ok_or_else(|| {
+        tonic::Status::failed_precondition(format!("Image {} has no layers", image))
+    })?;
+    let rootfs = prepare_snapshot(
+        channel,
+        namespace,
+        DEFAULT_SNAPSHOTTER,
+        id,
+        &chain_id,
+        lease,
+    )
+    .await?;
+
+    let spec = match default_spec(namespace, id, config, args, &rootfs).await {
+        Ok(spec) => spec,
+        Err(e) => {
+            cleanup_snapshot(channel, namespace, DEFAULT_SNAPSHOTTER, id).await;
+            return Err(e);
+        }
+    };
+    let container = Container {
+        id: id.to_string(),
+        image: image.to_string(),
+        runtime: Some(Runtime {
+            name: DEFAULT_RUNTIME.to_string(),
+            options: None,
+        }),
+        spec: Some(Any {
+            type_url: SPEC_TYPE_URL.to_string(),
+            value: serde_json::to_vec(&spec).unwrap_or_default(),
+        }),
+        snapshotter: DEFAULT_SNAPSHOTTER.to_string(),
+        snapshot_key: id.to_string(),
+        ..Default::default()
+    };
+    let mut client = ContainersClient::new(channel.clone());
+    let message = CreateContainerRequest {
+        container: Some(container),
+    };
+    match client
+        .create(leased(request(namespace, message)?, lease)?)
+        .await
+    {
+        Ok(response) => Ok((response.into_inner().container.unwrap_or_default(), rootfs)),
+        Err(e) => {
+            cleanup_snapshot(channel, namespace, DEFAULT_SNAPSHOTTER, id).await;
+            Err(e)
+        }
+    }
+}
+
+/// Remove a container together with its stopped task and its snapshot, like `ctr container rm`
+pub async fn remove_container(
+    channel: &Channel,
+    namespace: &str,
+    id: &str,
+) -> Result<(), tonic::Status> {
+    let container = get_container(channel, namespace, id).await?;
+
+    match delete_task(channel, namespace, id).await {
+        Ok(()) => {}
+        Err(e) if e.code() == tonic::Code::NotFound => {}
+        Err(e) if e.code() == tonic::Code::FailedPrecondition => {
+            return Err(tonic::Status::failed_precondition(format!(
+                "Task of container {} is still running, stop it first: {}",
+                id,
+                e.message()
+            )));
+        }
+        Err(e) => return Err(e),
+    }
+
+    delete_container(channel, namespace, id).await?;
+    if !container.snapshot_key.is_empty() {
+        cleanup_snapshot(
+            channel,
+            namespace,
+            &container.snapshotter,
+            &container.snapshot_key,
+        )
+        .await;
+    }
+    Ok(())
+}
+
+pub async fn delete_container(
+    channel: &Channel,
+    namespace: &str,
+    id: &str,
+) -> Result<(), tonic::Status> {
+    let mut client = ContainersClient::new(channel.clone());
+    let message = DeleteContainerRequest { id: id.to_string() };
+    client.delete(request(namespace, message)?).await?;
+    Ok(())
+}
+
+async fn cleanup_snapshot(channel: &Channel, namespace: &str, snapshotter: &str, key: &str) {
+    match remove_snapshot(channel, namespace, snapshotter, key).await {
+        Ok(()) => {}
+        Err(e) if e.code() == tonic::Code::NotFound => {}
+        Err(e) => warn!("failed to remove snapshot {}: {}", key, e),
+    }
+}
+
+/// Default OCI runtime spec for a container, following containerd's defaults for unix
+async fn default_spec(
+    namespace: &str,
+    id: &str,
+    config: &ImageRuntimeConfig,
+    args: Vec<String>,
+    rootfs: &[Mount],
+) -> Result<serde_json::Value, tonic::Status> {
+    let args = if args.is_empty() {
+        config
+            .entrypoint
+            .iter()
+            .chain(config.cmd.iter())
+            .cloned()
+            .collect()
+    } else {
+        args
+    };
+    if args.is_empty() {
+        return Err(tonic::Status::invalid_argument(
+            "Image has no entrypoint or command, args are required",
+        ));
+    }
+
+    let (uid, gid) = resolve_user(&config.user, rootfs).await?;
+    let mut env = vec![DEFAULT_PATH_ENV.to_string()];
+    for entry in &config.env {
+        let key = entry.split('=').next().unwrap_or_default();
+        env.retain(|e| e.split('=').next() != Some(key));
+        env.push(entry.clone());
+    }
+    let cwd = if config.working_dir.is_empty() {
+        "/"
+    } else {
+        config.working_dir.as_str()
+    };
+    debug!("spec for container {}: args {:?}", id, args);
+
+    Ok(serde_json::json!({
+        "ociVersion": "1.1.0",
+        "root": { "path": "rootfs" },
+        "process": {
+            "args": args,
+            "env": env,
+            "cwd": cwd,
+            "user": { "uid": uid, "gid": gid },
+            "capabilities": {
+                "bounding": DEFAULT_CAPABILITIES,
+                "permitted": DEFAULT_CAPABILITIES,
+                "effective": DEFAULT_CAPABILITIES,
+            },
+            "rlimits": [{ "type": "RLIMIT_NOFILE", "hard": 1024, "soft": 1024 }],
+            "noNewPrivileges": true,
+        },
+        "mounts": [
+            { "destination": "/proc", "type": "proc", "source": "proc", "options": ["nosuid", "noexec", "nodev"] },
+            { "destination": "/dev", "type": "tmpfs", "source": "tmpfs", "options": ["nosuid", "strictatime", "mode=755", "size=65536k"] },
+            { "destination": "/dev/pts", "type": "devpts", "source": "devpts", "options": ["nosuid", "noexec", "newinstance", "ptmxmode=0666", "mode=0620", "gid=5"] },
+            { "destination": "/dev/shm", "type": "tmpfs", "source": "shm", "options": ["nosuid", "noexec", "nodev", "mode=1777", "size=65536k"] },
+            { "destination": "/dev/mqueue", "type": "mqueue", "source": "mqueue", "options": ["nosuid", "noexec", "nodev"] },
+            { "destination": "/sys", "type": "sysfs", "source": "sysfs", "options": ["nosuid", "noexec", "nodev", "ro"] },
+            { "destination": "/sys/fs/cgroup", "type": "cgroup", "source": "cgroup", "options": ["ro", "nosuid", "noexec", "nodev"] },
+        ],
+        "linux": {
+            "cgroupsPath": format!("/{}/{}", namespace, id),
+            "resources": { "devices": [{ "allow": false, "access": "rwm" }] },
+            "namespaces": [
+                { "type": "pid" },
+                { "type": "ipc" },
+                { "type": "uts" },
+                { "type": "mount" },
+                { "type": "network" },
+            ],
+            "maskedPaths": [
+                "/proc/acpi", "/proc/asound", "/proc/kcore", "/proc/keys", "/proc/latency_stats",
+                "/proc/timer_list", "/proc/timer_stats", "/proc/sched_debug", "/sys/firmware",
+                "/sys/devices/virtual/powercap", "/proc/scsi",
+            ],
+            "readonlyPaths": [
+                "/proc/bus", "/proc/fs", "/proc/irq", "/proc/sys", "/proc/sysrq-trigger",
+            ],
+        },
+    }))
+}
+
+/// Resolve the `user[:group]` of an image to a uid and gid, like containerd's `WithUser`.
+///
+/// Names are looked up in the /etc/passwd and /etc/group files of the rootfs, read from the
+/// snapshot directories, so they need the server on the containerd host. A uid without a
+/// group gets the gid of its passwd entry, or 0 when it has none.
+async fn resolve_user(user: &str, rootfs: &[Mount]) -> Result<(u32, u32), tonic::Status> {
+    if user.is_empty() {
+        return Ok((0, 0));
+    }
+    let (name, group) = match user.split_once(':') {
+        Some((name, group)) => (name, Some(group)),
+        None => (user, None),
+    };
+
+    let dirs = rootfs_dirs(rootfs);
+    let passwd = read_rootfs_file(&dirs, "etc/passwd").await;
+    // name, uid and gid of each passwd entry
+    let users: Vec<(&str, u32, u32)> = passwd
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split(':');
+            let name = fields.next()?;
+            let uid = fields.nth(1)?.parse().ok()?;
+            let gid = fields.next()?.parse().ok()?;
+            Some((name, uid, gid))
+        })
+        .collect();
+    let (uid, user_gid) = match name.parse::<u32>() {
+        Ok(uid) => {
+            let gid = users.iter().find(|u| u.1 == uid).map(|u| u.2);
+            (uid, gid.unwrap_or(0))
+        }
+        Err(_) => match users.iter().find(|u| u.0 == name) {
+            Some(&(_, uid, gid)) => (uid, gid),
+            None => {
+                return Err(tonic::Status::invalid_argument(format!(
+                    "Image user '{}' not found in the /etc/passwd of the image",
+                    name
+                )))
+            }
+        },
+    };
+
+    let gid = match group {
+        None => user_gid,
+        Some(group) => match group.parse::<u32>() {
+            Ok(gid) => gid,
+            Err(_) => {
+                let groups = read_rootfs_file(&dirs, "etc/group").await;
+                groups
+                    .lines()
+                    .find_map(|line| {
+                        let mut fields = line.split(':');
+                        if fields.next()? != group {
+                            return None;
+                        }
+                        fields.nth(1)?.parse().ok()
+                    })
+                    .ok_or_else(|| {
+                        tonic::Status::invalid_argument(format!(
+                            "Image group '{}' not found in the /etc/group of the image",
+                            group
+                        ))
+                    })?
+            }
+        },
+    };
+    Ok((uid, gid))
+}
+
+/// Directories holding the files of a rootfs, topmost layer first
+fn rootfs_dirs(rootfs: &[Mount]) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    for mount in rootfs {
+        match mount.r#type.as_str() {
+            "overlay" => {
+                let option = |prefix: &str| {
+                    mount
+                        .options
+                        .iter()
+                        .find_map(|o| o.strip_prefix(prefix).map(str::to_string))
+                };
+                dirs.extend(option("upperdir=").map(PathBuf::from));
+                if let Some(lower) = option("lowerdir=") {
+                    dirs.extend(lower.split(':').map(PathBuf::from));
+                }
+            }
+            "bind" => dirs.push(PathBuf::from(&mount.source)),
+            _ => {}
+        }
+    }
+    dirs
+}
+
+/// Longest chain of symlinks followed while resolving a rootfs path, like the kernel's
+const MAX_SYMLINKS: usize = 40;
+
+/// Content of a file of the rootfs from the topmost layer that has it, empty when none does.
+///
+/// The path is resolved one component at a time across the layers, see `resolve_rootfs_path`,
+/// and the file found is opened with O_NOFOLLOW.
+async fn read_rootfs_file(dirs: &[PathBuf], path: &str) -> String {
+    let (dirs, rootfs_path) = (dirs.to_vec(), path.to_string());
+    let read = tokio::task::spawn_blocking(move || {
+        let Some(file) = resolve_rootfs_path(&dirs, &rootfs_path)? else {
+            return Ok(String::new());
+        };
+        let mut content = String::new();
+        std::fs::OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NOFOLLOW)
+            .open(&file)?
+            .read_to_string(&mut content)?;
+        Ok::<_, std::io::Error>(content)
+    })
+    .await
+    .unwrap_or_else(|e| Err(std::io::Error::other(e)));
+    read.unwrap_or_else(|e| {
+        debug!("failed to read {} of the rootfs: {}", path, e);
+        String::new()
+    })
+}
+
+/// Resolve a path of the rootfs to the regular file of the layer that provides it, None when
+/// the rootfs has no such file.
+///
+/// Symlinks are resolved against the rootfs root, never the host's, and refused when their
+/// target climbs out of the rootfs. No symlink inside a layer directory is followed.
+fn resolve_rootfs_path(dirs: &[PathBuf], path: &str) -> std::io::Result<Option<PathBuf>> {
+    // components still to resolve, next one last
+    let mut pending: Vec<OsString> = Vec::new();
+    push_components(&mut pending, Path::new(path));
+    let mut resolved: Vec<OsString> = Vec::new();
+    let mut links = 0;
+    while let Some(name) = pending.pop() {
+        if name == ".." {
+            if resolved.pop().is_none() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::PermissionDenied,
+                    format!("a symlink in {} leaves the rootfs", path),
+                ));
+            }
+            continue;
+        }
+        resolved.push(name);
+        let Some((entry, file_type)) = rootfs_entry(dirs, &resolved)? else {
+            return Ok(None);
+        };
+        if file_type.is_symlink() {
+            links += 1;
+            if links > MAX_SYMLINKS {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("too many symlinks in {}", path),
+                ));
+            }
+            let target = std::fs::read_link(&entry)?;
+            resolved.pop();
+            if target.is_absolute() {
+                resolved.clear();
+            }
+            push_components(&mut pending, &target);
+        } else if pending.is_empty() {
+            return Ok(file_type.is_file().then_some(entry));
+        } else if !file_type.is_dir() {
+            return Ok(None);
+        }
+    }
+    Ok(None)
+}
+
+/// Queue the components of `path` so they pop in order, `..` is kept for the caller
+fn push_components(pending: &mut Vec<OsString>, path: &Path) {
+    for component in path.components().rev() {
+        match component {
+            Component::Normal(name) => pending.push(name.to_os_string()),
+            Component::ParentDir => pending.push("..".into()),
+            Component::CurDir | Component::RootDir | Component::Prefix(_) => {}
+        }
+    }
+}
+
+/// The entry of the topmost layer at `path`, a list of plain names.
+///
+/// A whiteout, either a `.wh.<name>` file or overlayfs' 0:0 character device, hides the name
+/// in the layers below, and so does an opaque directory for everything under it. The entry
+/// itself may be a symlink, but none of the directories leading to it is.
+fn rootfs_entry(
+    dirs: &[PathBuf],
+    path: &[OsString],
+) -> std::io::Result<Option<(PathBuf, std::fs::FileType)>> {
+    'layers: for dir in dirs {
+        let mut current = dir.clone();
+        let mut opaque = false;
+        for (depth, name) in path.iter().enumerate() {
+            let mut whiteout = OsString::from(".wh.");
+            whiteout.push(name);
+            if exists(&current.join(whiteout))? {
+                return Ok(None);
+            }
+            let entry = current.join(name);
+            let metadata = match std::fs::symlink_metadata(&entry) {
+                Ok(metadata) => metadata,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    if opaque {
+                        return Ok(None);
+                    }
+                    continue 'layers;
+                }
+                Err(e) => return Err(e),
+            };
+            let file_type = metadata.file_type();
+            if file_type.is_char_device() && metadata.rdev() == 0 {
+                return Ok(None);
+            }
+            if depth + 1 == path.len() {
+                return Ok(Some((entry, file_type)));
+            }
+            // a file or symlink here hides any directory of the same name below
+            if !file_type.is_dir() {
+                return Ok(None);
+            }
+            opaque = opaque || is_opaque(&entry)?;
+            current = entry;
+        }
+    }
+    Ok(None)
+}
+
+/// Whether a layer directory hides the content lower layers have for it
+fn is_opaque(dir: &Path) -> std::io::Result<bool> {
+    if exists(&dir.join(".wh..wh..opq"))? {
+        return Ok(true);
+    }
+    // overlayfs marks opaque directories with an xattr instead
+    let Ok(path) = std::ffi::CString::new(dir.as_os_str().as_bytes()) else {
+        return Ok(false);
+    };
+    let mut value = [0u8; 1];
+    // SAFETY: both names are NUL terminated and the buffer length is that of `value`
+    let len = unsafe {
+        libc::lgetxattr(
+            path.as_ptr(),
+            c"trusted.overlay.opaque".as_ptr(),
+            value.as_mut_ptr().cast(),
+            value.len(),
+        )
+    };
+    Ok(len == 1 && value[0] == b'y')
+}
+
+/// Whether an entry exists, without following it when it is a symlink
+fn exists(path: &Path) -> std::io::Result<bool> {
+    match std::fs::symlink_metadata(path) {
+        Ok(_) => Ok(true),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e),
+    }
+}
@@ -0,0 +1,154 @@
+use crate::api::containerd::services::content::v1::{
+    content_client::ContentClient, DeleteContentRequest, Info, InfoRequest, ListContentRequest,
+    ReadContentRequest,
+};
+use crate::ctr::image::unix_seconds;
+use crate::ctr::namespace::request;
+use futures::StreamExt;
+use rmcp::schemars;
+use std::collections::HashMap;
+use tonic::transport::Channel;
+
+/// Largest blob read into memory, manifests and configs are far below this
+const MAX_BLOB_SIZE: usize = 16 * 1024 * 1024;
+
+/// A blob of the content store
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct BlobSummary {
+    pub digest: String,
+    pub size: i64,
+    pub labels: HashMap<String, String>,
+    /// Creation time in seconds since the unix epoch
+    pub created_at: Option<i64>,
+    /// Last update time in seconds since the unix epoch
+    pub updated_at: Option<i64>,
+}
+
+impl From<Info> for BlobSummary {
+    fn from(info: Info) -> Self {
+        Self {
+            digest: info.digest,
+            size: info.size,
+            labels: info.labels,
+            created_at: unix_seconds(info.created_at),
+            updated_at: unix_seconds(info.updated_at),
+        }
+    }
+}
+
+/// A JSON blob, such as a manifest, index or image config
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct JsonBlob {
+    pub digest: String,
+    pub size: usize,
+    /// The mediaType declared by the document, if any
+    pub media_type: Option<String>,
+    pub content: serde_json::Value,
+}
+
+/// Read a whole blob from the content store, refusing blobs above `MAX_BLOB_SIZE` up front
+pub async fn read_blob(
+    channel: &Channel,
+    namespace: &str,
+    digest: &str,
+) -> Result<Vec<u8>, tonic::Status> {
+    let mut client = ContentClient::new(channel.clone());
+    let message = InfoRequest {
+        digest: digest.to_string(),
+    };
+    let info = client
+        .info(request(namespace, message)?)
+        .await?
+        .into_inner()
+        .info
+        .unwrap_or_default();
+    if info.size > MAX_BLOB_SIZE as i64 {
+        return Err(tonic::Status::resource_exhausted(format!(
+            "Blob {} is {} bytes, larger than {} bytes",
+            digest, info.size, MAX_BLOB_SIZE
+        )));
+    }
+
+    let message = ReadContentRequest {
+        digest: digest.to_string(),
+        offset: 0,
+        size: 0,
+    };
+    let mut stream = client
+        .read(request(namespace, message)?)
+        .await?
+        .into_inner();
+
+    let mut data = Vec::new();
+    while let Some(chunk) = stream.next().await {
+        data.extend(chunk?.data);
+        if data.len() > MAX_BLOB_SIZE {
+            return Err(tonic::Status::resource_exhausted(format!(
+                "Blob {} is larger than {} bytes",
+                digest, MAX_BLOB_SIZE
+            )));
+        }
+    }
+    Ok(data)
+}
+
+/// Read a JSON blob, such as a manifest, index or image config
+pub async fn read_json(
+    channel: &Channel,
+    namespace: &str,
+    digest: &str,
+) -> Result<serde_json::Value, tonic::Status> {
+    let data = read_blob(channel, namespace, digest).await?;
+    serde_json::from_slice(&data)
+        .map_err(|e| tonic::Status::data_loss(format!("Blob {} is not valid JSON: {}", digest, e)))
+}
+
+/// Read a JSON blob together with its size and declared media type
+pub async fn get_json_blob(
+    channel: &Channel,
+    namespace: &str,
+    digest: &str,
+) -> Result<JsonBlob, tonic::Status> {
+    let data = read_blob(channel, namespace, digest).await?;
+    let content: serde_json::Value = serde_json::from_slice(&data).map_err(|e| {
+        tonic::Status::invalid_argument(format!(
+            "Blob {} is not a JSON document such as a manifest, index or config: {}",
+            digest, e
+        ))
+    })?;
+    Ok(JsonBlob {
+        digest: digest.to_string(),
+        size: data.len(),
+        media_type: content["mediaType"].as_str().map(|s| s.to_string()),
+        content,
+    })
+}
+
+/// List the blobs of the content store
+pub async fn list_content(channel: &Channel, namespace: &str) -> Result<Vec<Info>, tonic::Status> {
+    let mut client = ContentClient::new(channel.clone());
+    let message = ListContentRequest { filters: vec![] };
+    let mut stream = client
+        .list(request(namespace, message)?)
+        .await?
+        .into_inner();
+
+    let mut blobs = Vec::new();
+    while let Some(response) = stream.next().await {
+        blobs.extend(response?.info);
+    }
+    Ok(blobs)
+}
+
+pub async fn delete_content(
+    channel: &Channel,
+    namespace: &str,
+    digest: &str,
+) -> Result<(), tonic::Status> {
+    let mut client = ContentClient::new(channel.clone());
+    let message = DeleteContentRequest {
+        digest: digest.to_string(),
+    };
+    client.delete(request(namespace, message)?).await?;
+    Ok(())
+}
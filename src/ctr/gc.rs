@@ -0,0 +1,180 @@
+use crate::ctr::container::list_containers;
+use crate::ctr::content::{list_content, BlobSummary};
+use crate::ctr::image::list_images;
+use crate::ctr::lease::{list_lease_resources, list_leases, trigger_gc, LeaseSummary};
+use crate::ctr::snapshot::{list_snapshots, snapshot_usage, snapshotters, SnapshotSummary};
+use rmcp::schemars;
+use std::collections::{HashMap, HashSet, VecDeque};
+use tonic::transport::Channel;
+
+/// Label marking content or a snapshot as a root the garbage collector always keeps
+pub const GC_ROOT_LABEL: &str = "containerd.io/gc.root";
+
+/// Prefix of labels referring to content by digest
+const GC_REF_CONTENT_PREFIX: &str = "containerd.io/gc.ref.content";
+
+/// Prefix of labels referring to a snapshot, followed by the snapshotter name
+const GC_REF_SNAPSHOT_PREFIX: &str = "containerd.io/gc.ref.snapshot.";
+
+/// A snapshot the garbage collector would remove
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct CollectableSnapshot {
+    pub snapshotter: String,
+    #[serde(flatten)]
+    pub snapshot: SnapshotSummary,
+}
+
+/// What a garbage collection removes, or removed, from a namespace
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct GcReport {
+    /// Whether the collection ran, false for a preview
+    pub collected: bool,
+    pub content: Vec<BlobSummary>,
+    pub snapshots: Vec<CollectableSnapshot>,
+    /// Number of snapshots checked, those of one snapshotter when the preview is scoped to it
+    pub snapshot_count: usize,
+    /// Size of the content blobs in bytes
+    pub content_size: i64,
+    /// Disk usage of the snapshots in bytes
+    pub snapshot_size: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Node {
+    Content(String),
+    Snapshot(String, String),
+}
+
+/// References to content and snapshots held by the gc labels of an object
+fn label_refs(labels: &HashMap<String, String>) -> Vec<Node> {
+    labels
+        .iter()
+        .filter_map(|(key, value)| {
+            if key.starts_with(GC_REF_CONTENT_PREFIX) {
+                Some(Node::Content(value.clone()))
+            } else {
+                let snapshotter = key.strip_prefix(GC_REF_SNAPSHOT_PREFIX)?;
+                let snapshotter = snapshotter.split('/').next().unwrap_or_default();
+                Some(Node::Snapshot(snapshotter.to_string(), value.clone()))
+            }
+        })
+        .collect()
+}
+
+/// Work out which content and snapshots of a namespace nothing refers to.
+///
+/// This follows the rules of containerd's collector: images, containers, unexpired leases and
+/// objects labelled as gc roots are kept, together with everything they refer to through gc
+/// reference labels and snapshot parents. Blobs shared with other namespaces only free disk
+/// space once no namespace refers to them. With a `snapshotter`, the whole namespace is still
+/// walked but only the snapshots of that snapshotter are reported.
+pub async fn gc_preview(
+    channel: &Channel,
+    namespace: &str,
+    snapshotter: Option<&str>,
+) -> Result<GcReport, tonic::Status> {
+    let mut roots = Vec::new();
+    for image in list_images(channel, namespace).await? {
+        roots.push(Node::Content(image.digest.clone()));
+        roots.extend(label_refs(&image.labels));
+    }
+    for container in list_containers(channel, namespace).await? {
+        if !container.snapshot_key.is_empty() {
+            roots.push(Node::Snapshot(
+                container.snapshotter.clone(),
+                container.snapshot_key.clone(),
+            ));
+        }
+        roots.extend(label_refs(&container.labels));
+    }
+    for lease in list_leases(channel, namespace).await? {
+        if LeaseSummary::from(lease.clone()).expired {
+            continue;
+        }
+        for resource in list_lease_resources(channel, namespace, &lease.id).await? {
+            match resource.r#type.split_once('/') {
+                None if resource.r#type == "content" => roots.push(Node::Content(resource.id)),
+                Some(("snapshots", snapshotter)) => {
+                    roots.push(Node::Snapshot(snapshotter.to_string(), resource.id))
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let mut edges: HashMap<Node, Vec<Node>> = HashMap::new();
+    let content = list_content(channel, namespace).await?;
+    for blob in &content {
+        let node = Node::Content(blob.digest.clone());
+        if blob.labels.contains_key(GC_ROOT_LABEL) {
+            roots.push(node.clone());
+        }
+        edges.insert(node, label_refs(&blob.labels));
+    }
+    let mut snapshots = Vec::new();
+    for name in snapshotters(channel).await? {
+        for info in list_snapshots(channel, namespace, &name).await? {
+            let node = Node::Snapshot(name.clone(), info.name.clone());
+            if info.labels.contains_key(GC_ROOT_LABEL) {
+                roots.push(node.clone());
+            }
+            let mut refs = label_refs(&info.labels);
+            if !info.parent.is_empty() {
+                refs.push(Node::Snapshot(name.clone(), info.parent.clone()));
+            }
+            edges.insert(node, refs);
+            if snapshotter.is_none_or(|snapshotter| snapshotter == name) {
+                snapshots.push((name.clone(), info));
+            }
+        }
+    }
+
+    let mut reachable = HashSet::new();
+    let mut queue: VecDeque<Node> = roots.into();
+    while let Some(node) = queue.pop_front() {
+        if let Some(refs) = edges.get(&node) {
+            if !reachable.contains(&node) {
+                queue.extend(refs.iter().cloned());
+            }
+        }
+        reachable.insert(node);
+    }
+
+    let content: Vec<BlobSummary> = content
+        .into_iter()
+        .filter(|blob| !reachable.contains(&Node::Content(blob.digest.clone())))
+        .map(BlobSummary::from)
+        .collect();
+    let snapshot_count = snapshots.len();
+    let mut collectable = Vec::new();
+    let mut snapshot_size = 0;
+    for (snapshotter, info) in snapshots {
+        if reachable.contains(&Node::Snapshot(snapshotter.clone(), info.name.clone())) {
+            continue;
+        }
+        let mut snapshot = SnapshotSummary::from(info);
+        let usage = snapshot_usage(channel, namespace, &snapshotter, &snapshot.name).await?;
+        snapshot_size += usage.size;
+        snapshot.usage = Some(usage);
+        collectable.push(CollectableSnapshot {
+            snapshotter,
+            snapshot,
+        });
+    }
+    Ok(GcReport {
+        collected: false,
+        content_size: content.iter().map(|blob| blob.size).sum(),
+        content,
+        snapshots: collectable,
+        snapshot_count,
+        snapshot_size,
+    })
+}
+
+/// Run the garbage collector, reporting what it was expected to remove from the namespace
+pub async fn collect(channel: &Channel, namespace: &str) -> Result<GcReport, tonic::Status> {
+    let mut report = gc_preview(channel, namespace, None).await?;
+    trigger_gc(channel, namespace).await?;
+    report.collected = true;
+    Ok(report)
+}
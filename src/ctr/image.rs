@@ -0,0 +1,392 @@
+use crate::api::containerd::services::images::v1::{
+    images_client::ImagesClient, DeleteImageRequest, GetImageRequest, Image, ListImagesRequest,
+};
+use crate::api::containerd::services::transfer::v1::{
+    transfer_client::TransferClient, TransferRequest,
+};
+use crate::api::containerd::types::transfer::{
+    ImageStore, OciRegistry, RegistryResolver, UnpackConfiguration,
+};
+use crate::api::containerd::types::{Descriptor, Platform};
+use crate::api::google::protobuf::{Any, Timestamp};
+use crate::ctr::content::read_json;
+use crate::ctr::namespace::request;
+use prost::Message;
+use rmcp::schemars;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use tonic::transport::Channel;
+use tracing::debug;
+
+/// Snapshotter images are unpacked into and containers are run from
+pub const DEFAULT_SNAPSHOTTER: &str = "overlayfs";
+
+const INDEX_MEDIA_TYPES: &[&str] = &[
+    "application/vnd.oci.image.index.v1+json",
+    "application/vnd.docker.distribution.manifest.list.v2+json",
+];
+
+/// An image in a containerd namespace
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct ImageSummary {
+    pub name: String,
+    pub digest: String,
+    pub media_type: String,
+    pub size: i64,
+    pub labels: HashMap<String, String>,
+    /// Creation time in seconds since the unix epoch
+    pub created_at: Option<i64>,
+    /// Last update time in seconds since the unix epoch
+    pub updated_at: Option<i64>,
+}
+
+impl From<Image> for ImageSummary {
+    fn from(image: Image) -> Self {
+        let target = image.target.unwrap_or_default();
+        Self {
+            name: image.name,
+            digest: target.digest,
+            media_type: target.media_type,
+            size: target.size,
+            labels: image.labels,
+            created_at: unix_seconds(image.created_at),
+            updated_at: unix_seconds(image.updated_at),
+        }
+    }
+}
+
+/// The content tree of an image for one platform: index, manifest, config and layers
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct ImageTree {
+    pub name: String,
+    /// Index or manifest list the image points to, if it is multi-platform
+    pub index: Option<Descriptor>,
+    /// Platforms the index is built for
+    pub platforms: Vec<Platform>,
+    /// Platform the manifest was selected for
+    pub platform: Platform,
+    pub manifest: Descriptor,
+    pub config: Descriptor,
+    /// Layers from the base layer up
+    pub layers: Vec<Descriptor>,
+    /// Size of the manifest, config and layers in bytes
+    pub total_size: i64,
+}
+
+/// The parts of an image config needed to run a container from it
+#[derive(Debug, Default)]
+pub struct ImageRuntimeConfig {
+    pub diff_ids: Vec<String>,
+    pub env: Vec<String>,
+    pub entrypoint: Vec<String>,
+    pub cmd: Vec<String>,
+    pub working_dir: String,
+    pub user: String,
+}
+
+impl ImageRuntimeConfig {
+    /// Chain id of the top layer, which is the snapshot the image is unpacked to
+    pub fn chain_id(&self) -> Option<String> {
+        self.chain_ids().pop()
+    }
+
+    /// Chain ids of every layer from the bottom up, each one is a committed snapshot
+    pub fn chain_ids(&self) -> Vec<String> {
+        let mut chain_ids: Vec<String> = Vec::with_capacity(self.diff_ids.len());
+        for diff_id in &self.diff_ids {
+            let chain_id = match chain_ids.last() {
+                Some(parent) => format!(
+                    "sha256:{:x}",
+                    Sha256::digest(format!("{} {}", parent, diff_id))
+                ),
+                None => diff_id.clone(),
+            };
+            chain_ids.push(chain_id);
+        }
+        chain_ids
+    }
+}
+
+/// Seconds since the unix epoch of a protobuf timestamp
+pub fn unix_seconds(timestamp: Option<Timestamp>) -> Option<i64> {
+    timestamp.map(|t| t.seconds)
+}
+
+/// Platform of the host, in OCI notation
+pub fn host_platform() -> Platform {
+    let (architecture, variant) = match std::env::consts::ARCH {
+        "x86_64" => ("amd64", ""),
+        "aarch64" => ("arm64", "v8"),
+        "arm" => ("arm", "v7"),
+        "x86" => ("386", ""),
+        "powerpc64" => ("ppc64le", ""),
+        arch => (arch, ""),
+    };
+    Platform {
+        os: std::env::consts::OS.to_string(),
+        architecture: architecture.to_string(),
+        variant: variant.to_string(),
+    }
+}
+
+/// Parse an `os/architecture[/variant]` platform, such as `linux/arm64/v8`
+pub fn parse_platform(platform: &str) -> Result<Platform, tonic::Status> {
+    let parts: Vec<&str> = platform.split('/').collect();
+    match parts.as_slice() {
+        [os, architecture] | [os, architecture, ""]
+            if !os.is_empty() && !architecture.is_empty() =>
+        {
+            Ok(Platform {
+                os: os.to_string(),
+                architecture: architecture.to_string(),
+                variant: String::new(),
+            })
+        }
+        [os, architecture, variant] if !os.is_empty() && !architecture.is_empty() => Ok(Platform {
+            os: os.to_string(),
+            architecture: architecture.to_string(),
+            variant: variant.to_string(),
+        }),
+        _ => Err(tonic::Status::invalid_argument(format!(
+            "Invalid platform '{}', expected os/architecture[/variant]",
+            platform
+        ))),
+    }
+}
+
+pub async fn list_images(
+    channel: &Channel,
+    namespace: &str,
+) -> Result<Vec<ImageSummary>, tonic::Status> {
+    let mut client = ImagesClient::new(channel.clone());
+    let response = client
+        .list(request(namespace, ListImagesRequest { filters: vec![] })?)
+        .await?;
+    Ok(response
+        .into_inner()
+        .images
+        .into_iter()
+        .map(ImageSummary::from)
+        .collect())
+}
+
+pub async fn get_image(
+    channel: &Channel,
+    namespace: &str,
+    name: &str,
+) -> Result<Image, tonic::Status> {
+    let mut client = ImagesClient::new(channel.clone());
+    let message = GetImageRequest {
+        name: name.to_string(),
+    };
+    let response = client.get(request(namespace, message)?).await?;
+    response
+        .into_inner()
+        .image
+        .ok_or_else(|| tonic::Status::not_found(format!("Image {} not found", name)))
+}
+
+/// Pull an image for the host platform and unpack it, using containerd's transfer service
+pub async fn pull_image(
+    channel: &Channel,
+    namespace: &str,
+    reference: &str,
+) -> Result<ImageSummary, tonic::Status> {
+    let source = OciRegistry {
+        reference: reference.to_string(),
+        resolver: Some(RegistryResolver::default()),
+    };
+    let destination = ImageStore {
+        name: reference.to_string(),
+        platforms: vec![host_platform()],
+        unpacks: vec![UnpackConfiguration {
+            platform: Some(host_platform()),
+            snapshotter: DEFAULT_SNAPSHOTTER.to_string(),
+        }],
+        ..Default::default()
+    };
+    let message = TransferRequest {
+        source: Some(to_any(&source)),
+        destination: Some(to_any(&destination)),
+        options: None,
+    };
+    debug!("transfer request: {:?}", message);
+
+    let mut client = TransferClient::new(channel.clone());
+    client.transfer(request(namespace, message)?).await?;
+
+    let image = get_image(channel, namespace, reference).await?;
+    Ok(ImageSummary::from(image))
+}
+
+pub async fn remove_image(
+    channel: &Channel,
+    namespace: &str,
+    name: &str,
+) -> Result<(), tonic::Status> {
+    let mut client = ImagesClient::new(channel.clone());
+    let message = DeleteImageRequest {
+        name: name.to_string(),
+        sync: true,
+        target: None,
+    };
+    client.delete(request(namespace, message)?).await?;
+    Ok(())
+}
+
+/// Walk the content of an image for a platform, from its index down to its layers
+pub async fn image_tree(
+    channel: &Channel,
+    namespace: &str,
+    name: &str,
+    platform: &Platform,
+) -> Result<ImageTree, tonic::Status> {
+    let image = get_image(channel, namespace, name).await?;
+    let target = image.target.unwrap_or_default();
+
+    let mut index = None;
+    let mut platforms = Vec::new();
+    let mut manifest = target.clone();
+    if INDEX_MEDIA_TYPES.contains(&target.media_type.as_str()) {
+        let content = read_json(channel, namespace, &target.digest).await?;
+        let manifests = content["manifests"].as_array().cloned().unwrap_or_default();
+        platforms = manifests
+            .iter()
+            .filter_map(|m| descriptor_platform(&m["platform"]))
+            .collect();
+        manifest = select_manifest(&manifests, platform)
+            .map(descriptor)
+            .ok_or_else(|| {
+                tonic::Status::not_found(format!(
+                    "Image {} has no manifest for platform {}",
+                    name,
+                    platform_name(platform)
+                ))
+            })?;
+        index = Some(target);
+    }
+
+    let content = read_json(channel, namespace, &manifest.digest).await?;
+    if content["config"].is_null() {
+        return Err(tonic::Status::data_loss(format!(
+            "Manifest {} has no config",
+            manifest.digest
+        )));
+    }
+    let config = descriptor(&content["config"]);
+    let layers: Vec<Descriptor> = content["layers"]
+        .as_array()
+        .map(|layers| layers.iter().map(descriptor).collect())
+        .unwrap_or_default();
+    let total_size = manifest.size + config.size + layers.iter().map(|l| l.size).sum::<i64>();
+    Ok(ImageTree {
+        name: name.to_string(),
+        index,
+        platforms,
+        platform: platform.clone(),
+        manifest,
+        config,
+        layers,
+        total_size,
+    })
+}
+
+/// Read the config of an image for the host platform from the content store
+pub async fn image_runtime_config(
+    channel: &Channel,
+    namespace: &str,
+    name: &str,
+) -> Result<ImageRuntimeConfig, tonic::Status> {
+    let tree = image_tree(channel, namespace, name, &host_platform()).await?;
+    let config = read_json(channel, namespace, &tree.config.digest).await?;
+
+    let strings = |value: &serde_json::Value| -> Vec<String> {
+        value
+            .as_array()
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(|item| item.as_str().map(|s| s.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    Ok(ImageRuntimeConfig {
+        diff_ids: strings(&config["rootfs"]["diff_ids"]),
+        env: strings(&config["config"]["Env"]),
+        entrypoint: strings(&config["config"]["Entrypoint"]),
+        cmd: strings(&config["config"]["Cmd"]),
+        working_dir: config["config"]["WorkingDir"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        user: config["config"]["User"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+    })
+}
+
+/// Pick the manifest matching a platform from the manifests of an index
+fn select_manifest<'a>(
+    manifests: &'a [serde_json::Value],
+    platform: &Platform,
+) -> Option<&'a serde_json::Value> {
+    let matches = |manifest: &&serde_json::Value, strict: bool| {
+        let p = &manifest["platform"];
+        p["os"] == platform.os.as_str()
+            && p["architecture"] == platform.architecture.as_str()
+            && (!strict || platform.variant.is_empty() || p["variant"] == platform.variant.as_str())
+    };
+    manifests
+        .iter()
+        .find(|m| matches(m, true))
+        .or_else(|| manifests.iter().find(|m| matches(m, false)))
+}
+
+/// Descriptor of an OCI descriptor in JSON form
+fn descriptor(value: &serde_json::Value) -> Descriptor {
+    Descriptor {
+        media_type: value["mediaType"].as_str().unwrap_or_default().to_string(),
+        digest: value["digest"].as_str().unwrap_or_default().to_string(),
+        size: value["size"].as_i64().unwrap_or_default(),
+        annotations: value["annotations"]
+            .as_object()
+            .map(|annotations| {
+                annotations
+                    .iter()
+                    .filter_map(|(k, v)| v.as_str().map(|v| (k.clone(), v.to_string())))
+                    .collect()
+            })
+            .unwrap_or_default(),
+    }
+}
+
+/// Platform of an OCI descriptor in JSON form
+fn descriptor_platform(value: &serde_json::Value) -> Option<Platform> {
+    Some(Platform {
+        os: value["os"].as_str()?.to_string(),
+        architecture: value["architecture"].as_str()?.to_string(),
+        variant: value["variant"].as_str().unwrap_or_default().to_string(),
+    })
+}
+
+/// `os/architecture[/variant]` notation of a platform
+pub fn platform_name(platform: &Platform) -> String {
+    if platform.variant.is_empty() {
+        format!("{}/{}", platform.os, platform.architecture)
+    } else {
+        format!(
+            "{}/{}/{}",
+            platform.os, platform.architecture, platform.variant
+        )
+    }
+}
+
+/// Pack a containerd type into an `Any`, named the way containerd's typeurl registry expects
+fn to_any<T: Message + prost::Name>(message: &T) -> Any {
+    Any {
+        type_url: T::full_name(),
+        value: message.encode_to_vec(),
+    }
+}
@@ -0,0 +1,255 @@
+use crate::api::containerd::services::leases::v1::{
+    leases_client::LeasesClient, CreateRequest, DeleteRequest, Lease, ListRequest,
+    ListResourcesRequest, Resource,
+};
+use crate::ctr::image::unix_seconds;
+use crate::ctr::namespace::request;
+use rmcp::schemars;
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tonic::metadata::MetadataValue;
+use tonic::transport::Channel;
+
+/// Label holding the RFC 3339 time after which the garbage collector ignores a lease
+pub const EXPIRE_LABEL: &str = "containerd.io/gc.expire";
+
+/// gRPC header containerd reads the lease holding the resources created by a request from
+pub const LEASE_HEADER: &str = "containerd-lease";
+
+/// A lease protecting content and snapshots from garbage collection
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct LeaseSummary {
+    pub id: String,
+    pub labels: HashMap<String, String>,
+    /// Creation time in seconds since the unix epoch
+    pub created_at: Option<i64>,
+    /// Expiry time in seconds since the unix epoch, leases without one live until deleted
+    pub expires_at: Option<i64>,
+    /// Whether the lease has expired and no longer protects its resources
+    pub expired: bool,
+}
+
+impl From<Lease> for LeaseSummary {
+    fn from(lease: Lease) -> Self {
+        let expires_at = lease
+            .labels
+            .get(EXPIRE_LABEL)
+            .and_then(|t| parse_rfc3339(t));
+        Self {
+            id: lease.id,
+            labels: lease.labels,
+            created_at: unix_seconds(lease.created_at),
+            expires_at,
+            expired: expires_at.is_some_and(|t| t <= now()),
+        }
+    }
+}
+
+/// The resources a lease keeps alive
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct LeaseResources {
+    pub id: String,
+    /// Resources by id and type: content, ingests or snapshots/<snapshotter>
+    pub resources: Vec<Resource>,
+}
+
+pub async fn list_leases(channel: &Channel, namespace: &str) -> Result<Vec<Lease>, tonic::Status> {
+    let mut client = LeasesClient::new(channel.clone());
+    let response = client
+        .list(request(namespace, ListRequest { filters: vec![] })?)
+        .await?;
+    Ok(response.into_inner().leases)
+}
+
+/// Create a lease, with a generated id when none is given and an optional expiry
+pub async fn create_lease(
+    channel: &Channel,
+    namespace: &str,
+    id: Option<String>,
+    mut labels: HashMap<String, String>,
+    expire: Option<Duration>,
+) -> Result<LeaseSummary, tonic::Status> {
+    let id = id.unwrap_or_else(|| format!("mcp-containerd-{}", uuid::Uuid::new_v4()));
+    if let Some(expire) = expire {
+        let expires_at = now() + expire.as_secs() as i64;
+        labels.insert(EXPIRE_LABEL.to_string(), rfc3339(expires_at));
+    }
+    let mut client = LeasesClient::new(channel.clone());
+    let message = CreateRequest { id, labels };
+    let response = client.create(request(namespace, message)?).await?;
+    Ok(LeaseSummary::from(
+        response.into_inner().lease.unwrap_or_default(),
+    ))
+}
+
+/// Delete a lease, with `sync` waiting for the garbage collection that follows
+pub async fn delete_lease(
+    channel: &Channel,
+    namespace: &str,
+    id: &str,
+    sync: bool,
+) -> Result<(), tonic::Status> {
+    let mut client = LeasesClient::new(channel.clone());
+    let message = DeleteRequest {
+        id: id.to_string(),
+        sync,
+    };
+    client.delete(request(namespace, message)?).await?;
+    Ok(())
+}
+
+/// Attach a lease to a request, so the resources it creates are kept until the lease is deleted
+pub fn leased<T>(
+    mut request: tonic::Request<T>,
+    lease: &str,
+) -> Result<tonic::Request<T>, tonic::Status> {
+    let value = MetadataValue::try_from(lease)
+        .map_err(|_| tonic::Status::invalid_argument(format!("Invalid lease '{}'", lease)))?;
+    request.metadata_mut().insert(LEASE_HEADER, value);
+    Ok(request)
+}
+
+/// List the resources, such as content blobs and snapshots, a lease keeps alive
+pub async fn list_lease_resources(
+    channel: &Channel,
+    namespace: &str,
+    id: &str,
+) -> Result<Vec<Resource>, tonic::Status> {
+    let mut client = LeasesClient::new(channel.clone());
+    let message = ListResourcesRequest { id: id.to_string() };
+    let response = client.list_resources(request(namespace, message)?).await?;
+    Ok(response.into_inner().resources)
+}
+
+/// Run the garbage collector and wait for it to finish.
+///
+/// containerd has no API to start a collection, but deleting a lease synchronously runs
+/// one, so an empty lease is created and deleted again.
+pub async fn trigger_gc(channel: &Channel, namespace: &str) -> Result<(), tonic::Status> {
+    let lease = create_lease(channel, namespace, None, HashMap::new(), None).await?;
+    delete_lease(channel, namespace, &lease.id, true).await
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+/// Format seconds since the unix epoch as an RFC 3339 UTC time
+fn rfc3339(seconds: i64) -> String {
+    let (days, time) = (seconds.div_euclid(86400), seconds.rem_euclid(86400));
+    // days to civil date, from Howard Hinnant's date algorithms
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
+
+/// Parse an RFC 3339 time into seconds since the unix epoch
+fn parse_rfc3339(value: &str) -> Option<i64> {
+    let (date, time) = value.split_once(['T', 't'])?;
+    let mut date = date.splitn(3, '-').map(|p| p.parse::<i64>());
+    let (year, month, day) = (date.next()?.ok()?, date.next()?.ok()?, date.next()?.ok()?);
+
+    let (clock, offset) = match time.find(['Z', 'z', '+', '-']) {
+        Some(i) => time.split_at(i),
+        None => return None,
+    };
+    let mut clock = clock
+        .split('.')
+        .next()?
+        .splitn(3, ':')
+        .map(|p| p.parse::<i64>());
+    let (hour, minute, second) = (
+        clock.next()?.ok()?,
+        clock.next()?.ok()?,
+        clock.next()?.ok()?,
+    );
+    let offset = match offset {
+        "Z" | "z" => 0,
+        _ => {
+            let sign = if offset.starts_with('-') { -1 } else { 1 };
+            let (h, m) = offset[1..].split_once(':')?;
+            sign * (h.parse::<i64>().ok()? * 3600 + m.parse::<i64>().ok()? * 60)
+        }
+    };
+
+    // civil date to days, the inverse of the conversion in rfc3339
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146097 + doe - 719468;
+    Some(days * 86400 + hour * 3600 + minute * 60 + second - offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_utc_times() {
+        assert_eq!(rfc3339(0), "1970-01-01T00:00:00Z");
+        assert_eq!(rfc3339(1709208000), "2024-02-29T12:00:00Z");
+        assert_eq!(rfc3339(951782400), "2000-02-29T00:00:00Z");
+        assert_eq!(rfc3339(-1), "1969-12-31T23:59:59Z");
+        assert_eq!(rfc3339(-2203891200), "1900-03-01T00:00:00Z");
+        assert_eq!(rfc3339(-2203891201), "1900-02-28T23:59:59Z");
+        assert_eq!(rfc3339(-11670998400), "1600-02-29T00:00:00Z");
+    }
+
+    #[test]
+    fn parses_utc_times() {
+        assert_eq!(parse_rfc3339("1970-01-01T00:00:00Z"), Some(0));
+        assert_eq!(parse_rfc3339("2024-02-29T12:00:00Z"), Some(1709208000));
+        assert_eq!(parse_rfc3339("2000-02-29t00:00:00z"), Some(951782400));
+        assert_eq!(parse_rfc3339("1969-12-31T23:59:59Z"), Some(-1));
+        assert_eq!(parse_rfc3339("1900-03-01T00:00:00Z"), Some(-2203891200));
+        assert_eq!(parse_rfc3339("1600-02-29T00:00:00Z"), Some(-11670998400));
+    }
+
+    #[test]
+    fn parses_offsets_and_fractions() {
+        assert_eq!(parse_rfc3339("1970-01-01T01:00:00+01:00"), Some(0));
+        assert_eq!(parse_rfc3339("1969-12-31T19:00:00-05:00"), Some(0));
+        assert_eq!(
+            parse_rfc3339("2024-02-29T12:00:00.123456789+05:30"),
+            Some(1709188200)
+        );
+        assert_eq!(parse_rfc3339("2024-02-29T12:00:00.5Z"), Some(1709208000));
+    }
+
+    #[test]
+    fn rejects_invalid_times() {
+        assert_eq!(parse_rfc3339(""), None);
+        assert_eq!(parse_rfc3339("2024-02-29"), None);
+        assert_eq!(parse_rfc3339("2024-02-29T12:00:00"), None);
+        assert_eq!(parse_rfc3339("2024-02-29T12:00Z"), None);
+        assert_eq!(parse_rfc3339("2024-02-29T12:00:00+0530"), None);
+    }
+
+    #[test]
+    fn round_trips() {
+        for seconds in [0, 1, -1, 951782400, -2203891200, 1709208000, 4102444800] {
+            assert_eq!(parse_rfc3339(&rfc3339(seconds)), Some(seconds));
+        }
+    }
+}
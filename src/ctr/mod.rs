@@ -1 +1,10 @@
 pub mod cmd;
+pub mod container;
+pub mod content;
+pub mod gc;
+pub mod image;
+pub mod lease;
+pub mod namespace;
+pub mod snapshot;
+pub mod task;
+pub mod version;
@@ -0,0 +1,265 @@
+use crate::api::containerd::services::namespaces::v1::{
+    namespaces_client::NamespacesClient, CreateNamespaceRequest, DeleteNamespaceRequest,
+    ListNamespacesRequest, Namespace, UpdateNamespaceRequest,
+};
+use crate::api::google::protobuf::FieldMask;
+use crate::ctr::container::{delete_container, list_containers};
+use crate::ctr::content::{delete_content, list_content};
+use crate::ctr::image::{list_images, remove_image};
+use crate::ctr::lease::{delete_lease, list_leases};
+use crate::ctr::snapshot::{list_snapshots, remove_snapshot, snapshotters};
+use crate::ctr::task::{delete_task, kill_task, list_tasks, wait_task};
+use rmcp::schemars;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use tonic::metadata::MetadataValue;
+use tonic::transport::Channel;
+use tracing::{debug, warn};
+
+/// gRPC header containerd reads the namespace of a request from
+pub const NAMESPACE_HEADER: &str = "containerd-namespace";
+
+/// How long a forced namespace deletion waits for a killed task to exit
+const TASK_EXIT_TIMEOUT: Duration = Duration::from_secs(10);
+const SIGKILL: u32 = 9;
+
+/// Namespaces of the kubelet and Docker, never deleted since their engines own them
+pub const PROTECTED_NAMESPACES: &[&str] = &["k8s.io", "moby"];
+
+/// A containerd namespace
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct NamespaceSummary {
+    pub name: String,
+    pub labels: HashMap<String, String>,
+}
+
+impl From<Namespace> for NamespaceSummary {
+    fn from(namespace: Namespace) -> Self {
+        Self {
+            name: namespace.name,
+            labels: namespace.labels,
+        }
+    }
+}
+
+/// Number of resources of each kind owned by a namespace
+#[derive(Debug, Default, serde::Serialize, schemars::JsonSchema)]
+pub struct NamespaceResources {
+    pub containers: usize,
+    pub tasks: usize,
+    pub images: usize,
+    pub snapshots: usize,
+    pub content: usize,
+    pub leases: usize,
+}
+
+impl NamespaceResources {
+    pub fn is_empty(&self) -> bool {
+        self.containers + self.tasks + self.images + self.snapshots + self.content + self.leases
+            == 0
+    }
+}
+
+/// Wrap a request message, scoping it to a containerd namespace
+pub fn request<T>(namespace: &str, message: T) -> Result<tonic::Request<T>, tonic::Status> {
+    let value = MetadataValue::try_from(namespace).map_err(|_| {
+        tonic::Status::invalid_argument(format!("Invalid namespace '{}'", namespace))
+    })?;
+    let mut request = tonic::Request::new(message);
+    request.metadata_mut().insert(NAMESPACE_HEADER, value);
+    Ok(request)
+}
+
+pub async fn list_namespaces(channel: &Channel) -> Result<Vec<NamespaceSummary>, tonic::Status> {
+    let mut client = NamespacesClient::new(channel.clone());
+    let message = ListNamespacesRequest {
+        filter: String::new(),
+    };
+    let response = client.list(message).await?;
+    Ok(response
+        .into_inner()
+        .namespaces
+        .into_iter()
+        .map(NamespaceSummary::from)
+        .collect())
+}
+
+/// Fail with NotFound, naming the existing namespaces, unless `namespace` exists
+pub async fn ensure_namespace(channel: &Channel, namespace: &str) -> Result<(), tonic::Status> {
+    let namespaces = list_namespaces(channel).await?;
+    if namespaces.iter().any(|n| n.name == namespace) {
+        return Ok(());
+    }
+    let names: Vec<&str> = namespaces.iter().map(|n| n.name.as_str()).collect();
+    Err(tonic::Status::not_found(format!(
+        "Namespace '{}' does not exist, available namespaces: {}",
+        namespace,
+        names.join(", ")
+    )))
+}
+
+pub async fn create_namespace(
+    channel: &Channel,
+    name: &str,
+    labels: HashMap<String, String>,
+) -> Result<NamespaceSummary, tonic::Status> {
+    let mut client = NamespacesClient::new(channel.clone());
+    let message = CreateNamespaceRequest {
+        namespace: Some(Namespace {
+            name: name.to_string(),
+            labels,
+        }),
+    };
+    let response = client.create(message).await?;
+    Ok(NamespaceSummary::from(
+        response.into_inner().namespace.unwrap_or_default(),
+    ))
+}
+
+/// Set labels on a namespace, leaving other labels alone. An empty value removes the label.
+pub async fn set_namespace_labels(
+    channel: &Channel,
+    name: &str,
+    labels: HashMap<String, String>,
+) -> Result<NamespaceSummary, tonic::Status> {
+    let paths = labels.keys().map(|key| format!("labels.{}", key)).collect();
+    let mut client = NamespacesClient::new(channel.clone());
+    let message = UpdateNamespaceRequest {
+        namespace: Some(Namespace {
+            name: name.to_string(),
+            labels,
+        }),
+        update_mask: Some(FieldMask { paths }),
+    };
+    let response = client.update(message).await?;
+    Ok(NamespaceSummary::from(
+        response.into_inner().namespace.unwrap_or_default(),
+    ))
+}
+
+/// Count the resources a namespace still owns, containerd only deletes empty namespaces
+pub async fn namespace_resources(
+    channel: &Channel,
+    namespace: &str,
+) -> Result<NamespaceResources, tonic::Status> {
+    let mut snapshots = 0;
+    for snapshotter in snapshotters(channel).await? {
+        snapshots += list_snapshots(channel, namespace, &snapshotter)
+            .await?
+            .len();
+    }
+    Ok(NamespaceResources {
+        containers: list_containers(channel, namespace).await?.len(),
+        tasks: list_tasks(channel, namespace).await?.len(),
+        images: list_images(channel, namespace).await?.len(),
+        snapshots,
+        content: list_content(channel, namespace).await?.len(),
+        leases: list_leases(channel, namespace).await?.len(),
+    })
+}
+
+/// Delete a namespace, returning the resources it owned.
+///
+/// A namespace that still owns resources is refused unless `force` is set, in which case
+/// its tasks are killed and everything it owns is removed first. `PROTECTED_NAMESPACES`
+/// are always refused.
+pub async fn delete_namespace(
+    channel: &Channel,
+    name: &str,
+    force: bool,
+) -> Result<NamespaceResources, tonic::Status> {
+    if PROTECTED_NAMESPACES.contains(&name) {
+        return Err(tonic::Status::permission_denied(format!(
+            "Namespace '{}' belongs to the kubelet or Docker and cannot be deleted",
+            name
+        )));
+    }
+    let resources = namespace_resources(channel, name).await?;
+    if !resources.is_empty() {
+        if !force {
+            return Err(tonic::Status::failed_precondition(format!(
+                "Namespace '{}' is not empty ({} containers, {} tasks, {} images, {} snapshots, {} content blobs, {} leases), use force to remove everything in it",
+                name,
+                resources.containers,
+                resources.tasks,
+                resources.images,
+                resources.snapshots,
+                resources.content,
+                resources.leases
+            )));
+        }
+        empty_namespace(channel, name).await?;
+    }
+
+    let mut client = NamespacesClient::new(channel.clone());
+    let message = DeleteNamespaceRequest {
+        name: name.to_string(),
+    };
+    client.delete(message).await?;
+    Ok(resources)
+}
+
+/// Remove everything a namespace owns, from tasks down to content blobs
+async fn empty_namespace(channel: &Channel, namespace: &str) -> Result<(), tonic::Status> {
+    for task in list_tasks(channel, namespace).await? {
+        let id = &task.container_id;
+        if task.status != "stopped" {
+            debug!("killing task {} in namespace {}", id, namespace);
+            kill_task(channel, namespace, id, SIGKILL, true).await?;
+            wait_task(channel, namespace, id, TASK_EXIT_TIMEOUT).await?;
+        }
+        delete_task(channel, namespace, id).await?;
+    }
+    for container in list_containers(channel, namespace).await? {
+        delete_container(channel, namespace, &container.id).await?;
+    }
+    for image in list_images(channel, namespace).await? {
+        remove_image(channel, namespace, &image.name).await?;
+    }
+    for lease in list_leases(channel, namespace).await? {
+        delete_lease(channel, namespace, &lease.id, false).await?;
+    }
+    for snapshotter in snapshotters(channel).await? {
+        remove_snapshots(channel, namespace, &snapshotter).await?;
+    }
+    for blob in list_content(channel, namespace).await? {
+        match delete_content(channel, namespace, &blob.digest).await {
+            Ok(()) => {}
+            Err(e) if e.code() == tonic::Code::NotFound => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+/// Remove all snapshots of a snapshotter, children before their parents
+async fn remove_snapshots(
+    channel: &Channel,
+    namespace: &str,
+    snapshotter: &str,
+) -> Result<(), tonic::Status> {
+    loop {
+        let snapshots = list_snapshots(channel, namespace, snapshotter).await?;
+        if snapshots.is_empty() {
+            return Ok(());
+        }
+        let parents: HashSet<&str> = snapshots.iter().map(|s| s.parent.as_str()).collect();
+        let mut removed = 0;
+        for snapshot in snapshots
+            .iter()
+            .filter(|s| !parents.contains(s.name.as_str()))
+        {
+            match remove_snapshot(channel, namespace, snapshotter, &snapshot.name).await {
+                Ok(()) => removed += 1,
+                Err(e) => warn!("failed to remove snapshot {}: {}", snapshot.name, e),
+            }
+        }
+        if removed == 0 {
+            return Err(tonic::Status::failed_precondition(format!(
+                "Could not remove the remaining {} snapshots of snapshotter {}",
+                snapshots.len(),
+                snapshotter
+            )));
+        }
+    }
+}
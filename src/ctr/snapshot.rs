@@ -0,0 +1,276 @@
+use crate::api::containerd::services::introspection::v1::{
+    introspection_client::IntrospectionClient, PluginsRequest,
+};
+use crate::api::containerd::services::snapshots::v1::{
+    snapshots_client::SnapshotsClient, Info, Kind, ListSnapshotsRequest, PrepareSnapshotRequest,
+    RemoveSnapshotRequest, StatSnapshotRequest, UsageRequest,
+};
+use crate::api::containerd::types::Mount;
+use crate::ctr::gc::gc_preview;
+use crate::ctr::image::unix_seconds;
+use crate::ctr::lease::leased;
+use crate::ctr::namespace::request;
+use futures::StreamExt;
+use rmcp::schemars;
+use std::collections::HashMap;
+use tonic::transport::Channel;
+
+const SNAPSHOTTER_PLUGIN_TYPE: &str = "io.containerd.snapshotter.v1";
+
+/// A snapshot of a snapshotter
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct SnapshotSummary {
+    /// Key of active and view snapshots, name of committed ones
+    pub name: String,
+    pub parent: String,
+    /// Kind of the snapshot: active, view or committed
+    pub kind: String,
+    pub labels: HashMap<String, String>,
+    /// Creation time in seconds since the unix epoch
+    pub created_at: Option<i64>,
+    /// Last update time in seconds since the unix epoch
+    pub updated_at: Option<i64>,
+    /// Disk usage, only filled in when requested
+    pub usage: Option<SnapshotUsage>,
+}
+
+impl From<Info> for SnapshotSummary {
+    fn from(info: Info) -> Self {
+        Self {
+            name: info.name,
+            parent: info.parent,
+            kind: Kind::try_from(info.kind)
+                .map(|k| k.as_str_name().to_lowercase())
+                .unwrap_or_else(|_| info.kind.to_string()),
+            labels: info.labels,
+            created_at: unix_seconds(info.created_at),
+            updated_at: unix_seconds(info.updated_at),
+            usage: None,
+        }
+    }
+}
+
+/// Disk usage of a single snapshot, without its parents
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct SnapshotUsage {
+    pub size: i64,
+    pub inodes: i64,
+}
+
+/// A snapshot and its ancestors, from the snapshot down to the base layer
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct SnapshotChain {
+    pub snapshotter: String,
+    pub chain: Vec<SnapshotSummary>,
+    /// Disk usage of the whole chain in bytes
+    pub total_size: i64,
+}
+
+/// Snapshots no container, image, lease or gc root refers to, directly or as an ancestor
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct OrphanSnapshots {
+    pub snapshotter: String,
+    /// Number of snapshots of the snapshotter in the namespace
+    pub total: usize,
+    pub orphans: Vec<SnapshotSummary>,
+    /// Disk usage of the orphans in bytes
+    pub reclaimable_size: i64,
+}
+
+/// Names of the snapshotters loaded by the daemon
+pub async fn snapshotters(channel: &Channel) -> Result<Vec<String>, tonic::Status> {
+    let mut client = IntrospectionClient::new(channel.clone());
+    let message = PluginsRequest {
+        filters: vec![format!("type=={}", SNAPSHOTTER_PLUGIN_TYPE)],
+    };
+    let response = client.plugins(message).await?;
+    Ok(response
+        .into_inner()
+        .plugins
+        .into_iter()
+        .filter(|plugin| plugin.init_err.is_none())
+        .map(|plugin| plugin.id)
+        .collect())
+}
+
+/// Fail with NotFound, naming the loaded snapshotters, unless `snapshotter` is loaded
+pub async fn ensure_snapshotter(channel: &Channel, snapshotter: &str) -> Result<(), tonic::Status> {
+    let names = snapshotters(channel).await?;
+    if names.iter().any(|name| name == snapshotter) {
+        return Ok(());
+    }
+    Err(tonic::Status::not_found(format!(
+        "Snapshotter '{}' is not loaded, available snapshotters: {}",
+        snapshotter,
+        names.join(", ")
+    )))
+}
+
+/// List the snapshots of a snapshotter
+pub async fn list_snapshots(
+    channel: &Channel,
+    namespace: &str,
+    snapshotter: &str,
+) -> Result<Vec<Info>, tonic::Status> {
+    let mut client = SnapshotsClient::new(channel.clone());
+    let message = ListSnapshotsRequest {
+        snapshotter: snapshotter.to_string(),
+        filters: vec![],
+    };
+    let mut stream = client
+        .list(request(namespace, message)?)
+        .await?
+        .into_inner();
+
+    let mut snapshots = Vec::new();
+    while let Some(response) = stream.next().await {
+        snapshots.extend(response?.info);
+    }
+    Ok(snapshots)
+}
+
+/// Prepare a writable snapshot on top of `parent`, returning the mounts for its rootfs. The
+/// snapshot is held by `lease` until a container refers to it.
+pub async fn prepare_snapshot(
+    channel: &Channel,
+    namespace: &str,
+    snapshotter: &str,
+    key: &str,
+    parent: &str,
+    lease: &str,
+) -> Result<Vec<Mount>, tonic::Status> {
+    let mut client = SnapshotsClient::new(channel.clone());
+    let message = PrepareSnapshotRequest {
+        snapshotter: snapshotter.to_string(),
+        key: key.to_string(),
+        parent: parent.to_string(),
+        labels: HashMap::new(),
+    };
+    let response = client
+        .prepare(leased(request(namespace, message)?, lease)?)
+        .await?;
+    Ok(response.into_inner().mounts)
+}
+
+pub async fn remove_snapshot(
+    channel: &Channel,
+    namespace: &str,
+    snapshotter: &str,
+    key: &str,
+) -> Result<(), tonic::Status> {
+    let mut client = SnapshotsClient::new(channel.clone());
+    let message = RemoveSnapshotRequest {
+        snapshotter: snapshotter.to_string(),
+        key: key.to_string(),
+    };
+    client.remove(request(namespace, message)?).await?;
+    Ok(())
+}
+
+pub async fn stat_snapshot(
+    channel: &Channel,
+    namespace: &str,
+    snapshotter: &str,
+    key: &str,
+) -> Result<Info, tonic::Status> {
+    let mut client = SnapshotsClient::new(channel.clone());
+    let message = StatSnapshotRequest {
+        snapshotter: snapshotter.to_string(),
+        key: key.to_string(),
+    };
+    let response = client.stat(request(namespace, message)?).await?;
+    response
+        .into_inner()
+        .info
+        .ok_or_else(|| tonic::Status::not_found(format!("Snapshot {} not found", key)))
+}
+
+/// Disk usage of a snapshot, computing it can walk the whole upper directory of active snapshots
+pub async fn snapshot_usage(
+    channel: &Channel,
+    namespace: &str,
+    snapshotter: &str,
+    key: &str,
+) -> Result<SnapshotUsage, tonic::Status> {
+    let mut client = SnapshotsClient::new(channel.clone());
+    let message = UsageRequest {
+        snapshotter: snapshotter.to_string(),
+        key: key.to_string(),
+    };
+    let response = client
+        .usage(request(namespace, message)?)
+        .await?
+        .into_inner();
+    Ok(SnapshotUsage {
+        size: response.size,
+        inodes: response.inodes,
+    })
+}
+
+/// List the snapshots of a snapshotter as summaries, optionally with their disk usage
+pub async fn snapshot_summaries(
+    channel: &Channel,
+    namespace: &str,
+    snapshotter: &str,
+    usage: bool,
+) -> Result<Vec<SnapshotSummary>, tonic::Status> {
+    let mut snapshots = Vec::new();
+    for info in list_snapshots(channel, namespace, snapshotter).await? {
+        let mut snapshot = SnapshotSummary::from(info);
+        if usage {
+            snapshot.usage =
+                Some(snapshot_usage(channel, namespace, snapshotter, &snapshot.name).await?);
+        }
+        snapshots.push(snapshot);
+    }
+    Ok(snapshots)
+}
+
+/// Walk from a snapshot to its base layer, with the disk usage of each snapshot
+pub async fn snapshot_chain(
+    channel: &Channel,
+    namespace: &str,
+    snapshotter: &str,
+    key: &str,
+) -> Result<SnapshotChain, tonic::Status> {
+    let mut chain = Vec::new();
+    let mut total_size = 0;
+    let mut next = key.to_string();
+    while !next.is_empty() {
+        let info = stat_snapshot(channel, namespace, snapshotter, &next).await?;
+        let mut snapshot = SnapshotSummary::from(info);
+        let usage = snapshot_usage(channel, namespace, snapshotter, &snapshot.name).await?;
+        total_size += usage.size;
+        snapshot.usage = Some(usage);
+        next = snapshot.parent.clone();
+        chain.push(snapshot);
+    }
+    Ok(SnapshotChain {
+        snapshotter: snapshotter.to_string(),
+        chain,
+        total_size,
+    })
+}
+
+/// Find the snapshots of a snapshotter that nothing in the namespace refers to.
+///
+/// This is the garbage collection preview scoped to the snapshotter, so the orphans are the
+/// snapshots `gc_ctr` would remove: containers, images, unexpired leases, gc roots and gc
+/// reference labels keep a snapshot and its parents.
+pub async fn orphan_snapshots(
+    channel: &Channel,
+    namespace: &str,
+    snapshotter: &str,
+) -> Result<OrphanSnapshots, tonic::Status> {
+    let report = gc_preview(channel, namespace, Some(snapshotter)).await?;
+    Ok(OrphanSnapshots {
+        snapshotter: snapshotter.to_string(),
+        total: report.snapshot_count,
+        orphans: report
+            .snapshots
+            .into_iter()
+            .map(|collectable| collectable.snapshot)
+            .collect(),
+        reclaimable_size: report.snapshot_size,
+    })
+}
@@ -0,0 +1,350 @@
+use crate::api::containerd::runc::v1::ProcessDetails;
+use crate::api::containerd::services::tasks::v1::{
+    tasks_client::TasksClient, CreateTaskRequest, DeleteTaskRequest, GetRequest, KillRequest,
+    ListPidsRequest, ListTasksRequest, MetricsRequest, PauseTaskRequest, ResumeTaskRequest,
+    StartRequest, WaitRequest,
+};
+use crate::api::containerd::types::Mount;
+use crate::api::containerd::v1::types::{Process, Status};
+use crate::api::google::protobuf::Any;
+use crate::api::io::containerd::cgroups::v1::Metrics as CgroupV1Metrics;
+use crate::api::io::containerd::cgroups::v2::Metrics as CgroupV2Metrics;
+use crate::ctr::image::unix_seconds;
+use crate::ctr::namespace::request;
+use prost::{Message, Name};
+use rmcp::schemars;
+use std::time::Duration;
+use tonic::transport::Channel;
+
+/// Signal numbers on Linux, by name without the SIG prefix
+const SIGNALS: &[(&str, u32)] = &[
+    ("HUP", 1),
+    ("INT", 2),
+    ("QUIT", 3),
+    ("ILL", 4),
+    ("TRAP", 5),
+    ("ABRT", 6),
+    ("BUS", 7),
+    ("FPE", 8),
+    ("KILL", 9),
+    ("USR1", 10),
+    ("SEGV", 11),
+    ("USR2", 12),
+    ("PIPE", 13),
+    ("ALRM", 14),
+    ("TERM", 15),
+    ("STKFLT", 16),
+    ("CHLD", 17),
+    ("CONT", 18),
+    ("STOP", 19),
+    ("TSTP", 20),
+    ("TTIN", 21),
+    ("TTOU", 22),
+    ("URG", 23),
+    ("XCPU", 24),
+    ("XFSZ", 25),
+    ("VTALRM", 26),
+    ("PROF", 27),
+    ("WINCH", 28),
+    ("IO", 29),
+    ("PWR", 30),
+    ("SYS", 31),
+];
+
+/// Highest real-time signal number on Linux
+const SIGRTMAX: u32 = 64;
+
+/// The init process of a container
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct TaskSummary {
+    pub container_id: String,
+    pub pid: u32,
+    pub status: String,
+    pub exit_status: Option<u32>,
+    /// Exit time in seconds since the unix epoch
+    pub exited_at: Option<i64>,
+}
+
+impl From<Process> for TaskSummary {
+    fn from(process: Process) -> Self {
+        let exited = process.status == Status::Stopped as i32;
+        Self {
+            container_id: process.container_id,
+            pid: process.pid,
+            status: task_status_name(process.status),
+            exit_status: exited.then_some(process.exit_status),
+            exited_at: if exited {
+                unix_seconds(process.exited_at)
+            } else {
+                None
+            },
+        }
+    }
+}
+
+/// A process running inside a task
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct TaskProcess {
+    pub pid: u32,
+    /// Exec id of the process when it was started by an exec, none for the init process
+    pub exec_id: Option<String>,
+}
+
+/// The processes running inside a task
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct TaskProcesses {
+    pub container_id: String,
+    pub processes: Vec<TaskProcess>,
+}
+
+/// Metrics of the cgroup of a task
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct TaskMetrics {
+    pub container_id: String,
+    /// Collection time in seconds since the unix epoch
+    pub timestamp: Option<i64>,
+    /// Type of the metrics reported by the shim
+    pub type_url: String,
+    /// Decoded metrics of a cgroup v1 host
+    pub cgroup_v1: Option<CgroupV1Metrics>,
+    /// Decoded metrics of a cgroup v2 host
+    pub cgroup_v2: Option<CgroupV2Metrics>,
+}
+
+/// Human readable name of a raw task status value
+pub fn task_status_name(status: i32) -> String {
+    Status::try_from(status)
+        .map(|s| s.as_str_name().to_lowercase())
+        .unwrap_or_else(|_| status.to_string())
+}
+
+pub async fn list_tasks(
+    channel: &Channel,
+    namespace: &str,
+) -> Result<Vec<TaskSummary>, tonic::Status> {
+    let mut client = TasksClient::new(channel.clone());
+    let message = ListTasksRequest {
+        filter: String::new(),
+    };
+    let response = client.list(request(namespace, message)?).await?;
+    Ok(response
+        .into_inner()
+        .tasks
+        .into_iter()
+        .map(TaskSummary::from)
+        .collect())
+}
+
+/// Create the task of a container without any stdio attached and start it
+pub async fn start_task(
+    channel: &Channel,
+    namespace: &str,
+    container_id: &str,
+    rootfs: Vec<Mount>,
+) -> Result<u32, tonic::Status> {
+    let mut client = TasksClient::new(channel.clone());
+    let message = CreateTaskRequest {
+        container_id: container_id.to_string(),
+        rootfs,
+        ..Default::default()
+    };
+    client.create(request(namespace, message)?).await?;
+
+    let message = StartRequest {
+        container_id: container_id.to_string(),
+        exec_id: String::new(),
+    };
+    let response = client.start(request(namespace, message)?).await?;
+    Ok(response.into_inner().pid)
+}
+
+/// Parse a signal given by number or by name, with or without the SIG prefix
+pub fn parse_signal(signal: &str) -> Result<u32, tonic::Status> {
+    let signal = signal.trim();
+    if let Ok(number) = signal.parse::<u32>() {
+        if (1..=SIGRTMAX).contains(&number) {
+            return Ok(number);
+        }
+    }
+    let upper = signal.to_uppercase();
+    let name = upper.strip_prefix("SIG").unwrap_or(&upper);
+    SIGNALS
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, number)| *number)
+        .ok_or_else(|| tonic::Status::invalid_argument(format!("Unknown signal '{}'", signal)))
+}
+
+pub async fn get_task(
+    channel: &Channel,
+    namespace: &str,
+    container_id: &str,
+) -> Result<TaskSummary, tonic::Status> {
+    let mut client = TasksClient::new(channel.clone());
+    let message = GetRequest {
+        container_id: container_id.to_string(),
+        exec_id: String::new(),
+    };
+    let response = client.get(request(namespace, message)?).await?;
+    response
+        .into_inner()
+        .process
+        .map(TaskSummary::from)
+        .ok_or_else(|| tonic::Status::not_found(format!("Task {} not found", container_id)))
+}
+
+/// Freeze all processes of a task
+pub async fn pause_task(
+    channel: &Channel,
+    namespace: &str,
+    container_id: &str,
+) -> Result<(), tonic::Status> {
+    let mut client = TasksClient::new(channel.clone());
+    let message = PauseTaskRequest {
+        container_id: container_id.to_string(),
+    };
+    client.pause(request(namespace, message)?).await?;
+    Ok(())
+}
+
+/// Thaw the processes of a paused task
+pub async fn resume_task(
+    channel: &Channel,
+    namespace: &str,
+    container_id: &str,
+) -> Result<(), tonic::Status> {
+    let mut client = TasksClient::new(channel.clone());
+    let message = ResumeTaskRequest {
+        container_id: container_id.to_string(),
+    };
+    client.resume(request(namespace, message)?).await?;
+    Ok(())
+}
+
+/// List the processes of a task, including the ones started by execs
+pub async fn list_pids(
+    channel: &Channel,
+    namespace: &str,
+    container_id: &str,
+) -> Result<TaskProcesses, tonic::Status> {
+    let mut client = TasksClient::new(channel.clone());
+    let message = ListPidsRequest {
+        container_id: container_id.to_string(),
+    };
+    let response = client.list_pids(request(namespace, message)?).await?;
+    let processes = response
+        .into_inner()
+        .processes
+        .into_iter()
+        .map(|process| TaskProcess {
+            pid: process.pid,
+            exec_id: process
+                .info
+                .and_then(|info| unpack::<ProcessDetails>(&info))
+                .map(|details| details.exec_id)
+                .filter(|exec_id| !exec_id.is_empty()),
+        })
+        .collect();
+    Ok(TaskProcesses {
+        container_id: container_id.to_string(),
+        processes,
+    })
+}
+
+/// Read the cgroup metrics of a task, failing when they are neither cgroup v1 nor v2 metrics
+pub async fn task_metrics(
+    channel: &Channel,
+    namespace: &str,
+    container_id: &str,
+) -> Result<TaskMetrics, tonic::Status> {
+    let mut client = TasksClient::new(channel.clone());
+    let message = MetricsRequest {
+        filters: vec![format!("id=={}", container_id)],
+    };
+    let response = client.metrics(request(namespace, message)?).await?;
+    let metric = response
+        .into_inner()
+        .metrics
+        .into_iter()
+        .find(|metric| metric.id == container_id)
+        .ok_or_else(|| tonic::Status::not_found(format!("No metrics for task {}", container_id)))?;
+    let data = metric.data.unwrap_or_default();
+    let cgroup_v1 = unpack::<CgroupV1Metrics>(&data);
+    let cgroup_v2 = unpack::<CgroupV2Metrics>(&data);
+    if cgroup_v1.is_none() && cgroup_v2.is_none() {
+        return Err(tonic::Status::unimplemented(format!(
+            "Metrics of task {} have the unsupported type '{}'",
+            container_id, data.type_url
+        )));
+    }
+    Ok(TaskMetrics {
+        container_id: metric.id,
+        timestamp: unix_seconds(metric.timestamp),
+        type_url: data.type_url,
+        cgroup_v1,
+        cgroup_v2,
+    })
+}
+
+/// Decode an `Any` holding a `T`, whether or not its type url has a domain prefix
+fn unpack<T: Message + Name + Default>(any: &Any) -> Option<T> {
+    let name = any.type_url.rsplit('/').next().unwrap_or_default();
+    if name != T::full_name() {
+        return None;
+    }
+    T::decode(any.value.as_slice()).ok()
+}
+
+/// Send a signal to the init process of a task, or to all of its processes
+pub async fn kill_task(
+    channel: &Channel,
+    namespace: &str,
+    container_id: &str,
+    signal: u32,
+    all: bool,
+) -> Result<(), tonic::Status> {
+    let mut client = TasksClient::new(channel.clone());
+    let message = KillRequest {
+        container_id: container_id.to_string(),
+        exec_id: String::new(),
+        signal,
+        all,
+    };
+    client.kill(request(namespace, message)?).await?;
+    Ok(())
+}
+
+/// Wait for the init process of a task to exit, returning its exit status
+pub async fn wait_task(
+    channel: &Channel,
+    namespace: &str,
+    container_id: &str,
+    timeout: Duration,
+) -> Result<u32, tonic::Status> {
+    let mut client = TasksClient::new(channel.clone());
+    let message = WaitRequest {
+        container_id: container_id.to_string(),
+        exec_id: String::new(),
+    };
+    match tokio::time::timeout(timeout, client.wait(request(namespace, message)?)).await {
+        Ok(response) => Ok(response?.into_inner().exit_status),
+        Err(_) => Err(tonic::Status::deadline_exceeded(format!(
+            "Task {} did not exit within {:?}",
+            container_id, timeout
+        ))),
+    }
+}
+
+/// Delete the task of a container, the task must not be running
+pub async fn delete_task(
+    channel: &Channel,
+    namespace: &str,
+    container_id: &str,
+) -> Result<(), tonic::Status> {
+    let mut client = TasksClient::new(channel.clone());
+    let message = DeleteTaskRequest {
+        container_id: container_id.to_string(),
+    };
+    client.delete(request(namespace, message)?).await?;
+    Ok(())
+}
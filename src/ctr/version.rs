@@ -0,0 +1,30 @@
+use crate::api::containerd::services::introspection::v1::introspection_client::IntrospectionClient;
+use crate::api::containerd::services::version::v1::version_client::VersionClient;
+use crate::api::google::protobuf::Empty;
+use rmcp::schemars;
+use tonic::transport::Channel;
+
+/// Version and identity of the containerd daemon
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct DaemonVersion {
+    pub version: String,
+    pub revision: String,
+    /// UUID of the daemon, stable across restarts
+    pub uuid: String,
+}
+
+pub async fn version(channel: &Channel) -> Result<DaemonVersion, tonic::Status> {
+    let version = VersionClient::new(channel.clone())
+        .version(Empty {})
+        .await?
+        .into_inner();
+    let server = IntrospectionClient::new(channel.clone())
+        .server(Empty {})
+        .await?
+        .into_inner();
+    Ok(DaemonVersion {
+        version: version.version,
+        revision: version.revision,
+        uuid: server.uuid,
+    })
+}
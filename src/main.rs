@@ -12,6 +12,7 @@ use tracing_subscriber::{self, EnvFilter};
 
 pub mod api {
     pub mod runtime {
+        #[allow(clippy::doc_lazy_continuation)]
         pub mod v1 {
             tonic::include_proto!("runtime.v1");
 
@@ -21,6 +22,14 @@ pub mod api {
             };
         }
     }
+
+    // containerd's native services (containerd::services::*), the google types they use
+    // and the cgroup metrics reported by tasks (io::containerd::cgroups::{v1, v2})
+    #[allow(clippy::doc_lazy_continuation, clippy::tabs_in_doc_comments)]
+    mod native {
+        include!(concat!(env!("OUT_DIR"), "/_containerd.rs"));
+    }
+    pub use native::{containerd, google, io};
 }
 
 const DEFAULT_BIND_ADDRESS: &str = "0.0.0.0:3000";
@@ -40,6 +49,21 @@ struct Args {
     /// Containerd endpoint
     #[arg(short, long, default_value = DEFAULT_CONTAINERD_ENDPOINT)]
     endpoint: String,
+
+    /// Directory where container checkpoint archives are stored
+    #[arg(long, default_value = cri::checkpoint::DEFAULT_CHECKPOINT_DIR)]
+    checkpoint_dir: String,
+
+    /// Registry credentials file, either a docker config.json or a JSON map of
+    /// registry host to username/password/identity_token (can be repeated)
+    #[arg(long)]
+    registry_config: Vec<String>,
+
+    /// Pod Security Standards level every created pod and container must meet:
+    /// privileged, baseline or restricted, anything above privileged also disables
+    /// the ctr passthrough and run_container_ctr
+    #[arg(long, default_value = "privileged")]
+    min_security_level: String,
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -52,16 +76,31 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 }
 
 async fn async_main() -> Result<()> {
-    // init logger
+    // init logger, rmcp stays at info since it logs every request with the tool arguments,
+    // which include registry credentials
+    let filter = EnvFilter::from_default_env()
+        .add_directive(tracing::Level::DEBUG.into())
+        .add_directive("rmcp=info".parse()?);
     tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::from_default_env().add_directive(tracing::Level::DEBUG.into()))
+        .with_env_filter(filter)
         .with_writer(std::io::stderr)
         .with_ansi(false)
         .init();
     tracing::info!("Starting MCP server");
 
     let args = Args::parse();
-    let container_server = Server::new(args.endpoint.clone());
+    let credentials = cri::auth::CredentialStore::load(&args.registry_config)?;
+    let min_security_level = cri::security::parse_security_level(&args.min_security_level)
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "invalid security level '{}', expected privileged, baseline or restricted",
+                args.min_security_level
+            )
+        })?;
+    let container_server = Server::new(args.endpoint.clone())
+        .with_checkpoint_dir(args.checkpoint_dir.clone())
+        .with_credentials(credentials)
+        .with_minimum_security(min_security_level);
     container_server
         .connect()
         .await
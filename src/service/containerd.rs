@@ -3,52 +3,159 @@
  *
  * This service provides tools to interact with Containerd through both:
  * 1. Container Runtime Interface (CRI) - Standard K8s container runtime interface
- * 2. CTR CLI tool - Direct containerd command line interface
+ * 2. containerd's native gRPC services - the API the ctr CLI is built on, scoped by namespace
  *
  * CRI Tool Interfaces:
  * - version: Get the runtime version information
- * - list_pods: List all pod sandboxes
- * - list_containers: List all containers
- * - list_images: List all images
+ * - runtime_status: Get runtime conditions, handlers and features
+ * - runtime_config: Get the global runtime configuration
+ * - update_runtime_config: Update the pod CIDR of the runtime
+ * - list_pods: List pod sandboxes, filtered by id, state, labels, name or namespace
+ * - list_containers: List containers, filtered by id, state, pod, labels, name or namespace
+ * - list_images: List images, filtered by reference or name
  * - image_fs_info: Get image filesystem information
+ * - image_status: Get the parsed config of a local image
  * - create_pod: Create a new pod sandbox
  * - remove_pod: Remove a pod sandbox
  * - create_container: Create a new container
+ * - run_pod: Pull images, run a pod and start its containers, rolling back on failure
+ * - run_pod_manifest: Run a pod from a Kubernetes Pod manifest in YAML or JSON
  * - remove_container: Remove a container
  * - stop_pod: Stop a running pod sandbox
  * - start_container: Start a created container
  * - stop_container: Stop a running container
- * - exec: Execute a command in a running container
+ * - exec_sync: Execute a command in a running container and wait for it to finish
+ * - exec: Open a streaming exec session in a running container
+ * - exec_write: Write to the stdin of an exec session
+ * - exec_read: Read incremental stdout/stderr from an exec session
+ * - exec_resize: Resize the TTY of an exec session
+ * - exec_close: Close an exec session
  * - pull_image: Pull an image from registry
  * - remove_image: Remove an image
+ * - prune: Remove exited containers, not-ready pods and unused images, with a dry run
  * - container_stats: Get container statistics
- * - pod_stats: Get pod statistics
+ * - list_container_stats: Get statistics for a filtered set of containers
+ * - pod_stats: Get pod statistics, filtered by id, labels, name or namespace
  * - container_logs: Get container logs
+ * - inspect_container: Get the full status and runtime info of a container
+ * - inspect_pod: Get the full status, network and runtime info of a pod sandbox
+ * - update_container_resources: Update the resource limits of a running container
+ * - checkpoint_container: Checkpoint a container into an archive
+ * - list_checkpoints: List checkpoint archives in the checkpoint directory
+ * - wait_for_container_state: Wait until a container reaches a target state
+ * - recent_container_events: List recent container lifecycle events
  *
  * CTR Tool Interfaces:
- * - run_ctr_command: Run any ctr command
- * - list_containers_ctr: List all containers using ctr
- * - list_images_ctr: List all images using ctr
- * - list_tasks_ctr: List all tasks using ctr
- * - pull_image_ctr: Pull an image using ctr
- * - remove_image_ctr: Remove an image using ctr
- * - run_container_ctr: Run a container using ctr
- * - remove_container_ctr: Remove a container using ctr
+ * - run_ctr_command: Run any command of the ctr binary
+ * - version_ctr: Get the containerd daemon version
+ * - list_containers_ctr: List the containers of a namespace
+ * - list_images_ctr: List the images of a namespace
+ * - list_tasks_ctr: List the tasks of a namespace
+ * - pause_task_ctr: Pause a task
+ * - resume_task_ctr: Resume a paused task
+ * - kill_task_ctr: Send a signal to a task
+ * - list_task_pids_ctr: List the processes of a task
+ * - task_metrics_ctr: Get the cgroup metrics of a task
+ * - list_snapshots_ctr: List the snapshots of a snapshotter, with their disk usage
+ * - snapshot_chain_ctr: Show the parent chain of a snapshot
+ * - orphan_snapshots_ctr: Find snapshots nothing refers to
+ * - list_content_ctr: List the blobs of the content store
+ * - get_content_ctr: Fetch a manifest, index or config by digest
+ * - image_tree_ctr: Walk the manifest, config and layers of an image for a platform
+ * - list_leases_ctr: List the leases of a namespace
+ * - create_lease_ctr: Create a lease
+ * - delete_lease_ctr: Delete a lease
+ * - lease_resources_ctr: List the resources held by a lease
+ * - gc_ctr: Run or preview garbage collection
+ * - pull_image_ctr: Pull and unpack an image into a namespace
+ * - remove_image_ctr: Remove an image from a namespace
+ * - run_container_ctr: Create and start a container from a pulled image
+ * - remove_container_ctr: Remove a container with its task and snapshot
+ *
+ * The namespace of every CTR tool must be an existing containerd namespace.
+ *
+ * Namespace Tool Interfaces:
+ * - list_namespaces: List the containerd namespaces
+ * - create_namespace: Create a namespace
+ * - set_namespace_labels: Set or remove labels of a namespace
+ * - delete_namespace: Delete a namespace, optionally with everything in it
  */
 #![allow(dead_code)]
+use crate::api::runtime::v1::{
+    Container, ContainerConfig, ContainerFilter, ContainerState, ContainerStateValue,
+    ContainerStats, ContainerStatsFilter, ContainerStatsResponse, Image, PodSandbox,
+    PodSandboxConfig, PodSandboxFilter, PodSandboxStateValue, PodSandboxStats,
+    PodSandboxStatsFilter, VersionResponse,
+};
+use crate::cri::auth::{CredentialStore, RegistryCredential};
+use crate::cri::config::{
+    config_schema, ConfigOptions, CreateError, ParseMode, CONTAINER_CONFIG_SCHEMA_URI,
+    POD_CONFIG_SCHEMA_URI,
+};
+use crate::cri::events::{ContainerEvent, EventHistory};
+use crate::cri::exec::{ExecOutput, ExecSession};
+use crate::cri::manifest::ManifestRun;
+use crate::cri::pod::{contains, PodConfigs};
+use crate::cri::prune::{ImagePrune, PruneOptions, PrunePlan};
+use crate::cri::security::{Security, SecurityLevel};
+use crate::cri::workload::{PodSpec, RunPodFailure, RunningPod};
 use crate::ctr::cmd::CtrCmd;
+use crate::ctr::container::{ContainerSummary, RunningContainer};
+use crate::ctr::content::{BlobSummary, JsonBlob};
+use crate::ctr::gc::GcReport;
+use crate::ctr::image::{ImageSummary, ImageTree, DEFAULT_SNAPSHOTTER};
+use crate::ctr::lease::{LeaseResources, LeaseSummary};
+use crate::ctr::namespace::NamespaceSummary;
+use crate::ctr::snapshot::{OrphanSnapshots, SnapshotChain, SnapshotSummary};
+use crate::ctr::task::{TaskMetrics, TaskProcesses, TaskSummary};
+use crate::ctr::version::DaemonVersion;
+use crate::service::listing::{render, ListOptions, ListPage};
 use anyhow::Result;
 use rmcp::{
-    handler::server::tool::{Parameters, ToolRouter},
+    handler::server::tool::{cached_schema_for_type, Parameters, ToolRouter},
     model::*,
     schemars,
-    service::RequestContext,
-    tool, tool_handler, tool_router, ErrorData as McpError, RoleServer, ServerHandler,
+    service::{NotificationContext, RequestContext},
+    tool, tool_handler, tool_router, ErrorData as McpError, Peer, RoleServer, ServerHandler,
 };
+use std::collections::HashMap;
 use std::future::Future;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tokio::sync::Mutex;
-use tracing::debug;
+use tokio::sync::{broadcast, Mutex};
+use tracing::{debug, warn};
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct ListNamespacesParams {
+    #[serde(flatten)]
+    list: ListOptions,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct CreateNamespaceParams {
+    #[schemars(description = "The name of the namespace to create")]
+    name: String,
+    #[schemars(description = "Labels to set on the namespace")]
+    labels: Option<HashMap<String, String>>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct SetNamespaceLabelsParams {
+    #[schemars(description = "The name of the namespace")]
+    name: String,
+    #[schemars(description = "Labels to set, an empty value removes the label")]
+    labels: HashMap<String, String>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct DeleteNamespaceParams {
+    #[schemars(description = "The name of the namespace to delete")]
+    name: String,
+    #[schemars(
+        description = "Kill the tasks and remove all containers, images, snapshots, content and leases of the namespace first, only when the minimum security level is privileged (default false)"
+    )]
+    force: Option<bool>,
+}
 
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct RunCtrCommandParams {
@@ -62,27 +169,195 @@ pub struct RunCtrCommandParams {
 
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct ListContainersCtrParams {
-    #[schemars(description = "The namespace to use for the ctr command")]
+    #[schemars(description = "The containerd namespace, e.g. 'default' or 'k8s.io'")]
     namespace: String,
+    #[serde(flatten)]
+    list: ListOptions,
 }
 
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct ListImagesCtrParams {
-    #[schemars(description = "The namespace to use for the ctr command")]
+    #[schemars(description = "The containerd namespace, e.g. 'default' or 'k8s.io'")]
     namespace: String,
+    #[serde(flatten)]
+    list: ListOptions,
 }
 
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct ListTasksCtrParams {
-    #[schemars(description = "The namespace to use for the ctr command")]
+    #[schemars(description = "The containerd namespace, e.g. 'default' or 'k8s.io'")]
+    namespace: String,
+    #[serde(flatten)]
+    list: ListOptions,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct PauseTaskCtrParams {
+    #[schemars(description = "The ID of the container the task belongs to")]
+    container_id: String,
+    #[schemars(description = "The containerd namespace, e.g. 'default' or 'k8s.io'")]
+    namespace: String,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct ResumeTaskCtrParams {
+    #[schemars(description = "The ID of the container the task belongs to")]
+    container_id: String,
+    #[schemars(description = "The containerd namespace, e.g. 'default' or 'k8s.io'")]
+    namespace: String,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct KillTaskCtrParams {
+    #[schemars(description = "The ID of the container the task belongs to")]
+    container_id: String,
+    #[schemars(description = "The containerd namespace, e.g. 'default' or 'k8s.io'")]
+    namespace: String,
+    #[schemars(
+        description = "The signal to send, by name (e.g. 'SIGTERM', 'KILL', 'HUP') or number (default SIGTERM)"
+    )]
+    signal: Option<String>,
+    #[schemars(
+        description = "Send the signal to all processes of the task instead of only the init process (default false)"
+    )]
+    all: Option<bool>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct ListTaskPidsCtrParams {
+    #[schemars(description = "The ID of the container the task belongs to")]
+    container_id: String,
+    #[schemars(description = "The containerd namespace, e.g. 'default' or 'k8s.io'")]
+    namespace: String,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct TaskMetricsCtrParams {
+    #[schemars(description = "The ID of the container the task belongs to")]
+    container_id: String,
+    #[schemars(description = "The containerd namespace, e.g. 'default' or 'k8s.io'")]
+    namespace: String,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct ListSnapshotsCtrParams {
+    #[schemars(description = "The containerd namespace, e.g. 'default' or 'k8s.io'")]
+    namespace: String,
+    #[schemars(description = "The snapshotter, e.g. 'overlayfs' or 'native' (default overlayfs)")]
+    snapshotter: Option<String>,
+    #[schemars(
+        description = "Compute the disk usage of every snapshot, which can be slow for active snapshots (default false)"
+    )]
+    usage: Option<bool>,
+    #[serde(flatten)]
+    list: ListOptions,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct SnapshotChainCtrParams {
+    #[schemars(description = "The containerd namespace, e.g. 'default' or 'k8s.io'")]
+    namespace: String,
+    #[schemars(description = "The snapshotter, e.g. 'overlayfs' or 'native' (default overlayfs)")]
+    snapshotter: Option<String>,
+    #[schemars(
+        description = "The key or name of the snapshot, e.g. the snapshot key of a container"
+    )]
+    key: String,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct OrphanSnapshotsCtrParams {
+    #[schemars(description = "The containerd namespace, e.g. 'default' or 'k8s.io'")]
+    namespace: String,
+    #[schemars(description = "The snapshotter, e.g. 'overlayfs' or 'native' (default overlayfs)")]
+    snapshotter: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct ListContentCtrParams {
+    #[schemars(description = "The containerd namespace, e.g. 'default' or 'k8s.io'")]
+    namespace: String,
+    #[serde(flatten)]
+    list: ListOptions,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct GetContentCtrParams {
+    #[schemars(description = "The containerd namespace, e.g. 'default' or 'k8s.io'")]
+    namespace: String,
+    #[schemars(description = "The digest of the blob, e.g. 'sha256:...'")]
+    digest: String,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct ImageTreeCtrParams {
+    #[schemars(description = "The containerd namespace, e.g. 'default' or 'k8s.io'")]
+    namespace: String,
+    #[schemars(description = "The name of the image, e.g. 'docker.io/library/nginx:latest'")]
+    image_reference: String,
+    #[schemars(
+        description = "The platform to select from a multi-platform image, as os/architecture[/variant] (default the host platform)"
+    )]
+    platform: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct ListLeasesCtrParams {
+    #[schemars(description = "The containerd namespace, e.g. 'default' or 'k8s.io'")]
+    namespace: String,
+    #[serde(flatten)]
+    list: ListOptions,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct CreateLeaseCtrParams {
+    #[schemars(description = "The containerd namespace, e.g. 'default' or 'k8s.io'")]
+    namespace: String,
+    #[schemars(description = "The ID of the lease (default a generated ID)")]
+    id: Option<String>,
+    #[schemars(description = "Labels to set on the lease")]
+    labels: Option<HashMap<String, String>>,
+    #[schemars(
+        description = "Seconds after which the lease expires and stops protecting its resources (default never)"
+    )]
+    expire_seconds: Option<u64>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct DeleteLeaseCtrParams {
+    #[schemars(description = "The containerd namespace, e.g. 'default' or 'k8s.io'")]
+    namespace: String,
+    #[schemars(description = "The ID of the lease")]
+    id: String,
+    #[schemars(
+        description = "Wait for the garbage collection that reclaims the released resources (default false)"
+    )]
+    sync: Option<bool>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct LeaseResourcesCtrParams {
+    #[schemars(description = "The containerd namespace, e.g. 'default' or 'k8s.io'")]
+    namespace: String,
+    #[schemars(description = "The ID of the lease")]
+    id: String,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct GcCtrParams {
+    #[schemars(description = "The containerd namespace, e.g. 'default' or 'k8s.io'")]
     namespace: String,
+    #[schemars(
+        description = "Only report what would be reclaimed, without running the garbage collector (default false)"
+    )]
+    preview: Option<bool>,
 }
 
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct PullImageCtrParams {
     #[schemars(description = "The image reference to pull, e.g. 'docker.io/library/nginx:latest'")]
     image_reference: String,
-    #[schemars(description = "The namespace to use for the ctr command")]
+    #[schemars(description = "The containerd namespace, e.g. 'default' or 'k8s.io'")]
     namespace: String,
 }
 
@@ -92,7 +367,7 @@ pub struct RemoveImageCtrParams {
         description = "The image reference to remove, e.g. 'docker.io/library/nginx:latest'"
     )]
     image_reference: String,
-    #[schemars(description = "The namespace to use for the ctr command")]
+    #[schemars(description = "The containerd namespace, e.g. 'default' or 'k8s.io'")]
     namespace: String,
 }
 
@@ -103,10 +378,11 @@ pub struct RunContainerCtrParams {
     #[schemars(description = "The container ID or name")]
     container_id: String,
     #[schemars(
-        description = "Additional arguments for the container run command (as a space-separated string)"
+        description = "Command and arguments to run instead of the image entrypoint and command, as a space-separated string (empty to use the image defaults)"
     )]
+    #[serde(default)]
     args: String,
-    #[schemars(description = "The namespace to use for the ctr command")]
+    #[schemars(description = "The containerd namespace, e.g. 'default' or 'k8s.io'")]
     namespace: String,
 }
 
@@ -114,10 +390,26 @@ pub struct RunContainerCtrParams {
 pub struct RemoveContainerCtrParams {
     #[schemars(description = "The container ID or name to remove")]
     container_id: String,
-    #[schemars(description = "The namespace to use for the ctr command")]
+    #[schemars(description = "The containerd namespace, e.g. 'default' or 'k8s.io'")]
     namespace: String,
 }
 
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct RuntimeStatusParams {
+    #[schemars(
+        description = "Whether to include extra runtime info such as the CNI config and plugin settings (default: false)"
+    )]
+    verbose: Option<bool>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct UpdateRuntimeConfigParams {
+    #[schemars(
+        description = "CIDR to use for pod IP addresses, e.g. '10.244.0.0/16', comma separated for dual-stack"
+    )]
+    pod_cidr: String,
+}
+
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct GetContainerdLogsParams {
     #[schemars(
@@ -141,9 +433,65 @@ pub struct CreatePodParams {
     #[schemars(description = "Unique identifier for the pod (UUID format recommended)")]
     uid: String,
     #[schemars(
-        description = "Additional pod configuration options in hashmap format,the format is json in string"
+        description = "Additional PodSandboxConfig fields, merged over the metadata and hostname built from name, namespace and uid. The full shape is also published as the schema://pod-config resource"
+    )]
+    options: ConfigOptions<PodSandboxConfig>,
+    #[schemars(
+        description = "Reject unknown keys in options instead of returning them as warnings (default false)"
     )]
-    options: String,
+    strict: Option<bool>,
+    #[schemars(description = "Security preset (default the minimum level of the server)")]
+    security: Option<SecurityLevel>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct RunPodParams {
+    #[serde(flatten)]
+    pod: PodSpec,
+    #[schemars(
+        description = "Reject unknown keys in the pod and container options instead of returning them as warnings (default false)"
+    )]
+    strict: Option<bool>,
+    #[schemars(description = "Security preset (default the minimum level of the server)")]
+    security: Option<SecurityLevel>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct RunPodManifestParams {
+    #[schemars(description = "Kubernetes v1 Pod manifest, in YAML or JSON")]
+    manifest: String,
+    #[schemars(
+        description = "Only translate the manifest and check it against the security level, returning the pod spec without running it (default false)"
+    )]
+    dry_run: Option<bool>,
+    #[schemars(
+        description = "Refuse manifests with fields that have no CRI equivalent, and options with unknown keys, instead of reporting them (default false)"
+    )]
+    strict: Option<bool>,
+    #[schemars(description = "Security preset (default the minimum level of the server)")]
+    security: Option<SecurityLevel>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct PruneParams {
+    #[schemars(description = "Only return the plan without removing anything (default true)")]
+    dry_run: Option<bool>,
+    #[schemars(description = "Prune exited containers (default true)")]
+    containers: Option<bool>,
+    #[schemars(
+        description = "Prune not-ready pod sandboxes together with their containers, pods with a running container are kept (default true)"
+    )]
+    pods: Option<bool>,
+    #[schemars(
+        description = "Which images to prune: none, dangling (untagged and unused, the default) or unused (every image no container uses)"
+    )]
+    images: Option<String>,
+    #[schemars(
+        description = "Only prune containers and pods created more than this many seconds ago"
+    )]
+    older_than_seconds: Option<u64>,
+    #[schemars(description = "Only prune containers and pods that have all of these labels")]
+    label_selector: Option<HashMap<String, String>>,
 }
 
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
@@ -163,13 +511,19 @@ pub struct CreateContainerParams {
     #[schemars(description = "Container image to use (e.g., 'nginx:latest', 'ubuntu:20.04')")]
     image: String,
     #[schemars(
-        description = "Additional container configuration options in hashmap format,the format is json in string"
+        description = "Additional ContainerConfig fields, merged over the metadata, image and log path built from name and image. The full shape is also published as the schema://container-config resource"
+    )]
+    options: ConfigOptions<ContainerConfig>,
+    #[schemars(
+        description = "Sandbox config of the pod, only needed to override it. By default the config the pod was created with is used, or for pods created outside this server the one rebuilt from the pod status"
     )]
-    options: String,
+    pod_config: Option<ConfigOptions<PodSandboxConfig>>,
     #[schemars(
-        description = "It must be the result of create_pod tool, provides context for container creation within the pod, the format is json in string"
+        description = "Reject unknown keys in options instead of returning them as warnings (default false)"
     )]
-    pod_config: String,
+    strict: Option<bool>,
+    #[schemars(description = "Security preset (default the minimum level of the server)")]
+    security: Option<SecurityLevel>,
 }
 
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
@@ -202,16 +556,84 @@ pub struct StopContainerParams {
 pub struct ExecSyncParams {
     #[schemars(description = "The container id to execute the command in")]
     container_id: String,
-    #[schemars(description = "The command to execute")]
-    command: String,
+    #[schemars(description = "The command and its arguments, e.g. [\"ls\", \"-l\", \"/\"]")]
+    command: Vec<String>,
     #[schemars(description = "Optional timeout in seconds for command execution (default: 10)")]
     timeout: Option<i64>,
 }
 
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct ExecParams {
+    #[schemars(description = "The container id to execute the command in")]
+    container_id: String,
+    #[schemars(description = "The command and its arguments, e.g. [\"top\", \"-b\"]")]
+    command: Vec<String>,
+    #[schemars(
+        description = "Whether to allocate a TTY, stderr is merged into stdout (default: false)"
+    )]
+    tty: Option<bool>,
+    #[schemars(description = "Whether to keep stdin open for exec_write (default: false)")]
+    stdin: Option<bool>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct ExecWriteParams {
+    #[schemars(description = "The exec session id returned by the exec tool")]
+    session_id: String,
+    #[schemars(
+        description = "The data to write to stdin, include a trailing newline to submit a line"
+    )]
+    data: String,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct ExecReadParams {
+    #[schemars(description = "The exec session id returned by the exec tool")]
+    session_id: String,
+    #[schemars(
+        description = "Optional time in milliseconds to wait for new output when none is buffered (default: 1000)"
+    )]
+    wait_ms: Option<u64>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct ExecResizeParams {
+    #[schemars(description = "The exec session id returned by the exec tool")]
+    session_id: String,
+    #[schemars(description = "Terminal width in columns")]
+    width: u16,
+    #[schemars(description = "Terminal height in rows")]
+    height: u16,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct ExecCloseParams {
+    #[schemars(description = "The exec session id to close")]
+    session_id: String,
+}
+
+#[derive(serde::Deserialize, schemars::JsonSchema)]
 pub struct PullImageParams {
     #[schemars(description = "The image reference to pull, e.g. docker.io/library/nginx:latest")]
     image_reference: String,
+    #[schemars(
+        description = "Optional registry username, overrides the credentials configured on the server"
+    )]
+    username: Option<String>,
+    #[schemars(description = "Optional registry password, used together with username")]
+    password: Option<String>,
+    #[schemars(description = "Optional identity token used to obtain a registry access token")]
+    identity_token: Option<String>,
+    #[schemars(description = "Optional bearer token sent to the registry as is")]
+    registry_token: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct ImageStatusParams {
+    #[schemars(
+        description = "The image reference or id to inspect, e.g. docker.io/library/nginx:latest"
+    )]
+    image_reference: String,
 }
 
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
@@ -238,76 +660,635 @@ pub struct ContainerStatsParams {
 pub struct PodStatsParams {
     #[schemars(description = "Optional pod id to retrieve stats for")]
     pod_id: Option<String>,
+    #[schemars(description = "Only include pods that have all of these labels")]
+    label_selector: Option<HashMap<String, String>>,
+    #[schemars(description = "Only include pods whose name contains this substring")]
+    name: Option<String>,
+    #[schemars(description = "Only include pods whose namespace contains this substring")]
+    namespace: Option<String>,
+    #[serde(flatten)]
+    list: ListOptions,
 }
 
-type RuntimeClient =
-    Arc<Mutex<Option<crate::api::runtime::v1::RuntimeServiceClient<tonic::transport::Channel>>>>;
-type ImageClient =
-    Arc<Mutex<Option<crate::api::runtime::v1::ImageServiceClient<tonic::transport::Channel>>>>;
-#[derive(Clone)]
-pub struct Server {
-    endpoint: String,
-    runtime_client: RuntimeClient,
-    image_client: ImageClient,
-    binary: String,
-    tool_router: ToolRouter<Self>,
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct ListContainerStatsParams {
+    #[schemars(description = "Optional container id to retrieve stats for")]
+    container_id: Option<String>,
+    #[schemars(description = "Only include containers of this pod id")]
+    pod_id: Option<String>,
+    #[schemars(description = "Only include containers that have all of these labels")]
+    label_selector: Option<HashMap<String, String>>,
+    #[schemars(description = "Only include containers whose name contains this substring")]
+    name: Option<String>,
+    #[schemars(
+        description = "Only include containers whose pod namespace contains this substring"
+    )]
+    namespace: Option<String>,
+    #[serde(flatten)]
+    list: ListOptions,
 }
 
-#[tool_router]
-impl Server {
-    pub fn new(endpoint: String) -> Self {
-        Self {
-            endpoint,
-            runtime_client: Arc::new(Mutex::new(None)),
-            image_client: Arc::new(Mutex::new(None)),
-            binary: "ctr".to_string(),
-            tool_router: Self::tool_router(),
-        }
-    }
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct ListPodsParams {
+    #[schemars(description = "Only include the pod with this id")]
+    pod_id: Option<String>,
+    #[schemars(description = "Only include pods in this state: ready or notready")]
+    state: Option<String>,
+    #[schemars(description = "Only include pods that have all of these labels")]
+    label_selector: Option<HashMap<String, String>>,
+    #[schemars(description = "Only include pods whose name contains this substring")]
+    name: Option<String>,
+    #[schemars(description = "Only include pods whose namespace contains this substring")]
+    namespace: Option<String>,
+    #[serde(flatten)]
+    list: ListOptions,
+}
 
-    /// Helper function to create a CtrCmd instance
-    fn create_ctr_cmd(&self, namespace: String) -> CtrCmd {
-        CtrCmd::with_config(self.binary.clone(), namespace)
-    }
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct ListContainersParams {
+    #[schemars(description = "Only include the container with this id")]
+    container_id: Option<String>,
+    #[schemars(
+        description = "Only include containers in this state: created, running, exited or unknown"
+    )]
+    state: Option<String>,
+    #[schemars(description = "Only include containers of this pod id")]
+    pod_id: Option<String>,
+    #[schemars(description = "Only include containers that have all of these labels")]
+    label_selector: Option<HashMap<String, String>>,
+    #[schemars(description = "Only include containers whose name contains this substring")]
+    name: Option<String>,
+    #[schemars(
+        description = "Only include containers whose pod namespace contains this substring"
+    )]
+    namespace: Option<String>,
+    #[serde(flatten)]
+    list: ListOptions,
+}
 
-    pub async fn connect(&self) -> Result<()> {
-        let socket_path = self
-            .endpoint
-            .strip_prefix("unix://")
-            .expect("endpoint must start with unix://")
-            .to_string();
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct ListImagesParams {
+    #[schemars(
+        description = "Only include the image with this exact id, tag or digest, e.g. docker.io/library/nginx:latest"
+    )]
+    image: Option<String>,
+    #[schemars(description = "Only include images with a tag or digest containing this substring")]
+    name: Option<String>,
+    #[serde(flatten)]
+    list: ListOptions,
+}
 
-        let channel = tonic::transport::Endpoint::try_from("http://[::]:50051")?
-            .connect_with_connector(tower::service_fn(move |_: tonic::transport::Uri| {
-                let socket_path = socket_path.to_string();
-                async move { tokio::net::UnixStream::connect(socket_path).await }
-            }))
-            .await?;
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct InspectContainerParams {
+    #[schemars(description = "The container id to inspect")]
+    container_id: String,
+    #[schemars(
+        description = "Whether to include runtime debug info such as the OCI runtime spec, sandbox pid and snapshot key (default: true)"
+    )]
+    verbose: Option<bool>,
+}
 
-        {
-            debug!("connect runtime client");
-            let mut lock = self.runtime_client.lock().await;
-            *lock = Some(crate::api::runtime::v1::RuntimeServiceClient::new(
-                channel.clone(),
-            ));
-        }
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct InspectPodParams {
+    #[schemars(description = "The pod id to inspect")]
+    pod_id: String,
+    #[schemars(
+        description = "Whether to include runtime debug info such as the network namespace, sandbox pid and runtime spec (default: true)"
+    )]
+    verbose: Option<bool>,
+}
 
-        {
-            debug!("connect image client");
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct UpdateContainerResourcesParams {
+    #[schemars(description = "The container id to update")]
+    container_id: String,
+    #[schemars(description = "CPU shares, relative weight between 2 and 262144")]
+    cpu_shares: Option<i64>,
+    #[schemars(description = "CPU CFS quota in microseconds per period, -1 for unlimited")]
+    cpu_quota: Option<i64>,
+    #[schemars(description = "CPU CFS period in microseconds (1000 to 1000000)")]
+    cpu_period: Option<i64>,
+    #[schemars(description = "CPUs the container may run on, e.g. '0-3,6'")]
+    cpuset_cpus: Option<String>,
+    #[schemars(description = "Memory nodes the container may use, e.g. '0'")]
+    cpuset_mems: Option<String>,
+    #[schemars(description = "Memory limit in bytes (at least 6MiB)")]
+    memory_limit_in_bytes: Option<i64>,
+    #[schemars(
+        description = "Memory plus swap limit in bytes, -1 for unlimited, must not be less than the memory limit"
+    )]
+    memory_swap_limit_in_bytes: Option<i64>,
+    #[schemars(
+        description = "Hugepage limits in bytes keyed by page size, e.g. {\"2MB\": 1073741824}"
+    )]
+    hugepage_limits: Option<HashMap<String, u64>>,
+    #[schemars(
+        description = "Raw cgroup v2 settings keyed by file name, e.g. {\"memory.high\": \"1073741824\"}"
+    )]
+    unified: Option<HashMap<String, String>>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct CheckpointContainerParams {
+    #[schemars(description = "The container id to checkpoint")]
+    container_id: String,
+    #[schemars(
+        description = "Optional archive location relative to the server checkpoint directory, without '..' (default: <checkpoint_dir>/<container_id>-<timestamp>.tar)"
+    )]
+    location: Option<String>,
+    #[schemars(
+        description = "Optional timeout in seconds for the checkpoint to complete (default: runtime default)"
+    )]
+    timeout: Option<i64>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct WaitForContainerStateParams {
+    #[schemars(description = "The container id to wait for")]
+    container_id: String,
+    #[schemars(description = "The target state: 'created', 'running' or 'exited'")]
+    state: String,
+    #[schemars(description = "Optional timeout in seconds to wait for the state (default: 60)")]
+    timeout: Option<u64>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct RecentContainerEventsParams {
+    #[schemars(description = "Optional maximum number of events to return (default: 50)")]
+    limit: Option<usize>,
+    #[schemars(description = "Optional container id to only return events for that container")]
+    container_id: Option<String>,
+}
+
+type RuntimeClient =
+    Arc<Mutex<Option<crate::api::runtime::v1::RuntimeServiceClient<tonic::transport::Channel>>>>;
+type ImageClient =
+    Arc<Mutex<Option<crate::api::runtime::v1::ImageServiceClient<tonic::transport::Channel>>>>;
+type ContainerdChannel = Arc<Mutex<Option<tonic::transport::Channel>>>;
+type ExecSessions = Arc<Mutex<HashMap<String, Arc<ExecSession>>>>;
+type EventPeers = Arc<Mutex<Vec<EventPeer>>>;
+
+/// Interval between container status checks while waiting for a state change
+const WAIT_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Time after which an exec session nobody reads from or writes to is closed
+const EXEC_SESSION_TTL: std::time::Duration = std::time::Duration::from_secs(10 * 60);
+
+/// Interval between checks for idle exec sessions
+const EXEC_REAP_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Delay before resubscribing to the container event stream after it ends or fails
+const EVENT_RESUBSCRIBE_DELAY: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Result of a command that ran to completion
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct CommandOutput {
+    pub exit_code: i32,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// A newly opened exec session
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct ExecSessionInfo {
+    pub session_id: String,
+    pub tty: bool,
+    pub stdin: bool,
+}
+
+/// A client subscribed to container events with the log level it asked for
+struct EventPeer {
+    peer: Peer<RoleServer>,
+    level: LoggingLevel,
+}
+
+impl EventPeer {
+    /// Whether `peer` is the session of this client. Peers have no id, but the clones of a
+    /// session's peer share its client info.
+    fn is(&self, peer: &Peer<RoleServer>) -> bool {
+        match (self.peer.peer_info(), peer.peer_info()) {
+            (Some(a), Some(b)) => std::ptr::eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+/// Error result for a failed create, listing every configuration issue with its JSON path
+fn create_error(what: &str, e: CreateError) -> CallToolResult {
+    let message = match e {
+        CreateError::Config(e) => format!(
+            "Failed to create {}: invalid configuration {}",
+            what,
+            serde_json::to_string(&e).unwrap_or_default()
+        ),
+        CreateError::Policy(e) => format!("Failed to create {}: {}", what, e),
+        CreateError::Runtime(status) => format!("Failed to create {}: {}", what, status),
+    };
+    CallToolResult::error(vec![Content::text(message)])
+}
+
+/// Remove the emptyDir volumes of a removed pod, a failure only leaves the directory behind
+async fn remove_pod_volumes(uid: &str) {
+    match crate::cri::manifest::remove_volumes(uid).await {
+        Ok(Some(root)) => debug!("removed pod volumes {}", root.display()),
+        Ok(None) => {}
+        Err(e) => warn!("failed to remove the volumes of pod uid {}: {}", uid, e),
+    }
+}
+
+/// Remove the volumes a failed `run_pod_manifest` created: the host directories in `created`
+/// and, when it claimed it, the volume root of the pod
+async fn roll_back_manifest_volumes(
+    uid: &str,
+    claimed: bool,
+    created: &[PathBuf],
+    failure: &mut RunPodFailure,
+) {
+    let root = crate::cri::manifest::volume_root(uid).filter(|_| claimed);
+    for dir in created.iter().rev() {
+        // emptyDir directories go with the volume root
+        if root.as_ref().is_some_and(|root| dir.starts_with(root)) {
+            continue;
+        }
+        match tokio::fs::remove_dir_all(dir).await {
+            Ok(()) => failure
+                .rolled_back
+                .push(format!("volume directory {}", dir.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => {
+                failure
+                    .rollback_errors
+                    .push(format!("volume directory {}: {}", dir.display(), e))
+            }
+        }
+    }
+    if root.is_none() {
+        return;
+    }
+    match crate::cri::manifest::remove_volumes(uid).await {
+        Ok(Some(root)) => failure
+            .rolled_back
+            .push(format!("volumes {}", root.display())),
+        Ok(None) => {}
+        Err(e) => failure
+            .rollback_errors
+            .push(format!("volumes of pod uid {}: {}", uid, e)),
+    }
+}
+
+/// Successful tool result carrying `value` as structured content, matching the tool's output schema
+fn structured<T: serde::Serialize>(value: &T) -> CallToolResult {
+    CallToolResult::structured(serde_json::to_value(value).unwrap_or_default())
+}
+
+#[derive(Clone)]
+pub struct Server {
+    endpoint: String,
+    runtime_client: RuntimeClient,
+    image_client: ImageClient,
+    containerd_channel: ContainerdChannel,
+    exec_sessions: ExecSessions,
+    exec_reaper: Arc<std::sync::Mutex<Option<tokio::task::JoinHandle<()>>>>,
+    event_history: Arc<Mutex<EventHistory>>,
+    event_peers: EventPeers,
+    event_tx: broadcast::Sender<ContainerEvent>,
+    checkpoint_dir: PathBuf,
+    credentials: Arc<CredentialStore>,
+    pod_configs: Arc<PodConfigs>,
+    minimum_security: SecurityLevel,
+    binary: String,
+    tool_router: ToolRouter<Self>,
+}
+
+#[tool_router]
+impl Server {
+    pub fn new(endpoint: String) -> Self {
+        Self {
+            endpoint,
+            runtime_client: Arc::new(Mutex::new(None)),
+            image_client: Arc::new(Mutex::new(None)),
+            containerd_channel: Arc::new(Mutex::new(None)),
+            exec_sessions: Arc::new(Mutex::new(HashMap::new())),
+            exec_reaper: Arc::new(std::sync::Mutex::new(None)),
+            event_history: Arc::new(Mutex::new(EventHistory::new(
+                crate::cri::events::DEFAULT_HISTORY_CAPACITY,
+            ))),
+            event_peers: Arc::new(Mutex::new(Vec::new())),
+            event_tx: broadcast::channel(crate::cri::events::DEFAULT_HISTORY_CAPACITY).0,
+            checkpoint_dir: PathBuf::from(crate::cri::checkpoint::DEFAULT_CHECKPOINT_DIR),
+            credentials: Arc::new(CredentialStore::default()),
+            pod_configs: Arc::new(PodConfigs::default()),
+            minimum_security: SecurityLevel::default(),
+            binary: "ctr".to_string(),
+            tool_router: Self::tool_router(),
+        }
+    }
+
+    /// Set the directory used to store and list checkpoint archives
+    pub fn with_checkpoint_dir(mut self, checkpoint_dir: String) -> Self {
+        self.checkpoint_dir = PathBuf::from(checkpoint_dir);
+        self
+    }
+
+    /// Set the registry credentials used for image pulls
+    pub fn with_credentials(mut self, credentials: CredentialStore) -> Self {
+        debug!(
+            "registry credentials configured for: {:?}",
+            credentials.registries()
+        );
+        self.credentials = Arc::new(credentials);
+        self
+    }
+
+    /// Set the security level every created pod and container must meet
+    pub fn with_minimum_security(mut self, level: SecurityLevel) -> Self {
+        debug!("minimum security level: {}", level);
+        self.minimum_security = level;
+        self
+    }
+
+    /// The security of a pod or container created with the given preset
+    fn security(&self, preset: Option<SecurityLevel>) -> Security {
+        Security {
+            preset,
+            minimum: self.minimum_security,
+        }
+    }
+
+    /// Error result for a tool that can run containers past the security checks of the
+    /// CRI tools, when the server has a minimum security level
+    fn security_bypass(&self, tool: &str) -> Option<CallToolResult> {
+        if self.minimum_security == SecurityLevel::Privileged {
+            return None;
+        }
+        Some(CallToolResult::error(vec![Content::text(format!(
+            "{} is disabled, the containers it runs cannot be checked against the {} minimum security level of the server",
+            tool, self.minimum_security
+        ))]))
+    }
+
+    /// Helper function to create a CtrCmd instance
+    fn create_ctr_cmd(&self, namespace: String) -> CtrCmd {
+        let cmd = CtrCmd::with_config(self.binary.clone(), namespace);
+        match self.endpoint.strip_prefix("unix://") {
+            Some(address) => cmd.with_address(address.to_string()),
+            None => cmd,
+        }
+    }
+
+    /// Helper function to get the channel to containerd's native services
+    async fn containerd_channel(&self) -> Option<tonic::transport::Channel> {
+        self.containerd_channel.lock().await.clone()
+    }
+
+    /// Helper function to get the channel to containerd for a namespace that exists,
+    /// or the tool result to return instead
+    async fn namespace_channel(
+        &self,
+        namespace: &str,
+    ) -> Result<tonic::transport::Channel, CallToolResult> {
+        let Some(channel) = self.containerd_channel().await else {
+            return Err(CallToolResult::error(vec![Content::text(
+                "Containerd client not connected",
+            )]));
+        };
+        match crate::ctr::namespace::ensure_namespace(&channel, namespace).await {
+            Ok(()) => Ok(channel),
+            Err(e) if e.code() == tonic::Code::NotFound => {
+                Err(CallToolResult::error(vec![Content::text(e.message())]))
+            }
+            Err(e) => Err(CallToolResult::error(vec![Content::text(format!(
+                "Failed to check namespace {}: {}",
+                namespace, e
+            ))])),
+        }
+    }
+
+    /// Helper function to get the channel to containerd for an existing namespace and a
+    /// loaded snapshotter, or the tool result to return instead
+    async fn snapshotter_channel(
+        &self,
+        namespace: &str,
+        snapshotter: &str,
+    ) -> Result<tonic::transport::Channel, CallToolResult> {
+        let channel = self.namespace_channel(namespace).await?;
+        match crate::ctr::snapshot::ensure_snapshotter(&channel, snapshotter).await {
+            Ok(()) => Ok(channel),
+            Err(e) if e.code() == tonic::Code::NotFound => {
+                Err(CallToolResult::error(vec![Content::text(e.message())]))
+            }
+            Err(e) => Err(CallToolResult::error(vec![Content::text(format!(
+                "Failed to check snapshotter {}: {}",
+                snapshotter, e
+            ))])),
+        }
+    }
+
+    /// Helper function to look up a live exec session
+    async fn exec_session(&self, session_id: &str) -> Option<Arc<ExecSession>> {
+        self.exec_sessions.lock().await.get(session_id).cloned()
+    }
+
+    pub async fn connect(&self) -> Result<()> {
+        let socket_path = self
+            .endpoint
+            .strip_prefix("unix://")
+            .expect("endpoint must start with unix://")
+            .to_string();
+
+        let channel = tonic::transport::Endpoint::try_from("http://[::]:50051")?
+            .connect_with_connector(tower::service_fn(move |_: tonic::transport::Uri| {
+                let socket_path = socket_path.to_string();
+                async move { tokio::net::UnixStream::connect(socket_path).await }
+            }))
+            .await?;
+
+        {
+            debug!("connect runtime client");
+            let mut lock = self.runtime_client.lock().await;
+            *lock = Some(crate::api::runtime::v1::RuntimeServiceClient::new(
+                channel.clone(),
+            ));
+        }
+
+        {
+            debug!("connect image client");
             let mut lock = self.image_client.lock().await;
-            *lock = Some(crate::api::runtime::v1::ImageServiceClient::new(channel));
+            *lock = Some(crate::api::runtime::v1::ImageServiceClient::new(
+                channel.clone(),
+            ));
+        }
+
+        {
+            debug!("connect containerd client");
+            let mut lock = self.containerd_channel.lock().await;
+            *lock = Some(channel);
         }
 
+        self.start_event_subscriber();
+        self.start_exec_reaper();
+
         Ok(())
     }
 
+    /// Collect version, status and config of the runtime, recording errors instead of failing
+    async fn runtime_health_report(&self) -> serde_json::Value {
+        let Some(mut client) = self.runtime_client.lock().await.clone() else {
+            return serde_json::json!({ "connected": false });
+        };
+
+        let version = match crate::cri::runtime::version(&mut client).await {
+            Ok(version) => serde_json::to_value(version).unwrap(),
+            Err(e) => serde_json::json!({ "error": e.to_string() }),
+        };
+        let status = match crate::cri::runtime::status(&mut client, false).await {
+            Ok(response) => serde_json::json!({
+                "ready": response.status.as_ref().is_some_and(crate::cri::runtime::is_ready),
+                "conditions": response.status.map(|s| s.conditions).unwrap_or_default(),
+                "runtime_handlers": response.runtime_handlers,
+            }),
+            Err(e) => serde_json::json!({ "error": e.to_string() }),
+        };
+        let config = match crate::cri::runtime::runtime_config(&mut client).await {
+            Ok(response) => serde_json::json!({
+                "cgroup_driver": response
+                    .linux
+                    .map(|l| crate::cri::runtime::cgroup_driver_name(l.cgroup_driver)),
+            }),
+            Err(e) => serde_json::json!({ "error": e.to_string() }),
+        };
+
+        serde_json::json!({
+            "connected": true,
+            "endpoint": self.endpoint,
+            "version": version,
+            "status": status,
+            "config": config,
+        })
+    }
+
+    /// Consume the CRI container event stream in the background, recording every
+    /// event in the history and forwarding it to connected clients as a log message
+    fn start_event_subscriber(&self) {
+        let server = self.clone();
+        tokio::spawn(async move {
+            loop {
+                let client = server.runtime_client.lock().await.clone();
+                if let Some(mut client) = client {
+                    match crate::cri::events::container_events(&mut client).await {
+                        Ok(mut stream) => {
+                            debug!("subscribed to container events");
+                            loop {
+                                match stream.message().await {
+                                    Ok(Some(response)) => {
+                                        server.publish_event(ContainerEvent::from(response)).await;
+                                    }
+                                    Ok(None) => break,
+                                    Err(e) => {
+                                        warn!("container event stream failed: {}", e);
+                                        break;
+                                    }
+                                }
+                            }
+                        }
+                        Err(e) => warn!("failed to subscribe to container events: {}", e),
+                    }
+                }
+                tokio::time::sleep(EVENT_RESUBSCRIBE_DELAY).await;
+            }
+        });
+    }
+
+    /// Close exec sessions left idle for longer than `EXEC_SESSION_TTL` in the background,
+    /// such as those of clients that went away without calling exec_close. A reaper started
+    /// by an earlier connect is stopped
+    fn start_exec_reaper(&self) {
+        let sessions = self.exec_sessions.clone();
+        let reaper = tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(EXEC_REAP_INTERVAL).await;
+                let idle: Vec<(String, Arc<ExecSession>)> = {
+                    let mut sessions = sessions.lock().await;
+                    let ids: Vec<String> = sessions
+                        .iter()
+                        .filter(|(_, session)| session.idle_for(EXEC_SESSION_TTL))
+                        .map(|(id, _)| id.clone())
+                        .collect();
+                    ids.into_iter()
+                        .filter_map(|id| sessions.remove(&id).map(|session| (id, session)))
+                        .collect()
+                };
+                for (id, session) in idle {
+                    debug!(
+                        "closing idle exec session {} of container {}",
+                        id, session.container_id
+                    );
+                    session.close().await;
+                }
+            }
+        });
+        let previous = self
+            .exec_reaper
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .replace(reaper);
+        if let Some(previous) = previous {
+            previous.abort();
+        }
+    }
+
+    async fn publish_event(&self, event: ContainerEvent) {
+        debug!("container event: {:?}", event);
+        self.event_history.lock().await.push(event.clone());
+        // Nobody waiting on events is not an error
+        let _ = self.event_tx.send(event.clone());
+
+        let data = serde_json::to_value(&event).unwrap();
+        // Notify from a snapshot so a slow client doesn't hold up subscribing and set_level
+        let peers: Vec<Peer<RoleServer>> = self
+            .event_peers
+            .lock()
+            .await
+            .iter()
+            // Events are published at info level, skip clients that asked for less noise
+            .filter(|p| (p.level as u8) <= (LoggingLevel::Info as u8))
+            .map(|p| p.peer.clone())
+            .collect();
+        let mut gone = Vec::new();
+        for peer in peers {
+            let result = peer
+                .notify_logging_message(LoggingMessageNotificationParam {
+                    level: LoggingLevel::Info,
+                    logger: Some("container_events".to_string()),
+                    data: data.clone(),
+                })
+                .await;
+            if result.is_err() {
+                gone.push(peer);
+            }
+        }
+        // Drop peers whose transport has gone away
+        if !gone.is_empty() {
+            self.event_peers
+                .lock()
+                .await
+                .retain(|p| !gone.iter().any(|peer| p.is(peer)));
+        }
+    }
+
     // ================== CTR Tool Functions ==================
-    #[tool(description = "Run any ctr command with custom arguments")]
+    #[tool(
+        description = "Run any ctr command with custom arguments. Disabled when the server has a minimum security level",
+        output_schema = cached_schema_for_type::<CommandOutput>()
+    )]
     pub async fn run_ctr_command(
         &self,
         Parameters(RunCtrCommandParams { command, namespace }): Parameters<RunCtrCommandParams>,
     ) -> Result<CallToolResult, McpError> {
         debug!("Running ctr command: {}", command);
+        if let Some(result) = self.security_bypass("run_ctr_command") {
+            return Ok(result);
+        }
 
         // Split the command into parts
         let parts: Vec<&str> = command.split_whitespace().collect();
@@ -317,21 +1298,20 @@ impl Server {
             )]));
         }
 
+        if let Err(result) = self.namespace_channel(&namespace).await {
+            return Ok(result);
+        }
+
         let ctr_cmd = self.create_ctr_cmd(namespace);
         debug!("Created ctr command: {:?}", ctr_cmd);
         match ctr_cmd.custom_command(parts[0], parts[1..].to_vec()) {
             Ok(output) => {
-                let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-                let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-
-                let result = format!(
-                    "Exit Code: {}\n\nStdout:\n{}\n\nStderr:\n{}",
-                    output.status.code().unwrap_or(-1),
-                    stdout,
-                    stderr
-                );
-
-                Ok(CallToolResult::success(vec![Content::text(result)]))
+                let result = CommandOutput {
+                    exit_code: output.status.code().unwrap_or(-1),
+                    stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+                    stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+                };
+                Ok(structured(&result))
             }
             Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
                 "Failed to execute ctr command: {}",
@@ -340,19 +1320,42 @@ impl Server {
         }
     }
 
-    #[tool(description = "List all containers using ctr command")]
+    #[tool(
+        description = "Get the version, revision and uuid of the containerd daemon",
+        output_schema = cached_schema_for_type::<DaemonVersion>()
+    )]
+    pub async fn version_ctr(&self) -> Result<CallToolResult, McpError> {
+        let Some(channel) = self.containerd_channel().await else {
+            return Ok(CallToolResult::error(vec![Content::text(
+                "Containerd client not connected",
+            )]));
+        };
+        match crate::ctr::version::version(&channel).await {
+            Ok(version) => Ok(structured(&version)),
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to get containerd version: {}",
+                e
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "List the containers of a containerd namespace",
+        output_schema = cached_schema_for_type::<ListPage<ContainerSummary>>()
+    )]
     pub async fn list_containers_ctr(
         &self,
-        Parameters(ListContainersCtrParams { namespace }): Parameters<ListContainersCtrParams>,
+        Parameters(ListContainersCtrParams { namespace, list }): Parameters<
+            ListContainersCtrParams,
+        >,
     ) -> Result<CallToolResult, McpError> {
-        debug!("Listing containers with ctr");
-
-        let ctr_cmd = self.create_ctr_cmd(namespace);
-        match ctr_cmd.containers_list() {
-            Ok(output) => {
-                let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-                Ok(CallToolResult::success(vec![Content::text(stdout)]))
-            }
+        debug!("Listing containers in namespace {}", namespace);
+        let channel = match self.namespace_channel(&namespace).await {
+            Ok(channel) => channel,
+            Err(result) => return Ok(result),
+        };
+        match crate::ctr::container::list_containers(&channel, &namespace).await {
+            Ok(containers) => Ok(structured(&render(containers, &list))),
             Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
                 "Failed to list containers: {}",
                 e
@@ -360,19 +1363,21 @@ impl Server {
         }
     }
 
-    #[tool(description = "List all images using ctr command")]
+    #[tool(
+        description = "List the images of a containerd namespace",
+        output_schema = cached_schema_for_type::<ListPage<ImageSummary>>()
+    )]
     pub async fn list_images_ctr(
         &self,
-        Parameters(ListImagesCtrParams { namespace }): Parameters<ListImagesCtrParams>,
+        Parameters(ListImagesCtrParams { namespace, list }): Parameters<ListImagesCtrParams>,
     ) -> Result<CallToolResult, McpError> {
-        debug!("Listing images with ctr");
-
-        let ctr_cmd = self.create_ctr_cmd(namespace);
-        match ctr_cmd.images_list() {
-            Ok(output) => {
-                let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-                Ok(CallToolResult::success(vec![Content::text(stdout)]))
-            }
+        debug!("Listing images in namespace {}", namespace);
+        let channel = match self.namespace_channel(&namespace).await {
+            Ok(channel) => channel,
+            Err(result) => return Ok(result),
+        };
+        match crate::ctr::image::list_images(&channel, &namespace).await {
+            Ok(images) => Ok(structured(&render(images, &list))),
             Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
                 "Failed to list images: {}",
                 e
@@ -380,19 +1385,21 @@ impl Server {
         }
     }
 
-    #[tool(description = "List all tasks (running containers) using ctr command")]
+    #[tool(
+        description = "List the tasks (running or stopped container processes) of a containerd namespace",
+        output_schema = cached_schema_for_type::<ListPage<TaskSummary>>()
+    )]
     pub async fn list_tasks_ctr(
         &self,
-        Parameters(ListTasksCtrParams { namespace }): Parameters<ListTasksCtrParams>,
+        Parameters(ListTasksCtrParams { namespace, list }): Parameters<ListTasksCtrParams>,
     ) -> Result<CallToolResult, McpError> {
-        debug!("Listing tasks with ctr");
-
-        let ctr_cmd = self.create_ctr_cmd(namespace);
-        match ctr_cmd.tasks_list() {
-            Ok(output) => {
-                let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-                Ok(CallToolResult::success(vec![Content::text(stdout)]))
-            }
+        debug!("Listing tasks in namespace {}", namespace);
+        let channel = match self.namespace_channel(&namespace).await {
+            Ok(channel) => channel,
+            Err(result) => return Ok(result),
+        };
+        match crate::ctr::task::list_tasks(&channel, &namespace).await {
+            Ok(tasks) => Ok(structured(&render(tasks, &list))),
             Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
                 "Failed to list tasks: {}",
                 e
@@ -400,357 +1407,1539 @@ impl Server {
         }
     }
 
-    #[tool(description = "Pull an image using ctr command")]
-    pub async fn pull_image_ctr(
+    #[tool(
+        description = "Pause a task, freezing all processes of the container",
+        output_schema = cached_schema_for_type::<TaskSummary>()
+    )]
+    pub async fn pause_task_ctr(
         &self,
-        Parameters(PullImageCtrParams {
-            image_reference,
+        Parameters(PauseTaskCtrParams {
+            container_id,
             namespace,
-        }): Parameters<PullImageCtrParams>,
+        }): Parameters<PauseTaskCtrParams>,
     ) -> Result<CallToolResult, McpError> {
-        debug!("Pulling image with ctr: {}", image_reference);
-
-        let ctr_cmd = self.create_ctr_cmd(namespace);
-        match ctr_cmd.image_pull(&image_reference) {
-            Ok(output) => {
-                let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-                let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-
-                if output.status.success() {
-                    Ok(CallToolResult::success(vec![Content::text(format!(
-                        "Successfully pulled image: {}\n\n{}",
-                        image_reference, stdout
-                    ))]))
-                } else {
-                    Ok(CallToolResult::error(vec![Content::text(format!(
-                        "Failed to pull image: {}\n\n{}",
-                        image_reference, stderr
-                    ))]))
-                }
-            }
+        debug!("Pausing task {} in namespace {}", container_id, namespace);
+        let channel = match self.namespace_channel(&namespace).await {
+            Ok(channel) => channel,
+            Err(result) => return Ok(result),
+        };
+        let result = match crate::ctr::task::pause_task(&channel, &namespace, &container_id).await {
+            Ok(()) => crate::ctr::task::get_task(&channel, &namespace, &container_id).await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(task) => Ok(structured(&task)),
             Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
-                "Failed to execute pull command: {}",
-                e
+                "Failed to pause task {}: {}",
+                container_id, e
             ))])),
         }
     }
 
-    #[tool(description = "Remove an image using ctr command")]
-    pub async fn remove_image_ctr(
+    #[tool(
+        description = "Resume a paused task",
+        output_schema = cached_schema_for_type::<TaskSummary>()
+    )]
+    pub async fn resume_task_ctr(
         &self,
-        Parameters(RemoveImageCtrParams {
-            image_reference,
+        Parameters(ResumeTaskCtrParams {
+            container_id,
             namespace,
-        }): Parameters<RemoveImageCtrParams>,
+        }): Parameters<ResumeTaskCtrParams>,
     ) -> Result<CallToolResult, McpError> {
-        debug!("Removing image with ctr: {}", image_reference);
-
-        let ctr_cmd = self.create_ctr_cmd(namespace);
-        match ctr_cmd.image_remove(&image_reference) {
-            Ok(output) => {
-                let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-                let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-
-                if output.status.success() {
-                    Ok(CallToolResult::success(vec![Content::text(format!(
-                        "Successfully removed image: {}\n\n{}",
-                        image_reference, stdout
-                    ))]))
-                } else {
-                    Ok(CallToolResult::error(vec![Content::text(format!(
-                        "Failed to remove image: {}\n\n{}",
-                        image_reference, stderr
-                    ))]))
-                }
-            }
+        debug!("Resuming task {} in namespace {}", container_id, namespace);
+        let channel = match self.namespace_channel(&namespace).await {
+            Ok(channel) => channel,
+            Err(result) => return Ok(result),
+        };
+        let result = match crate::ctr::task::resume_task(&channel, &namespace, &container_id).await
+        {
+            Ok(()) => crate::ctr::task::get_task(&channel, &namespace, &container_id).await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(task) => Ok(structured(&task)),
             Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
-                "Failed to execute remove command: {}",
-                e
+                "Failed to resume task {}: {}",
+                container_id, e
             ))])),
         }
     }
 
-    #[tool(description = "Run a container using ctr command")]
-    pub async fn run_container_ctr(
+    #[tool(description = "Send a signal to the init process of a task, or to all of its processes")]
+    pub async fn kill_task_ctr(
         &self,
-        Parameters(RunContainerCtrParams {
-            image_reference,
+        Parameters(KillTaskCtrParams {
             container_id,
-            args,
             namespace,
-        }): Parameters<RunContainerCtrParams>,
+            signal,
+            all,
+        }): Parameters<KillTaskCtrParams>,
     ) -> Result<CallToolResult, McpError> {
+        let signal = signal.unwrap_or_else(|| "SIGTERM".to_string());
+        let all = all.unwrap_or(false);
         debug!(
-            "Running container with ctr - image: {}, id: {}, args: {}",
-            image_reference, container_id, args
+            "Sending {} to task {} in namespace {} (all: {})",
+            signal, container_id, namespace, all
         );
-
-        let args_vec: Vec<String> = args.split_whitespace().map(|s| s.to_string()).collect();
-
-        let ctr_cmd = self.create_ctr_cmd(namespace);
-        match ctr_cmd.container_run(&image_reference, &container_id, args_vec) {
-            Ok(output) => {
-                let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-                let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-
-                if output.status.success() {
-                    Ok(CallToolResult::success(vec![Content::text(format!(
-                        "Successfully created container: {}\n\n{}",
-                        container_id, stdout
-                    ))]))
-                } else {
-                    Ok(CallToolResult::error(vec![Content::text(format!(
-                        "Failed to create container: {}\n\n{}",
-                        container_id, stderr
-                    ))]))
-                }
+        let number = match crate::ctr::task::parse_signal(&signal) {
+            Ok(number) => number,
+            Err(e) => {
+                return Ok(CallToolResult::error(vec![Content::text(e.message())]));
+            }
+        };
+        let channel = match self.namespace_channel(&namespace).await {
+            Ok(channel) => channel,
+            Err(result) => return Ok(result),
+        };
+        match crate::ctr::task::kill_task(&channel, &namespace, &container_id, number, all).await {
+            Ok(()) => {
+                let result = serde_json::json!({
+                    "success": true,
+                    "container_id": container_id,
+                    "signal": number,
+                    "all": all
+                });
+                Ok(CallToolResult::success(vec![Content::text(
+                    result.to_string(),
+                )]))
             }
             Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
-                "Failed to execute container run command: {}",
-                e
+                "Failed to send signal {} to task {}: {}",
+                signal, container_id, e
             ))])),
         }
     }
 
-    #[tool(description = "Remove a container using ctr command")]
-    pub async fn remove_container_ctr(
+    #[tool(
+        description = "List the PIDs of the processes inside a task, with the exec id of processes started by an exec",
+        output_schema = cached_schema_for_type::<TaskProcesses>()
+    )]
+    pub async fn list_task_pids_ctr(
         &self,
-        Parameters(RemoveContainerCtrParams {
+        Parameters(ListTaskPidsCtrParams {
             container_id,
             namespace,
-        }): Parameters<RemoveContainerCtrParams>,
+        }): Parameters<ListTaskPidsCtrParams>,
     ) -> Result<CallToolResult, McpError> {
-        debug!("Removing container with ctr: {}", container_id);
-
-        let ctr_cmd = self.create_ctr_cmd(namespace);
-        match ctr_cmd.container_remove(&container_id) {
-            Ok(output) => {
-                let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-                let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-
-                if output.status.success() {
-                    Ok(CallToolResult::success(vec![Content::text(format!(
-                        "Successfully removed container: {}\n\n{}",
-                        container_id, stdout
-                    ))]))
-                } else {
-                    Ok(CallToolResult::error(vec![Content::text(format!(
-                        "Failed to remove container: {}\n\n{}",
-                        container_id, stderr
-                    ))]))
+        debug!(
+            "Listing pids of task {} in namespace {}",
+            container_id, namespace
+        );
+        let channel = match self.namespace_channel(&namespace).await {
+            Ok(channel) => channel,
+            Err(result) => return Ok(result),
+        };
+        match crate::ctr::task::list_pids(&channel, &namespace, &container_id).await {
+            Ok(processes) => Ok(structured(&processes)),
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to list pids of task {}: {}",
+                container_id, e
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Get the cgroup metrics (cpu, memory, pids, io, hugetlb) of a task, decoded for cgroup v1 and v2 hosts",
+        output_schema = cached_schema_for_type::<TaskMetrics>()
+    )]
+    pub async fn task_metrics_ctr(
+        &self,
+        Parameters(TaskMetricsCtrParams {
+            container_id,
+            namespace,
+        }): Parameters<TaskMetricsCtrParams>,
+    ) -> Result<CallToolResult, McpError> {
+        debug!(
+            "Getting metrics of task {} in namespace {}",
+            container_id, namespace
+        );
+        let channel = match self.namespace_channel(&namespace).await {
+            Ok(channel) => channel,
+            Err(result) => return Ok(result),
+        };
+        match crate::ctr::task::task_metrics(&channel, &namespace, &container_id).await {
+            Ok(metrics) => Ok(structured(&metrics)),
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to get metrics of task {}: {}",
+                container_id, e
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "List the snapshots of a snapshotter in a containerd namespace, optionally with the disk usage of each snapshot",
+        output_schema = cached_schema_for_type::<ListPage<SnapshotSummary>>()
+    )]
+    pub async fn list_snapshots_ctr(
+        &self,
+        Parameters(ListSnapshotsCtrParams {
+            namespace,
+            snapshotter,
+            usage,
+            list,
+        }): Parameters<ListSnapshotsCtrParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let snapshotter = snapshotter.unwrap_or_else(|| DEFAULT_SNAPSHOTTER.to_string());
+        debug!(
+            "Listing snapshots of {} in namespace {}",
+            snapshotter, namespace
+        );
+        let channel = match self.snapshotter_channel(&namespace, &snapshotter).await {
+            Ok(channel) => channel,
+            Err(result) => return Ok(result),
+        };
+        match crate::ctr::snapshot::snapshot_summaries(
+            &channel,
+            &namespace,
+            &snapshotter,
+            usage.unwrap_or(false),
+        )
+        .await
+        {
+            Ok(snapshots) => Ok(structured(&render(snapshots, &list))),
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to list snapshots: {}",
+                e
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Show the parent chain of a snapshot down to the base layer, with the disk usage of each snapshot and of the whole chain",
+        output_schema = cached_schema_for_type::<SnapshotChain>()
+    )]
+    pub async fn snapshot_chain_ctr(
+        &self,
+        Parameters(SnapshotChainCtrParams {
+            namespace,
+            snapshotter,
+            key,
+        }): Parameters<SnapshotChainCtrParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let snapshotter = snapshotter.unwrap_or_else(|| DEFAULT_SNAPSHOTTER.to_string());
+        debug!("Getting chain of snapshot {} of {}", key, snapshotter);
+        let channel = match self.snapshotter_channel(&namespace, &snapshotter).await {
+            Ok(channel) => channel,
+            Err(result) => return Ok(result),
+        };
+        match crate::ctr::snapshot::snapshot_chain(&channel, &namespace, &snapshotter, &key).await {
+            Ok(chain) => Ok(structured(&chain)),
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to get chain of snapshot {}: {}",
+                key, e
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Find the snapshots of a snapshotter that no container, image, lease or gc root of the namespace refers to, with the disk space they use",
+        output_schema = cached_schema_for_type::<OrphanSnapshots>()
+    )]
+    pub async fn orphan_snapshots_ctr(
+        &self,
+        Parameters(OrphanSnapshotsCtrParams {
+            namespace,
+            snapshotter,
+        }): Parameters<OrphanSnapshotsCtrParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let snapshotter = snapshotter.unwrap_or_else(|| DEFAULT_SNAPSHOTTER.to_string());
+        debug!(
+            "Finding orphan snapshots of {} in namespace {}",
+            snapshotter, namespace
+        );
+        let channel = match self.snapshotter_channel(&namespace, &snapshotter).await {
+            Ok(channel) => channel,
+            Err(result) => return Ok(result),
+        };
+        match crate::ctr::snapshot::orphan_snapshots(&channel, &namespace, &snapshotter).await {
+            Ok(orphans) => Ok(structured(&orphans)),
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to find orphan snapshots: {}",
+                e
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "List the blobs of the content store of a containerd namespace with their sizes and labels",
+        output_schema = cached_schema_for_type::<ListPage<BlobSummary>>()
+    )]
+    pub async fn list_content_ctr(
+        &self,
+        Parameters(ListContentCtrParams { namespace, list }): Parameters<ListContentCtrParams>,
+    ) -> Result<CallToolResult, McpError> {
+        debug!("Listing content in namespace {}", namespace);
+        let channel = match self.namespace_channel(&namespace).await {
+            Ok(channel) => channel,
+            Err(result) => return Ok(result),
+        };
+        match crate::ctr::content::list_content(&channel, &namespace).await {
+            Ok(blobs) => {
+                let blobs: Vec<BlobSummary> = blobs.into_iter().map(BlobSummary::from).collect();
+                Ok(structured(&render(blobs, &list)))
+            }
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to list content: {}",
+                e
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Fetch a JSON blob, such as a manifest, index or image config, from the content store by digest",
+        output_schema = cached_schema_for_type::<JsonBlob>()
+    )]
+    pub async fn get_content_ctr(
+        &self,
+        Parameters(GetContentCtrParams { namespace, digest }): Parameters<GetContentCtrParams>,
+    ) -> Result<CallToolResult, McpError> {
+        debug!("Getting content {} in namespace {}", digest, namespace);
+        let channel = match self.namespace_channel(&namespace).await {
+            Ok(channel) => channel,
+            Err(result) => return Ok(result),
+        };
+        match crate::ctr::content::get_json_blob(&channel, &namespace, &digest).await {
+            Ok(blob) => Ok(structured(&blob)),
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to get content {}: {}",
+                digest, e
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Walk the content tree of an image for a platform: the index and the platforms it is built for, the selected manifest, the config and the layers with their digests and sizes. Compare the layer digests of two images to find the layers they share",
+        output_schema = cached_schema_for_type::<ImageTree>()
+    )]
+    pub async fn image_tree_ctr(
+        &self,
+        Parameters(ImageTreeCtrParams {
+            namespace,
+            image_reference,
+            platform,
+        }): Parameters<ImageTreeCtrParams>,
+    ) -> Result<CallToolResult, McpError> {
+        debug!(
+            "Getting content tree of image {} in namespace {}",
+            image_reference, namespace
+        );
+        let platform = match platform {
+            Some(platform) => match crate::ctr::image::parse_platform(&platform) {
+                Ok(platform) => platform,
+                Err(e) => return Ok(CallToolResult::error(vec![Content::text(e.message())])),
+            },
+            None => crate::ctr::image::host_platform(),
+        };
+        let channel = match self.namespace_channel(&namespace).await {
+            Ok(channel) => channel,
+            Err(result) => return Ok(result),
+        };
+        match crate::ctr::image::image_tree(&channel, &namespace, &image_reference, &platform).await
+        {
+            Ok(tree) => Ok(structured(&tree)),
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to get content tree of image {}: {}",
+                image_reference, e
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "List the leases of a containerd namespace with their age and expiry. Leases left behind by interrupted pulls keep content and snapshots alive",
+        output_schema = cached_schema_for_type::<ListPage<LeaseSummary>>()
+    )]
+    pub async fn list_leases_ctr(
+        &self,
+        Parameters(ListLeasesCtrParams { namespace, list }): Parameters<ListLeasesCtrParams>,
+    ) -> Result<CallToolResult, McpError> {
+        debug!("Listing leases in namespace {}", namespace);
+        let channel = match self.namespace_channel(&namespace).await {
+            Ok(channel) => channel,
+            Err(result) => return Ok(result),
+        };
+        match crate::ctr::lease::list_leases(&channel, &namespace).await {
+            Ok(leases) => {
+                let leases: Vec<LeaseSummary> =
+                    leases.into_iter().map(LeaseSummary::from).collect();
+                Ok(structured(&render(leases, &list)))
+            }
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to list leases: {}",
+                e
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Create a lease that protects content and snapshots from garbage collection, optionally expiring after some time",
+        output_schema = cached_schema_for_type::<LeaseSummary>()
+    )]
+    pub async fn create_lease_ctr(
+        &self,
+        Parameters(CreateLeaseCtrParams {
+            namespace,
+            id,
+            labels,
+            expire_seconds,
+        }): Parameters<CreateLeaseCtrParams>,
+    ) -> Result<CallToolResult, McpError> {
+        debug!("Creating lease {:?} in namespace {}", id, namespace);
+        let channel = match self.namespace_channel(&namespace).await {
+            Ok(channel) => channel,
+            Err(result) => return Ok(result),
+        };
+        match crate::ctr::lease::create_lease(
+            &channel,
+            &namespace,
+            id,
+            labels.unwrap_or_default(),
+            expire_seconds.map(std::time::Duration::from_secs),
+        )
+        .await
+        {
+            Ok(lease) => Ok(structured(&lease)),
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to create lease: {}",
+                e
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Delete a lease, releasing the content and snapshots it protects to the garbage collector"
+    )]
+    pub async fn delete_lease_ctr(
+        &self,
+        Parameters(DeleteLeaseCtrParams {
+            namespace,
+            id,
+            sync,
+        }): Parameters<DeleteLeaseCtrParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let sync = sync.unwrap_or(false);
+        debug!(
+            "Deleting lease {} in namespace {} (sync: {})",
+            id, namespace, sync
+        );
+        let channel = match self.namespace_channel(&namespace).await {
+            Ok(channel) => channel,
+            Err(result) => return Ok(result),
+        };
+        match crate::ctr::lease::delete_lease(&channel, &namespace, &id, sync).await {
+            Ok(()) => {
+                let result = serde_json::json!({
+                    "success": true,
+                    "id": id,
+                    "namespace": namespace
+                });
+                Ok(CallToolResult::success(vec![Content::text(
+                    result.to_string(),
+                )]))
+            }
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to delete lease {}: {}",
+                id, e
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "List the resources held by a lease: content blobs, ingests and snapshots",
+        output_schema = cached_schema_for_type::<LeaseResources>()
+    )]
+    pub async fn lease_resources_ctr(
+        &self,
+        Parameters(LeaseResourcesCtrParams { namespace, id }): Parameters<LeaseResourcesCtrParams>,
+    ) -> Result<CallToolResult, McpError> {
+        debug!(
+            "Listing resources of lease {} in namespace {}",
+            id, namespace
+        );
+        let channel = match self.namespace_channel(&namespace).await {
+            Ok(channel) => channel,
+            Err(result) => return Ok(result),
+        };
+        match crate::ctr::lease::list_lease_resources(&channel, &namespace, &id).await {
+            Ok(resources) => Ok(structured(&LeaseResources { id, resources })),
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to list resources of lease {}: {}",
+                id, e
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Run containerd's garbage collector and report the content and snapshots of the namespace nothing refers to. With preview set, only report what would be reclaimed without deleting anything",
+        output_schema = cached_schema_for_type::<GcReport>()
+    )]
+    pub async fn gc_ctr(
+        &self,
+        Parameters(GcCtrParams { namespace, preview }): Parameters<GcCtrParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let preview = preview.unwrap_or(false);
+        debug!(
+            "Garbage collecting namespace {} (preview: {})",
+            namespace, preview
+        );
+        let channel = match self.namespace_channel(&namespace).await {
+            Ok(channel) => channel,
+            Err(result) => return Ok(result),
+        };
+        let result = if preview {
+            crate::ctr::gc::gc_preview(&channel, &namespace, None).await
+        } else {
+            crate::ctr::gc::collect(&channel, &namespace).await
+        };
+        match result {
+            Ok(report) => Ok(structured(&report)),
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to garbage collect namespace {}: {}",
+                namespace, e
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Pull an image into a containerd namespace and unpack it for the host platform",
+        output_schema = cached_schema_for_type::<ImageSummary>()
+    )]
+    pub async fn pull_image_ctr(
+        &self,
+        Parameters(PullImageCtrParams {
+            image_reference,
+            namespace,
+        }): Parameters<PullImageCtrParams>,
+    ) -> Result<CallToolResult, McpError> {
+        debug!(
+            "Pulling image {} into namespace {}",
+            image_reference, namespace
+        );
+        let channel = match self.namespace_channel(&namespace).await {
+            Ok(channel) => channel,
+            Err(result) => return Ok(result),
+        };
+        match crate::ctr::image::pull_image(&channel, &namespace, &image_reference).await {
+            Ok(image) => Ok(structured(&image)),
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to pull image {}: {}",
+                image_reference, e
+            ))])),
+        }
+    }
+
+    #[tool(description = "Remove an image from a containerd namespace")]
+    pub async fn remove_image_ctr(
+        &self,
+        Parameters(RemoveImageCtrParams {
+            image_reference,
+            namespace,
+        }): Parameters<RemoveImageCtrParams>,
+    ) -> Result<CallToolResult, McpError> {
+        debug!(
+            "Removing image {} from namespace {}",
+            image_reference, namespace
+        );
+        let channel = match self.namespace_channel(&namespace).await {
+            Ok(channel) => channel,
+            Err(result) => return Ok(result),
+        };
+        match crate::ctr::image::remove_image(&channel, &namespace, &image_reference).await {
+            Ok(()) => {
+                let result = serde_json::json!({
+                    "success": true,
+                    "image_reference": image_reference,
+                    "namespace": namespace
+                });
+                Ok(CallToolResult::success(vec![Content::text(
+                    result.to_string(),
+                )]))
+            }
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to remove image {}: {}",
+                image_reference, e
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Create a container from a pulled image and start it in the background, like 'ctr run -d'. Disabled when the server has a minimum security level",
+        output_schema = cached_schema_for_type::<RunningContainer>()
+    )]
+    pub async fn run_container_ctr(
+        &self,
+        Parameters(RunContainerCtrParams {
+            image_reference,
+            container_id,
+            args,
+            namespace,
+        }): Parameters<RunContainerCtrParams>,
+    ) -> Result<CallToolResult, McpError> {
+        debug!(
+            "Running container - image: {}, id: {}, args: {}",
+            image_reference, container_id, args
+        );
+        if let Some(result) = self.security_bypass("run_container_ctr") {
+            return Ok(result);
+        }
+        let channel = match self.namespace_channel(&namespace).await {
+            Ok(channel) => channel,
+            Err(result) => return Ok(result),
+        };
+
+        let args_vec: Vec<String> = args.split_whitespace().map(|s| s.to_string()).collect();
+        match crate::ctr::container::run_container(
+            &channel,
+            &namespace,
+            &image_reference,
+            &container_id,
+            args_vec,
+        )
+        .await
+        {
+            Ok(container) => Ok(structured(&container)),
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to run container {}: {}",
+                container_id, e
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Remove a container, its stopped task and its snapshot from a containerd namespace"
+    )]
+    pub async fn remove_container_ctr(
+        &self,
+        Parameters(RemoveContainerCtrParams {
+            container_id,
+            namespace,
+        }): Parameters<RemoveContainerCtrParams>,
+    ) -> Result<CallToolResult, McpError> {
+        debug!(
+            "Removing container {} from namespace {}",
+            container_id, namespace
+        );
+        let channel = match self.namespace_channel(&namespace).await {
+            Ok(channel) => channel,
+            Err(result) => return Ok(result),
+        };
+        match crate::ctr::container::remove_container(&channel, &namespace, &container_id).await {
+            Ok(()) => {
+                let result = serde_json::json!({
+                    "success": true,
+                    "container_id": container_id,
+                    "namespace": namespace
+                });
+                Ok(CallToolResult::success(vec![Content::text(
+                    result.to_string(),
+                )]))
+            }
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to remove container {}: {}",
+                container_id, e
+            ))])),
+        }
+    }
+
+    // ================== Namespace Tool Functions ==================
+
+    #[tool(
+        description = "List the containerd namespaces with their labels, e.g. 'default', 'k8s.io', 'moby' or 'buildkit'",
+        output_schema = cached_schema_for_type::<ListPage<NamespaceSummary>>()
+    )]
+    pub async fn list_namespaces(
+        &self,
+        Parameters(ListNamespacesParams { list }): Parameters<ListNamespacesParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let Some(channel) = self.containerd_channel().await else {
+            return Ok(CallToolResult::error(vec![Content::text(
+                "Containerd client not connected",
+            )]));
+        };
+        match crate::ctr::namespace::list_namespaces(&channel).await {
+            Ok(namespaces) => Ok(structured(&render(namespaces, &list))),
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to list namespaces: {}",
+                e
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Create a containerd namespace",
+        output_schema = cached_schema_for_type::<NamespaceSummary>()
+    )]
+    pub async fn create_namespace(
+        &self,
+        Parameters(CreateNamespaceParams { name, labels }): Parameters<CreateNamespaceParams>,
+    ) -> Result<CallToolResult, McpError> {
+        debug!("Creating namespace {}", name);
+        let Some(channel) = self.containerd_channel().await else {
+            return Ok(CallToolResult::error(vec![Content::text(
+                "Containerd client not connected",
+            )]));
+        };
+        match crate::ctr::namespace::create_namespace(&channel, &name, labels.unwrap_or_default())
+            .await
+        {
+            Ok(namespace) => Ok(structured(&namespace)),
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to create namespace {}: {}",
+                name, e
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Set labels on a containerd namespace, other labels are kept and an empty value removes a label",
+        output_schema = cached_schema_for_type::<NamespaceSummary>()
+    )]
+    pub async fn set_namespace_labels(
+        &self,
+        Parameters(SetNamespaceLabelsParams { name, labels }): Parameters<SetNamespaceLabelsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        debug!("Setting labels {:?} on namespace {}", labels, name);
+        let channel = match self.namespace_channel(&name).await {
+            Ok(channel) => channel,
+            Err(result) => return Ok(result),
+        };
+        match crate::ctr::namespace::set_namespace_labels(&channel, &name, labels).await {
+            Ok(namespace) => Ok(structured(&namespace)),
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to set labels on namespace {}: {}",
+                name, e
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Delete a containerd namespace. A namespace that still has containers, tasks, images, snapshots, content or leases is refused unless force is set, which kills its tasks and removes everything in it. Force is rejected unless the minimum security level of the server is privileged, and the 'k8s.io' and 'moby' namespaces are never deleted"
+    )]
+    pub async fn delete_namespace(
+        &self,
+        Parameters(DeleteNamespaceParams { name, force }): Parameters<DeleteNamespaceParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let force = force.unwrap_or(false);
+        debug!("Deleting namespace {} (force: {})", name, force);
+        // Force kills tasks whose containers were never checked against the minimum level
+        if force && self.minimum_security > SecurityLevel::Privileged {
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "Force is disabled, the {} minimum security level of the server only allows deleting empty namespaces",
+                self.minimum_security
+            ))]));
+        }
+        let channel = match self.namespace_channel(&name).await {
+            Ok(channel) => channel,
+            Err(result) => return Ok(result),
+        };
+        match crate::ctr::namespace::delete_namespace(&channel, &name, force).await {
+            Ok(removed) => {
+                let result = serde_json::json!({
+                    "success": true,
+                    "namespace": name,
+                    "removed": removed
+                });
+                Ok(CallToolResult::success(vec![Content::text(
+                    result.to_string(),
+                )]))
+            }
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to delete namespace {}: {}",
+                name, e
+            ))])),
+        }
+    }
+
+    // ================== CRI Tool Functions ==================
+
+    /// This interface may have some security issues, need to be fixed
+    #[tool(description = "Get the containerd log file contents to diagnose runtime issues")]
+    pub async fn get_containerd_logs(
+        &self,
+        Parameters(GetContainerdLogsParams { path }): Parameters<GetContainerdLogsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let path = path.unwrap_or_default();
+        // check if the file exists
+        if !std::path::Path::new(&path).exists() {
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "File {} does not exist",
+                path
+            ))]));
+        }
+        // read the file
+        let content = std::fs::read_to_string(path).unwrap();
+        Ok(CallToolResult::success(vec![Content::text(content)]))
+    }
+
+    #[tool(description = "Reopen target container log")]
+    pub async fn reopen_container_log(
+        &self,
+        Parameters(ReopenContainerLogParams { container_id }): Parameters<ReopenContainerLogParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let lock = self.runtime_client.lock().await;
+        if let Some(client) = &*lock {
+            let mut client_clone = client.clone();
+            match crate::cri::container::reopen_container_log(&mut client_clone, container_id).await
+            {
+                Ok(_) => {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        "Container log reopened successfully",
+                    )]));
+                }
+                Err(e) => {
+                    return Ok(CallToolResult::error(vec![Content::text(format!(
+                        "Failed to reopen container log: {}",
+                        e
+                    ))]));
+                }
+            }
+        }
+        Ok(CallToolResult::error(vec![Content::text(
+            "Runtime client not connected",
+        )]))
+    }
+
+    #[tool(
+        description = "Get version information from the containerd runtime to verify compatibility",
+        output_schema = cached_schema_for_type::<VersionResponse>()
+    )]
+    pub async fn version(&self) -> Result<CallToolResult, McpError> {
+        let lock = self.runtime_client.lock().await;
+        if let Some(client) = &*lock {
+            let mut client_clone = client.clone();
+            match crate::cri::runtime::version(&mut client_clone).await {
+                Ok(version_response) => {
+                    return Ok(structured(&version_response));
+                }
+                Err(e) => {
+                    return Ok(CallToolResult::error(vec![Content::text(format!(
+                        "Failed to get version: {}",
+                        e
+                    ))]));
+                }
+            }
+        }
+        Ok(CallToolResult::error(vec![Content::text(
+            "Runtime client not connected",
+        )]))
+    }
+
+    #[tool(
+        description = "Get the runtime health: RuntimeReady and NetworkReady conditions, runtime handlers and features, plus CNI config and plugin info with verbose"
+    )]
+    pub async fn runtime_status(
+        &self,
+        Parameters(RuntimeStatusParams { verbose }): Parameters<RuntimeStatusParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let lock = self.runtime_client.lock().await;
+        if let Some(client) = &*lock {
+            let mut client_clone = client.clone();
+            match crate::cri::runtime::status(&mut client_clone, verbose.unwrap_or(false)).await {
+                Ok(response) => {
+                    let ready = response
+                        .status
+                        .as_ref()
+                        .is_some_and(crate::cri::runtime::is_ready);
+                    let result = serde_json::json!({
+                        "ready": ready,
+                        "status": response.status,
+                        "runtime_handlers": response.runtime_handlers,
+                        "features": response.features,
+                        "info": crate::cri::runtime::parse_info(&response.info),
+                    });
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        result.to_string(),
+                    )]));
+                }
+                Err(e) => {
+                    return Ok(CallToolResult::error(vec![Content::text(format!(
+                        "Failed to get runtime status: {}",
+                        e
+                    ))]));
                 }
             }
-            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
-                "Failed to execute remove container command: {}",
-                e
-            ))])),
         }
+        Ok(CallToolResult::error(vec![Content::text(
+            "Runtime client not connected",
+        )]))
     }
 
-    // ================== CRI Tool Functions ==================
+    #[tool(description = "Get the global runtime configuration, such as the cgroup driver")]
+    pub async fn runtime_config(&self) -> Result<CallToolResult, McpError> {
+        let lock = self.runtime_client.lock().await;
+        if let Some(client) = &*lock {
+            let mut client_clone = client.clone();
+            match crate::cri::runtime::runtime_config(&mut client_clone).await {
+                Ok(response) => {
+                    let result = serde_json::json!({
+                        "cgroup_driver": response
+                            .linux
+                            .map(|l| crate::cri::runtime::cgroup_driver_name(l.cgroup_driver)),
+                    });
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        result.to_string(),
+                    )]));
+                }
+                Err(e) => {
+                    return Ok(CallToolResult::error(vec![Content::text(format!(
+                        "Failed to get runtime config: {}",
+                        e
+                    ))]));
+                }
+            }
+        }
+        Ok(CallToolResult::error(vec![Content::text(
+            "Runtime client not connected",
+        )]))
+    }
 
-    /// This interface may have some security issues, need to be fixed
-    #[tool(description = "Get the containerd log file contents to diagnose runtime issues")]
-    pub async fn get_containerd_logs(
+    #[tool(description = "Update the runtime configuration, currently the pod CIDR")]
+    pub async fn update_runtime_config(
         &self,
-        Parameters(GetContainerdLogsParams { path }): Parameters<GetContainerdLogsParams>,
+        Parameters(UpdateRuntimeConfigParams { pod_cidr }): Parameters<UpdateRuntimeConfigParams>,
     ) -> Result<CallToolResult, McpError> {
-        let path = path.unwrap_or_default();
-        // check if the file exists
-        if !std::path::Path::new(&path).exists() {
-            return Ok(CallToolResult::error(vec![Content::text(format!(
-                "File {} does not exist",
-                path
-            ))]));
+        let lock = self.runtime_client.lock().await;
+        if let Some(client) = &*lock {
+            let mut client_clone = client.clone();
+            match crate::cri::runtime::update_runtime_config(&mut client_clone, pod_cidr).await {
+                Ok(_) => {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        "{\"success\": true, \"message\": \"Runtime config updated successfully\"}",
+                    )]));
+                }
+                Err(e) => {
+                    return Ok(CallToolResult::error(vec![Content::text(format!(
+                        "Failed to update runtime config: {}",
+                        e
+                    ))]));
+                }
+            }
         }
-        // read the file
-        let content = std::fs::read_to_string(path).unwrap();
-        Ok(CallToolResult::success(vec![Content::text(content)]))
+        Ok(CallToolResult::error(vec![Content::text(
+            "Runtime client not connected",
+        )]))
     }
 
-    #[tool(description = "Reopen target container log")]
-    pub async fn reopen_container_log(
+    #[tool(
+        description = "List all pod sandboxes created by containerd, showing their status and metadata",
+        output_schema = cached_schema_for_type::<ListPage<PodSandbox>>()
+    )]
+    pub async fn list_pods(
         &self,
-        Parameters(ReopenContainerLogParams { container_id }): Parameters<ReopenContainerLogParams>,
+        Parameters(ListPodsParams {
+            pod_id,
+            state,
+            label_selector,
+            name,
+            namespace,
+            list,
+        }): Parameters<ListPodsParams>,
     ) -> Result<CallToolResult, McpError> {
+        let state = match state {
+            Some(state) => match crate::cri::pod::parse_pod_state(&state) {
+                Some(state) => Some(PodSandboxStateValue {
+                    state: state as i32,
+                }),
+                None => {
+                    return Ok(CallToolResult::error(vec![Content::text(format!(
+                        "Invalid pod state '{}', expected ready or notready",
+                        state
+                    ))]));
+                }
+            },
+            None => None,
+        };
+        let filter = PodSandboxFilter {
+            id: pod_id.unwrap_or_default(),
+            state,
+            label_selector: label_selector.unwrap_or_default(),
+        };
+
         let lock = self.runtime_client.lock().await;
         if let Some(client) = &*lock {
             let mut client_clone = client.clone();
-            match crate::cri::container::reopen_container_log(&mut client_clone, container_id).await
+            match crate::cri::pod::list_pods(&mut client_clone, Some(filter)).await {
+                Ok(mut response) => {
+                    response.items.retain(|pod| {
+                        let metadata = pod.metadata.clone().unwrap_or_default();
+                        contains(&metadata.name, name.as_deref())
+                            && contains(&metadata.namespace, namespace.as_deref())
+                    });
+                    return Ok(structured(&render(response.items, &list)));
+                }
+                Err(e) => {
+                    return Ok(CallToolResult::error(vec![Content::text(format!(
+                        "Failed to list pods: {}",
+                        e
+                    ))]));
+                }
+            }
+        }
+        Ok(CallToolResult::error(vec![Content::text(
+            "Runtime client not connected",
+        )]))
+    }
+
+    #[tool(
+        description = "List all containers managed by containerd, including their status, pod association, and metadata",
+        output_schema = cached_schema_for_type::<ListPage<Container>>()
+    )]
+    pub async fn list_containers(
+        &self,
+        Parameters(ListContainersParams {
+            container_id,
+            state,
+            pod_id,
+            label_selector,
+            name,
+            namespace,
+            list,
+        }): Parameters<ListContainersParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let state = match state {
+            Some(state) => match crate::cri::container::parse_container_state(&state) {
+                Some(state) => Some(ContainerStateValue {
+                    state: state as i32,
+                }),
+                None => {
+                    return Ok(CallToolResult::error(vec![Content::text(format!(
+                        "Invalid container state '{}', expected created, running, exited or unknown",
+                        state
+                    ))]));
+                }
+            },
+            None => None,
+        };
+        let filter = ContainerFilter {
+            id: container_id.unwrap_or_default(),
+            state,
+            pod_sandbox_id: pod_id.unwrap_or_default(),
+            label_selector: label_selector.unwrap_or_default(),
+        };
+
+        let lock = self.runtime_client.lock().await;
+        if let Some(client) = &*lock {
+            let mut client_clone = client.clone();
+            let pod_ids = match &namespace {
+                Some(namespace) => match crate::cri::pod::matching_pod_ids(
+                    &mut client_clone,
+                    None,
+                    Some(namespace),
+                )
+                .await
+                {
+                    Ok(pod_ids) => Some(pod_ids),
+                    Err(e) => {
+                        return Ok(CallToolResult::error(vec![Content::text(format!(
+                            "Failed to list pods: {}",
+                            e
+                        ))]));
+                    }
+                },
+                None => None,
+            };
+            match crate::cri::container::list_containers(&mut client_clone, Some(filter)).await {
+                Ok(mut response) => {
+                    response.containers.retain(|container| {
+                        let metadata = container.metadata.clone().unwrap_or_default();
+                        contains(&metadata.name, name.as_deref())
+                            && pod_ids
+                                .as_ref()
+                                .is_none_or(|ids| ids.contains(&container.pod_sandbox_id))
+                    });
+                    return Ok(structured(&render(response.containers, &list)));
+                }
+                Err(e) => {
+                    return Ok(CallToolResult::error(vec![Content::text(format!(
+                        "Failed to list containers: {}",
+                        e
+                    ))]));
+                }
+            }
+        }
+        Ok(CallToolResult::error(vec![Content::text(
+            "Runtime client not connected",
+        )]))
+    }
+
+    #[tool(
+        description = "List all container images available in the containerd registry, including their tags, digests, and sizes",
+        output_schema = cached_schema_for_type::<ListPage<Image>>()
+    )]
+    pub async fn list_images(
+        &self,
+        Parameters(ListImagesParams { image, name, list }): Parameters<ListImagesParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let lock = self.image_client.lock().await;
+        if let Some(client) = &*lock {
+            let mut client_clone = client.clone();
+            match crate::cri::image::list_images(&mut client_clone, image).await {
+                Ok(mut response) => {
+                    response.images.retain(|image| {
+                        name.is_none()
+                            || image
+                                .repo_tags
+                                .iter()
+                                .chain(image.repo_digests.iter())
+                                .any(|reference| contains(reference, name.as_deref()))
+                    });
+                    return Ok(structured(&render(response.images, &list)));
+                }
+                Err(e) => {
+                    return Ok(CallToolResult::error(vec![Content::text(format!(
+                        "Failed to list images: {}",
+                        e
+                    ))]));
+                }
+            }
+        }
+        Ok(CallToolResult::error(vec![Content::text(
+            "Image client not connected",
+        )]))
+    }
+
+    #[tool(
+        description = "Get filesystem information for container images, including storage capacity and usage metrics"
+    )]
+    pub async fn image_fs_info(&self) -> Result<CallToolResult, McpError> {
+        let lock = self.image_client.lock().await;
+        if let Some(client) = &*lock {
+            let mut client_clone = client.clone();
+            match crate::cri::image::image_fs_info(&mut client_clone).await {
+                Ok(response) => {
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        serde_json::to_string(&response).unwrap(),
+                    )]));
+                }
+                Err(e) => {
+                    return Ok(CallToolResult::error(vec![Content::text(format!(
+                        "Failed to get image filesystem info: {}",
+                        e
+                    ))]));
+                }
+            }
+        }
+        Ok(CallToolResult::error(vec![Content::text(
+            "Image client not connected",
+        )]))
+    }
+
+    #[tool(
+        description = "Create a new pod sandbox with customizable configuration including networking, security settings, and resource constraints. Unknown option keys are returned as warnings, or rejected in strict mode; wrong-typed values are rejected with their JSON path"
+    )]
+    pub async fn create_pod(
+        &self,
+        Parameters(CreatePodParams {
+            name,
+            namespace,
+            uid,
+            options,
+            strict,
+            security,
+        }): Parameters<CreatePodParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let security = self.security(security);
+        debug!(
+            "Create pod request - name: {}, namespace: {}, uid: {}, options: {:?}",
+            name, namespace, uid, options
+        );
+        let lock = self.runtime_client.lock().await;
+        if let Some(client) = &*lock {
+            let mut client_clone = client.clone();
+            match crate::cri::pod::create_pod(
+                &mut client_clone,
+                name,
+                namespace,
+                uid,
+                &options,
+                ParseMode::from_strict(strict.unwrap_or(false)),
+                &security,
+            )
+            .await
             {
+                Ok(created) => {
+                    let (pod_id, pod_config) = created.value;
+                    self.pod_configs
+                        .insert(pod_id.clone(), pod_config.clone())
+                        .await;
+                    let create_pod_result = serde_json::json!({
+                        "pod_id": pod_id,
+                        "pod_config": pod_config,
+                        "warnings": created.warnings,
+                    });
+                    return Ok(CallToolResult::success(vec![Content::text(
+                        serde_json::to_string(&create_pod_result).unwrap(),
+                    )]));
+                }
+                Err(e) => return Ok(create_error("pod", e)),
+            }
+        }
+
+        Ok(CallToolResult::error(vec![Content::text(
+            "Runtime client not connected",
+        )]))
+    }
+
+    #[tool(
+        description = "Remove a pod sandbox and clean up all associated resources, including network namespaces"
+    )]
+    pub async fn remove_pod(
+        &self,
+        Parameters(RemovePodParams { pod_id }): Parameters<RemovePodParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let lock = self.runtime_client.lock().await;
+        if let Some(client) = &*lock {
+            let mut client_clone = client.clone();
+            let uid = crate::cri::pod::pod_status(&mut client_clone, pod_id.clone(), false)
+                .await
+                .ok()
+                .and_then(|response| response.status)
+                .and_then(|status| status.metadata)
+                .map(|metadata| metadata.uid);
+            match crate::cri::pod::remove_pod(&mut client_clone, pod_id.clone()).await {
                 Ok(_) => {
+                    self.pod_configs.remove(&pod_id).await;
+                    if let Some(uid) = uid {
+                        remove_pod_volumes(&uid).await;
+                    }
                     return Ok(CallToolResult::success(vec![Content::text(
-                        "Container log reopened successfully",
+                        "{\"success\": true, \"message\": \"Pod removed successfully\"}",
                     )]));
                 }
                 Err(e) => {
                     return Ok(CallToolResult::error(vec![Content::text(format!(
-                        "Failed to reopen container log: {}",
+                        "Failed to remove pod: {}",
                         e
                     ))]));
                 }
             }
         }
+
         Ok(CallToolResult::error(vec![Content::text(
             "Runtime client not connected",
         )]))
     }
-
+    /// containerd panics on a missing sandbox_config, so one is always resolved from
+    /// the pod configs when the caller does not pass it
     #[tool(
-        description = "Get version information from the containerd runtime to verify compatibility"
+        description = "Create a new container within a pod sandbox with configurable runtime settings, environment variables, mounts, and image specification. Unknown option keys are returned as warnings, or rejected in strict mode; wrong-typed values are rejected with their JSON path"
     )]
-    pub async fn version(&self) -> Result<CallToolResult, McpError> {
+    pub async fn create_container(
+        &self,
+        Parameters(CreateContainerParams {
+            pod_id,
+            name,
+            image,
+            options,
+            pod_config,
+            strict,
+            security,
+        }): Parameters<CreateContainerParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let security = self.security(security);
+        debug!(
+            "Create container request - pod_id: {}, name: {}, image: {}, options: {:?}",
+            pod_id, name, image, options
+        );
         let lock = self.runtime_client.lock().await;
         if let Some(client) = &*lock {
             let mut client_clone = client.clone();
-            match crate::cri::runtime::version(&mut client_clone).await {
-                Ok(version_response) => {
+            let mode = ParseMode::from_strict(strict.unwrap_or(false));
+            let sandbox_config = match self
+                .pod_configs
+                .resolve(&mut client_clone, &pod_id, pod_config.as_ref(), mode)
+                .await
+            {
+                Ok(sandbox_config) => sandbox_config,
+                Err(e) => return Ok(create_error("container", e)),
+            };
+            match crate::cri::container::create_container(
+                &mut client_clone,
+                pod_id,
+                name,
+                image,
+                &options,
+                sandbox_config.value,
+                mode,
+                &security,
+            )
+            .await
+            {
+                Ok(created) => {
+                    let mut warnings = created.warnings;
+                    warnings.extend(sandbox_config.warnings);
+                    let result = serde_json::json!({
+                        "container_id": created.value,
+                        "warnings": warnings,
+                    });
                     return Ok(CallToolResult::success(vec![Content::text(
-                        serde_json::to_string(&version_response).unwrap(),
+                        result.to_string(),
                     )]));
                 }
-                Err(e) => {
-                    return Ok(CallToolResult::error(vec![Content::text(format!(
-                        "Failed to get version: {}",
-                        e
-                    ))]));
-                }
+                Err(e) => return Ok(create_error("container", e)),
             }
         }
+
         Ok(CallToolResult::error(vec![Content::text(
             "Runtime client not connected",
         )]))
     }
 
     #[tool(
-        description = "List all pod sandboxes created by containerd, showing their status and metadata"
+        description = "Run a pod in one call: pull the images that are missing, run the pod sandbox with its port mappings, then create and start each container with its command, env and mounts. If any step fails, the containers created so far and the sandbox are removed again",
+        output_schema = cached_schema_for_type::<RunningPod>()
     )]
-    pub async fn list_pods(&self) -> Result<CallToolResult, McpError> {
-        let lock = self.runtime_client.lock().await;
-        if let Some(client) = &*lock {
-            let mut client_clone = client.clone();
-            match crate::cri::pod::list_pods(&mut client_clone).await {
-                Ok(response) => {
-                    return Ok(CallToolResult::success(vec![Content::text(
-                        serde_json::to_string(&response).unwrap(),
-                    )]));
-                }
-                Err(e) => {
-                    return Ok(CallToolResult::error(vec![Content::text(format!(
-                        "Failed to list pods: {}",
-                        e
-                    ))]));
-                }
-            }
+    pub async fn run_pod(
+        &self,
+        Parameters(RunPodParams {
+            pod,
+            strict,
+            security,
+        }): Parameters<RunPodParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let security = self.security(security);
+        debug!("Run pod request: {:?}", pod);
+        let Some(mut runtime_client) = self.runtime_client.lock().await.clone() else {
+            return Ok(CallToolResult::error(vec![Content::text(
+                "Runtime client not connected",
+            )]));
+        };
+        let Some(mut image_client) = self.image_client.lock().await.clone() else {
+            return Ok(CallToolResult::error(vec![Content::text(
+                "Image client not connected",
+            )]));
+        };
+        match crate::cri::workload::run_pod(
+            &mut runtime_client,
+            &mut image_client,
+            &self.pod_configs,
+            &pod,
+            ParseMode::from_strict(strict.unwrap_or(false)),
+            &security,
+            |image| self.credentials.lookup(image),
+        )
+        .await
+        {
+            Ok(running) => Ok(structured(&running)),
+            Err(failure) => Ok(CallToolResult::error(vec![Content::text(
+                serde_json::to_string(&failure).unwrap(),
+            )])),
         }
-        Ok(CallToolResult::error(vec![Content::text(
-            "Runtime client not connected",
-        )]))
     }
 
     #[tool(
-        description = "List all containers managed by containerd, including their status, pod association, and metadata"
+        description = "Run a pod from a Kubernetes v1 Pod manifest in YAML or JSON, the way run_pod does. Containers, command/args, env, hostPath and emptyDir volume mounts, host ports, security contexts, cpu/memory resources, host namespaces and dnsConfig are translated; every other field is listed as unsupported in the result. emptyDir volumes are directories this server creates, so they need it to run on the containerd host, and are removed with the pod. Set dry_run to only see the translation",
+        output_schema = cached_schema_for_type::<ManifestRun>()
     )]
-    pub async fn list_containers(&self) -> Result<CallToolResult, McpError> {
-        let lock = self.runtime_client.lock().await;
-        if let Some(client) = &*lock {
-            let request = crate::api::runtime::v1::ListContainersRequest { filter: None };
-            let response = client.clone().list_containers(request).await.unwrap();
-            return Ok(CallToolResult::success(vec![Content::text(
-                serde_json::to_string(&response.into_inner()).unwrap(),
+    pub async fn run_pod_manifest(
+        &self,
+        Parameters(RunPodManifestParams {
+            manifest,
+            dry_run,
+            strict,
+            security,
+        }): Parameters<RunPodManifestParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let security = self.security(security);
+        let translated = match crate::cri::manifest::parse_manifest(&manifest) {
+            Ok(translated) => translated,
+            Err(e) => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Failed to translate manifest: {}",
+                    e
+                ))]));
+            }
+        };
+        if strict.unwrap_or(false) && !translated.unsupported.is_empty() {
+            let fields: Vec<String> = translated
+                .unsupported
+                .iter()
+                .map(|f| format!("{} ({})", f.field, f.reason))
+                .collect();
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "The manifest has unsupported fields: {}",
+                fields.join(", ")
+            ))]));
+        }
+        // checked up front, volume directories must not be created for a pod that is refused
+        let mode = ParseMode::from_strict(strict.unwrap_or(false));
+        if let Err(e) = crate::cri::workload::check_pod_spec(&translated.pod, mode, &security).await
+        {
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to run manifest: {}",
+                e
+            ))]));
+        }
+        if dry_run.unwrap_or(false) {
+            return Ok(structured(&ManifestRun {
+                pod: None,
+                spec: Some(translated.pod),
+                host_dirs: translated.host_dirs,
+                unsupported: translated.unsupported,
+            }));
+        }
+
+        let Some(mut runtime_client) = self.runtime_client.lock().await.clone() else {
+            return Ok(CallToolResult::error(vec![Content::text(
+                "Runtime client not connected",
+            )]));
+        };
+        let Some(mut image_client) = self.image_client.lock().await.clone() else {
+            return Ok(CallToolResult::error(vec![Content::text(
+                "Image client not connected",
             )]));
+        };
+        // the uid comes from the manifest, and so may be that of a pod that is running already
+        let uid = translated.pod.uid.clone().unwrap_or_default();
+        match crate::cri::pod::list_pods(&mut runtime_client, None).await {
+            Ok(response) => {
+                let used = response
+                    .items
+                    .iter()
+                    .find(|pod| pod.metadata.as_ref().is_some_and(|m| m.uid == uid));
+                if let Some(pod) = used {
+                    return Ok(CallToolResult::error(vec![Content::text(format!(
+                        "Pod uid {} is already used by pod {}",
+                        uid, pod.id
+                    ))]));
+                }
+            }
+            Err(e) => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Failed to list pods: {}",
+                    e
+                ))]));
+            }
+        }
+        // only a volume root this call created is removed again on failure
+        let root = crate::cri::manifest::volume_root(&uid);
+        let claim = root.as_ref().is_some_and(|root| {
+            translated
+                .host_dirs
+                .iter()
+                .any(|d| Path::new(d).starts_with(root))
+        });
+        if claim {
+            if let Err(e) = crate::cri::manifest::claim_volume_root(&uid).await {
+                let message = if e.kind() == std::io::ErrorKind::AlreadyExists {
+                    format!("The volumes of pod uid {} exist already", uid)
+                } else {
+                    format!("Failed to create the volumes of pod uid {}: {}", uid, e)
+                };
+                return Ok(CallToolResult::error(vec![Content::text(message)]));
+            }
+        }
+        let mut created = Vec::new();
+        if let Err(e) =
+            crate::cri::manifest::create_host_dirs(&translated.host_dirs, &mut created).await
+        {
+            let mut failure = RunPodFailure::new(format!("Failed to create {}", e));
+            roll_back_manifest_volumes(&uid, claim, &created, &mut failure).await;
+            return Ok(CallToolResult::error(vec![Content::text(
+                serde_json::to_string(&failure).unwrap(),
+            )]));
+        }
+        match crate::cri::workload::run_pod(
+            &mut runtime_client,
+            &mut image_client,
+            &self.pod_configs,
+            &translated.pod,
+            mode,
+            &security,
+            |image| self.credentials.lookup(image),
+        )
+        .await
+        {
+            Ok(running) => Ok(structured(&ManifestRun {
+                pod: Some(running),
+                spec: None,
+                host_dirs: translated.host_dirs,
+                unsupported: translated.unsupported,
+            })),
+            Err(mut failure) => {
+                roll_back_manifest_volumes(&uid, claim, &created, &mut failure).await;
+                Ok(CallToolResult::error(vec![Content::text(
+                    serde_json::to_string(&failure).unwrap(),
+                )]))
+            }
         }
-        Ok(CallToolResult::error(vec![Content::text(
-            "Runtime client not connected",
-        )]))
     }
 
     #[tool(
-        description = "List all container images available in the containerd registry, including their tags, digests, and sizes"
+        description = "Remove a container from a pod sandbox and clean up all associated resources, including filesystem mounts"
     )]
-    pub async fn list_images(&self) -> Result<CallToolResult, McpError> {
-        let lock = self.image_client.lock().await;
+    pub async fn remove_container(
+        &self,
+        Parameters(RemoveContainerParams { container_id }): Parameters<RemoveContainerParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let lock = self.runtime_client.lock().await;
         if let Some(client) = &*lock {
             let mut client_clone = client.clone();
-            match crate::cri::image::list_images(&mut client_clone).await {
-                Ok(response) => {
+            match crate::cri::container::remove_container(&mut client_clone, container_id).await {
+                Ok(_) => {
                     return Ok(CallToolResult::success(vec![Content::text(
-                        serde_json::to_string(&response).unwrap(),
+                        "{\"success\": true, \"message\": \"Container removed successfully\"}",
                     )]));
                 }
                 Err(e) => {
                     return Ok(CallToolResult::error(vec![Content::text(format!(
-                        "Failed to list images: {}",
+                        "Failed to remove container: {}",
                         e
                     ))]));
                 }
             }
         }
+
         Ok(CallToolResult::error(vec![Content::text(
-            "Image client not connected",
+            "Runtime client not connected",
         )]))
     }
 
-    #[tool(
-        description = "Get filesystem information for container images, including storage capacity and usage metrics"
-    )]
-    pub async fn image_fs_info(&self) -> Result<CallToolResult, McpError> {
-        let lock = self.image_client.lock().await;
+    #[tool(description = "Stop a running pod sandbox and all its containers")]
+    pub async fn stop_pod(
+        &self,
+        Parameters(StopPodParams { pod_id }): Parameters<StopPodParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let lock = self.runtime_client.lock().await;
         if let Some(client) = &*lock {
             let mut client_clone = client.clone();
-            match crate::cri::image::image_fs_info(&mut client_clone).await {
-                Ok(response) => {
+            match crate::cri::pod::stop_pod(&mut client_clone, pod_id).await {
+                Ok(_) => {
                     return Ok(CallToolResult::success(vec![Content::text(
-                        serde_json::to_string(&response).unwrap(),
+                        "{\"success\": true, \"message\": \"Pod stopped successfully\"}",
                     )]));
                 }
                 Err(e) => {
                     return Ok(CallToolResult::error(vec![Content::text(format!(
-                        "Failed to get image filesystem info: {}",
+                        "Failed to stop pod: {}",
                         e
                     ))]));
                 }
             }
         }
+
         Ok(CallToolResult::error(vec![Content::text(
-            "Image client not connected",
+            "Runtime client not connected",
         )]))
     }
 
-    #[tool(
-        description = "Create a new pod sandbox with customizable configuration including networking, security settings, and resource constraints"
-    )]
-    pub async fn create_pod(
+    #[tool(description = "Start a created container, making it ready to execute workloads")]
+    pub async fn start_container(
         &self,
-        Parameters(CreatePodParams {
-            name,
-            namespace,
-            uid,
-            options,
-        }): Parameters<CreatePodParams>,
+        Parameters(StartContainerParams { container_id }): Parameters<StartContainerParams>,
     ) -> Result<CallToolResult, McpError> {
-        debug!(
-            "Create pod request - name: {}, namespace: {}, uid: {}, options: {:?}",
-            name, namespace, uid, options
-        );
         let lock = self.runtime_client.lock().await;
         if let Some(client) = &*lock {
             let mut client_clone = client.clone();
-            match crate::cri::pod::create_pod(&mut client_clone, name, namespace, uid, options)
-                .await
-            {
-                Ok((pod_id, pod_config)) => {
-                    let create_pod_result = serde_json::json!({
-                        "pod_id": pod_id,
-                        "pod_config": pod_config
-                    });
+            match crate::cri::container::start_container(&mut client_clone, container_id).await {
+                Ok(_) => {
                     return Ok(CallToolResult::success(vec![Content::text(
-                        serde_json::to_string(&create_pod_result).unwrap(),
+                        "{\"success\": true, \"message\": \"Container started successfully\"}",
                     )]));
                 }
                 Err(e) => {
                     return Ok(CallToolResult::error(vec![Content::text(format!(
-                        "Failed to create pod: {}",
+                        "Failed to start container: {}",
                         e
                     ))]));
                 }
@@ -762,25 +2951,23 @@ impl Server {
         )]))
     }
 
-    #[tool(
-        description = "Remove a pod sandbox and clean up all associated resources, including network namespaces"
-    )]
-    pub async fn remove_pod(
+    #[tool(description = "Stop a running container gracefully with an optional timeout")]
+    pub async fn stop_container(
         &self,
-        Parameters(RemovePodParams { pod_id }): Parameters<RemovePodParams>,
+        Parameters(StopContainerParams { id, timeout }): Parameters<StopContainerParams>,
     ) -> Result<CallToolResult, McpError> {
         let lock = self.runtime_client.lock().await;
         if let Some(client) = &*lock {
             let mut client_clone = client.clone();
-            match crate::cri::pod::remove_pod(&mut client_clone, pod_id).await {
+            match crate::cri::container::stop_container(&mut client_clone, id, timeout).await {
                 Ok(_) => {
                     return Ok(CallToolResult::success(vec![Content::text(
-                        "{\"success\": true, \"message\": \"Pod removed successfully\"}",
+                        "{\"success\": true, \"message\": \"Container stopped successfully\"}",
                     )]));
                 }
                 Err(e) => {
                     return Ok(CallToolResult::error(vec![Content::text(format!(
-                        "Failed to remove pod: {}",
+                        "Failed to stop container: {}",
                         e
                     ))]));
                 }
@@ -791,77 +2978,239 @@ impl Server {
             "Runtime client not connected",
         )]))
     }
-    /// sandbox_config is none will cause containerd panic
-    /// fix it , and use more greater method to create container
+
     #[tool(
-        description = "Create a new container within a pod sandbox with configurable runtime settings, environment variables, mounts, and image specification"
+        description = "Execute a command in a running container in sync mode",
+        output_schema = cached_schema_for_type::<CommandOutput>()
     )]
-    pub async fn create_container(
+    pub async fn exec_sync(
         &self,
-        Parameters(CreateContainerParams {
-            pod_id,
-            name,
-            image,
-            options,
-            pod_config,
-        }): Parameters<CreateContainerParams>,
+        Parameters(ExecSyncParams {
+            container_id,
+            command,
+            timeout,
+        }): Parameters<ExecSyncParams>,
     ) -> Result<CallToolResult, McpError> {
-        debug!(
-            "Create container request - pod_id: {}, name: {}, image: {}, options: {:?}",
-            pod_id, name, image, options
-        );
-        let lock = self.runtime_client.lock().await;
-        if let Some(client) = &*lock {
+        if command.is_empty() {
+            return Ok(CallToolResult::error(vec![Content::text(
+                "Command cannot be empty",
+            )]));
+        }
+        let Some(mut client) = self.runtime_client.lock().await.clone() else {
+            return Ok(CallToolResult::error(vec![Content::text(
+                "Runtime client not connected",
+            )]));
+        };
+        match crate::cri::container::exec_sync(&mut client, container_id, command, timeout).await {
+            Ok(response) => Ok(structured(&CommandOutput {
+                exit_code: response.exit_code,
+                stdout: String::from_utf8_lossy(&response.stdout).to_string(),
+                stderr: String::from_utf8_lossy(&response.stderr).to_string(),
+            })),
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to execute command: {}",
+                e
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Start an interactive exec session in a running container for long-running or streaming commands (top, tail -f, shells). Returns a session id to use with exec_read, exec_write, exec_resize and exec_close",
+        output_schema = cached_schema_for_type::<ExecSessionInfo>()
+    )]
+    pub async fn exec(
+        &self,
+        Parameters(ExecParams {
+            container_id,
+            command,
+            tty,
+            stdin,
+        }): Parameters<ExecParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if command.is_empty() {
+            return Ok(CallToolResult::error(vec![Content::text(
+                "Command cannot be empty",
+            )]));
+        }
+        let tty = tty.unwrap_or(false);
+        let stdin = stdin.unwrap_or(false);
+
+        let response = {
+            let lock = self.runtime_client.lock().await;
+            let Some(client) = &*lock else {
+                return Ok(CallToolResult::error(vec![Content::text(
+                    "Runtime client not connected",
+                )]));
+            };
             let mut client_clone = client.clone();
-            match crate::cri::container::create_container(
+            match crate::cri::container::exec(
                 &mut client_clone,
-                pod_id,
-                name,
-                image,
-                options,
-                pod_config,
+                container_id.clone(),
+                command,
+                tty,
+                stdin,
             )
             .await
             {
-                Ok(container_id) => {
-                    return Ok(CallToolResult::success(vec![Content::text(format!(
-                        "{{\"container_id\": \"{}\"}}",
-                        container_id
-                    ))]));
-                }
+                Ok(response) => response,
                 Err(e) => {
                     return Ok(CallToolResult::error(vec![Content::text(format!(
-                        "Failed to create container: {}",
+                        "Failed to execute command: {}",
                         e
                     ))]));
                 }
             }
+        };
+
+        match ExecSession::connect(&response.url, container_id, tty, stdin).await {
+            Ok(session) => {
+                let session_id = uuid::Uuid::new_v4().to_string();
+                self.exec_sessions
+                    .lock()
+                    .await
+                    .insert(session_id.clone(), Arc::new(session));
+                Ok(structured(&ExecSessionInfo {
+                    session_id,
+                    tty,
+                    stdin,
+                }))
+            }
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to open exec stream: {}",
+                e
+            ))])),
         }
+    }
 
-        Ok(CallToolResult::error(vec![Content::text(
-            "Runtime client not connected",
-        )]))
+    #[tool(description = "Write data to the stdin of an exec session")]
+    pub async fn exec_write(
+        &self,
+        Parameters(ExecWriteParams { session_id, data }): Parameters<ExecWriteParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let Some(session) = self.exec_session(&session_id).await else {
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "Exec session {} not found",
+                session_id
+            ))]));
+        };
+        match session.write_stdin(data.as_bytes()) {
+            Ok(_) => Ok(CallToolResult::success(vec![Content::text(
+                "{\"success\": true, \"message\": \"Data written to stdin\"}",
+            )])),
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to write to exec session: {}",
+                e
+            ))])),
+        }
     }
 
     #[tool(
-        description = "Remove a container from a pod sandbox and clean up all associated resources, including filesystem mounts"
+        description = "Read stdout and stderr produced by an exec session since the last read, including the exit code once the command has finished. The session is closed once its exit is read, and after 10 minutes without use",
+        output_schema = cached_schema_for_type::<ExecOutput>()
     )]
-    pub async fn remove_container(
+    pub async fn exec_read(
         &self,
-        Parameters(RemoveContainerParams { container_id }): Parameters<RemoveContainerParams>,
+        Parameters(ExecReadParams {
+            session_id,
+            wait_ms,
+        }): Parameters<ExecReadParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let Some(session) = self.exec_session(&session_id).await else {
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "Exec session {} not found",
+                session_id
+            ))]));
+        };
+        let wait = std::time::Duration::from_millis(wait_ms.unwrap_or(1000));
+        let output = session.read(wait).await;
+        // The exit status has been handed out with the last output, nothing is left to read
+        if output.exited {
+            self.exec_sessions.lock().await.remove(&session_id);
+        }
+        Ok(structured(&output))
+    }
+
+    #[tool(description = "Resize the terminal of an exec session opened with tty")]
+    pub async fn exec_resize(
+        &self,
+        Parameters(ExecResizeParams {
+            session_id,
+            width,
+            height,
+        }): Parameters<ExecResizeParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let Some(session) = self.exec_session(&session_id).await else {
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "Exec session {} not found",
+                session_id
+            ))]));
+        };
+        match session.resize(width, height) {
+            Ok(_) => Ok(CallToolResult::success(vec![Content::text(
+                "{\"success\": true, \"message\": \"Terminal resized\"}",
+            )])),
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to resize exec session: {}",
+                e
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Close an exec session and return any output that was not read yet",
+        output_schema = cached_schema_for_type::<ExecOutput>()
+    )]
+    pub async fn exec_close(
+        &self,
+        Parameters(ExecCloseParams { session_id }): Parameters<ExecCloseParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let Some(session) = self.exec_sessions.lock().await.remove(&session_id) else {
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "Exec session {} not found",
+                session_id
+            ))]));
+        };
+        let output = session.close().await;
+        Ok(structured(&output))
+    }
+
+    #[tool(
+        description = "Inspect a single container: full status, state, exit code, mounts, resources and, with verbose, the parsed runtime info (OCI spec, sandbox pid, snapshot key)"
+    )]
+    pub async fn inspect_container(
+        &self,
+        Parameters(InspectContainerParams {
+            container_id,
+            verbose,
+        }): Parameters<InspectContainerParams>,
     ) -> Result<CallToolResult, McpError> {
         let lock = self.runtime_client.lock().await;
         if let Some(client) = &*lock {
             let mut client_clone = client.clone();
-            match crate::cri::container::remove_container(&mut client_clone, container_id).await {
-                Ok(_) => {
+            match crate::cri::container::container_status(
+                &mut client_clone,
+                container_id,
+                verbose.unwrap_or(true),
+            )
+            .await
+            {
+                Ok(response) => {
+                    let state = response
+                        .status
+                        .as_ref()
+                        .map(|s| crate::cri::container::container_state_name(s.state));
+                    let result = serde_json::json!({
+                        "state": state,
+                        "status": response.status,
+                        "info": crate::cri::runtime::parse_info(&response.info),
+                    });
                     return Ok(CallToolResult::success(vec![Content::text(
-                        "{\"success\": true, \"message\": \"Container removed successfully\"}",
+                        result.to_string(),
                     )]));
                 }
                 Err(e) => {
                     return Ok(CallToolResult::error(vec![Content::text(format!(
-                        "Failed to remove container: {}",
+                        "Failed to inspect container: {}",
                         e
                     ))]));
                 }
@@ -873,23 +3222,49 @@ impl Server {
         )]))
     }
 
-    #[tool(description = "Stop a running pod sandbox and all its containers")]
-    pub async fn stop_pod(
+    #[tool(
+        description = "Inspect a single pod sandbox: full status, state, network IPs, container statuses and, with verbose, the parsed runtime info (network namespace, sandbox pid, runtime spec)"
+    )]
+    pub async fn inspect_pod(
         &self,
-        Parameters(StopPodParams { pod_id }): Parameters<StopPodParams>,
+        Parameters(InspectPodParams { pod_id, verbose }): Parameters<InspectPodParams>,
     ) -> Result<CallToolResult, McpError> {
         let lock = self.runtime_client.lock().await;
         if let Some(client) = &*lock {
             let mut client_clone = client.clone();
-            match crate::cri::pod::stop_pod(&mut client_clone, pod_id).await {
-                Ok(_) => {
+            match crate::cri::pod::pod_status(&mut client_clone, pod_id, verbose.unwrap_or(true))
+                .await
+            {
+                Ok(response) => {
+                    let state = response
+                        .status
+                        .as_ref()
+                        .map(|s| crate::cri::pod::pod_state_name(s.state));
+                    let ips: Vec<String> = response
+                        .status
+                        .as_ref()
+                        .and_then(|s| s.network.as_ref())
+                        .map(|network| {
+                            std::iter::once(network.ip.clone())
+                                .chain(network.additional_ips.iter().map(|ip| ip.ip.clone()))
+                                .filter(|ip| !ip.is_empty())
+                                .collect()
+                        })
+                        .unwrap_or_default();
+                    let result = serde_json::json!({
+                        "state": state,
+                        "ips": ips,
+                        "status": response.status,
+                        "containers_statuses": response.containers_statuses,
+                        "info": crate::cri::runtime::parse_info(&response.info),
+                    });
                     return Ok(CallToolResult::success(vec![Content::text(
-                        "{\"success\": true, \"message\": \"Pod stopped successfully\"}",
+                        result.to_string(),
                     )]));
                 }
                 Err(e) => {
                     return Ok(CallToolResult::error(vec![Content::text(format!(
-                        "Failed to stop pod: {}",
+                        "Failed to inspect pod: {}",
                         e
                     ))]));
                 }
@@ -901,23 +3276,64 @@ impl Server {
         )]))
     }
 
-    #[tool(description = "Start a created container, making it ready to execute workloads")]
-    pub async fn start_container(
+    #[tool(
+        description = "Update the CPU, memory, cpuset, hugepage and cgroup v2 limits of a running container without recreating it. Unset fields keep their current value. Returns the resources before and after the update"
+    )]
+    pub async fn update_container_resources(
         &self,
-        Parameters(StartContainerParams { container_id }): Parameters<StartContainerParams>,
+        Parameters(UpdateContainerResourcesParams {
+            container_id,
+            cpu_shares,
+            cpu_quota,
+            cpu_period,
+            cpuset_cpus,
+            cpuset_mems,
+            memory_limit_in_bytes,
+            memory_swap_limit_in_bytes,
+            hugepage_limits,
+            unified,
+        }): Parameters<UpdateContainerResourcesParams>,
     ) -> Result<CallToolResult, McpError> {
+        let update = crate::cri::container::ContainerResourceUpdate {
+            cpu_shares,
+            cpu_quota,
+            cpu_period,
+            cpuset_cpus,
+            cpuset_mems,
+            memory_limit_in_bytes,
+            memory_swap_limit_in_bytes,
+            hugepage_limits,
+            unified,
+        };
+        if update.is_empty() {
+            return Ok(CallToolResult::error(vec![Content::text(
+                "No resources to update were given",
+            )]));
+        }
+
         let lock = self.runtime_client.lock().await;
         if let Some(client) = &*lock {
             let mut client_clone = client.clone();
-            match crate::cri::container::start_container(&mut client_clone, container_id).await {
-                Ok(_) => {
+            match crate::cri::container::update_container_resources(
+                &mut client_clone,
+                container_id,
+                &update,
+            )
+            .await
+            {
+                Ok((before, after)) => {
+                    let result = serde_json::json!({
+                        "success": true,
+                        "before": before,
+                        "after": after,
+                    });
                     return Ok(CallToolResult::success(vec![Content::text(
-                        "{\"success\": true, \"message\": \"Container started successfully\"}",
+                        result.to_string(),
                     )]));
                 }
                 Err(e) => {
                     return Ok(CallToolResult::error(vec![Content::text(format!(
-                        "Failed to start container: {}",
+                        "Failed to update container resources: {}",
                         e
                     ))]));
                 }
@@ -929,72 +3345,259 @@ impl Server {
         )]))
     }
 
-    #[tool(description = "Stop a running container gracefully with an optional timeout")]
-    pub async fn stop_container(
+    #[tool(
+        description = "Checkpoint a running container (CRIU) into an archive for offline analysis, returning the archive path and size"
+    )]
+    pub async fn checkpoint_container(
         &self,
-        Parameters(StopContainerParams { id, timeout }): Parameters<StopContainerParams>,
+        Parameters(CheckpointContainerParams {
+            container_id,
+            location,
+            timeout,
+        }): Parameters<CheckpointContainerParams>,
     ) -> Result<CallToolResult, McpError> {
-        let lock = self.runtime_client.lock().await;
-        if let Some(client) = &*lock {
-            let mut client_clone = client.clone();
-            match crate::cri::container::stop_container(&mut client_clone, id, timeout).await {
-                Ok(_) => {
-                    return Ok(CallToolResult::success(vec![Content::text(
-                        "{\"success\": true, \"message\": \"Container stopped successfully\"}",
-                    )]));
-                }
+        let location = match crate::cri::checkpoint::checkpoint_location(
+            &self.checkpoint_dir,
+            &container_id,
+            location,
+        ) {
+            Ok(location) => location,
+            Err(e) => {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Failed to checkpoint container: {}",
+                    e
+                ))]));
+            }
+        };
+        let Some(mut client) = self.runtime_client.lock().await.clone() else {
+            return Ok(CallToolResult::error(vec![Content::text(
+                "Runtime client not connected",
+            )]));
+        };
+        match crate::cri::checkpoint::checkpoint_container(
+            &mut client,
+            &self.checkpoint_dir,
+            container_id,
+            location,
+            timeout.unwrap_or(0),
+        )
+        .await
+        {
+            Ok(archive) => Ok(CallToolResult::success(vec![Content::text(
+                serde_json::to_string(&archive).unwrap(),
+            )])),
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to checkpoint container: {}",
+                e
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "List container checkpoint archives in the server checkpoint directory, including those written to nested locations"
+    )]
+    pub async fn list_checkpoints(&self) -> Result<CallToolResult, McpError> {
+        match crate::cri::checkpoint::list_checkpoints(&self.checkpoint_dir) {
+            Ok(archives) => Ok(CallToolResult::success(vec![Content::text(
+                serde_json::json!({
+                    "checkpoint_dir": self.checkpoint_dir,
+                    "checkpoints": archives,
+                })
+                .to_string(),
+            )])),
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to list checkpoints in {}: {}",
+                self.checkpoint_dir.display(),
+                e
+            ))])),
+        }
+    }
+
+    #[tool(
+        description = "Wait until a container reaches a target state (created, running or exited) and return its final status including exit code, reason and message. Sends progress notifications while waiting"
+    )]
+    pub async fn wait_for_container_state(
+        &self,
+        Parameters(WaitForContainerStateParams {
+            container_id,
+            state,
+            timeout,
+        }): Parameters<WaitForContainerStateParams>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let Some(target) = crate::cri::container::parse_container_state(&state) else {
+            return Ok(CallToolResult::error(vec![Content::text(format!(
+                "Invalid container state: {}, expected one of created, running, exited",
+                state
+            ))]));
+        };
+        let Some(mut client) = self.runtime_client.lock().await.clone() else {
+            return Ok(CallToolResult::error(vec![Content::text(
+                "Runtime client not connected",
+            )]));
+        };
+
+        // Subscribe before the first status check so no transition is missed
+        let mut events = self.event_tx.subscribe();
+        let timeout = std::time::Duration::from_secs(timeout.unwrap_or(60));
+        let started = tokio::time::Instant::now();
+        let deadline = started + timeout;
+        let progress_token = context.meta.get_progress_token();
+
+        loop {
+            let status = match crate::cri::container::container_status(
+                &mut client,
+                container_id.clone(),
+                false,
+            )
+            .await
+            {
+                Ok(response) => response.status,
                 Err(e) => {
                     return Ok(CallToolResult::error(vec![Content::text(format!(
-                        "Failed to stop container: {}",
+                        "Failed to get container status: {}",
                         e
                     ))]));
                 }
+            };
+            let Some(status) = status else {
+                return Ok(CallToolResult::error(vec![Content::text(
+                    "Container status not available",
+                )]));
+            };
+
+            let elapsed = started.elapsed();
+            let current = crate::cri::container::container_state_name(status.state);
+            let result = serde_json::json!({
+                "container_id": status.id,
+                "state": current,
+                "exit_code": status.exit_code,
+                "reason": status.reason,
+                "message": status.message,
+                "created_at": status.created_at,
+                "started_at": status.started_at,
+                "finished_at": status.finished_at,
+                "waited_ms": elapsed.as_millis() as u64,
+            });
+
+            if status.state == target as i32 {
+                return Ok(CallToolResult::success(vec![Content::text(
+                    result.to_string(),
+                )]));
+            }
+            // An exited container never transitions again
+            if status.state == ContainerState::ContainerExited as i32 {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Container exited before reaching {}: {}",
+                    target.as_str_name(),
+                    result
+                ))]));
+            }
+            if elapsed >= timeout {
+                return Ok(CallToolResult::error(vec![Content::text(format!(
+                    "Timed out waiting for {}: {}",
+                    target.as_str_name(),
+                    result
+                ))]));
+            }
+
+            if let Some(progress_token) = &progress_token {
+                let _ = context
+                    .peer
+                    .notify_progress(ProgressNotificationParam {
+                        progress_token: progress_token.clone(),
+                        progress: elapsed.as_secs_f64(),
+                        total: Some(timeout.as_secs_f64()),
+                        message: Some(format!(
+                            "Container is {}, waiting for {}",
+                            current,
+                            target.as_str_name()
+                        )),
+                    })
+                    .await;
+            }
+
+            // Re-check on the next event for this container, or after the poll interval
+            let poll_until =
+                std::cmp::min(tokio::time::Instant::now() + WAIT_POLL_INTERVAL, deadline);
+            tokio::select! {
+                _ = wait_for_container_event(&mut events, &container_id) => {}
+                _ = tokio::time::sleep_until(poll_until) => {}
+                _ = context.ct.cancelled() => {
+                    return Ok(CallToolResult::error(vec![Content::text(
+                        "Wait for container state cancelled",
+                    )]));
+                }
             }
         }
+    }
 
-        Ok(CallToolResult::error(vec![Content::text(
-            "Runtime client not connected",
+    #[tool(
+        description = "List recent container lifecycle events (created, started, stopped, deleted) received from the runtime, oldest first"
+    )]
+    pub async fn recent_container_events(
+        &self,
+        Parameters(RecentContainerEventsParams {
+            limit,
+            container_id,
+        }): Parameters<RecentContainerEventsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let events = self
+            .event_history
+            .lock()
+            .await
+            .recent(limit.unwrap_or(50), container_id.as_deref());
+        Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string(&events).unwrap(),
         )]))
     }
 
-    #[tool(description = "Execute a command in a running container in sync mode")]
-    pub async fn exec_sync(
+    /// Per-call credentials take precedence over the server credential store
+    #[tool(
+        description = "Pull an image from a registry to make it available for container creation. Private registries use the credentials configured on the server unless username/password or a token is given"
+    )]
+    pub async fn pull_image(
         &self,
-        Parameters(ExecSyncParams {
-            container_id,
-            command,
-            timeout,
-        }): Parameters<ExecSyncParams>,
+        Parameters(PullImageParams {
+            image_reference,
+            username,
+            password,
+            identity_token,
+            registry_token,
+        }): Parameters<PullImageParams>,
     ) -> Result<CallToolResult, McpError> {
-        let lock = self.runtime_client.lock().await;
+        let credential = RegistryCredential {
+            username: username.unwrap_or_default(),
+            password: password.unwrap_or_default(),
+            identity_token: identity_token.unwrap_or_default(),
+            registry_token: registry_token.unwrap_or_default(),
+        };
+        let auth = if credential.is_empty() {
+            self.credentials.lookup(&image_reference)
+        } else {
+            Some(credential.to_auth_config(&crate::cri::auth::registry_host(&image_reference)))
+        };
+        let authenticated = auth.is_some();
+
+        let lock = self.image_client.lock().await;
         if let Some(client) = &*lock {
             let mut client_clone = client.clone();
-            match crate::cri::container::exec_sync(
-                &mut client_clone,
-                container_id,
-                command,
-                timeout,
-            )
-            .await
+            match crate::cri::image::pull_image(&mut client_clone, image_reference.clone(), auth)
+                .await
             {
-                Ok(response) => {
-                    let stdout = String::from_utf8_lossy(&response.stdout).to_string();
-                    let stderr = String::from_utf8_lossy(&response.stderr).to_string();
-                    let exit_code = response.exit_code;
-
+                Ok(image_ref) => {
                     let result = serde_json::json!({
-                        "stdout": stdout,
-                        "stderr": stderr,
-                        "exit_code": exit_code
+                        "success": true,
+                        "image_ref": image_ref,
+                        "authenticated": authenticated,
                     });
-
                     return Ok(CallToolResult::success(vec![Content::text(
                         result.to_string(),
                     )]));
                 }
                 Err(e) => {
                     return Ok(CallToolResult::error(vec![Content::text(format!(
-                        "Failed to execute command: {}",
+                        "Failed to pull image: {}",
                         e
                     ))]));
                 }
@@ -1002,31 +3605,39 @@ impl Server {
         }
 
         Ok(CallToolResult::error(vec![Content::text(
-            "Runtime client not connected",
+            "Image client not connected",
         )]))
     }
 
-    /// Now not support pull with auth
     #[tool(
-        description = "Pull an image from a registry to make it available for container creation"
+        description = "Inspect a local image: entrypoint, cmd, env, exposed ports, user, working dir, labels, platform, tags, digests and size. Use it to build a correct create_container call"
     )]
-    pub async fn pull_image(
+    pub async fn image_status(
         &self,
-        Parameters(PullImageParams { image_reference }): Parameters<PullImageParams>,
+        Parameters(ImageStatusParams { image_reference }): Parameters<ImageStatusParams>,
     ) -> Result<CallToolResult, McpError> {
         let lock = self.image_client.lock().await;
         if let Some(client) = &*lock {
             let mut client_clone = client.clone();
-            match crate::cri::image::pull_image(&mut client_clone, image_reference.clone()).await {
-                Ok(image_ref) => {
-                    return Ok(CallToolResult::success(vec![Content::text(format!(
-                        "{{\"success\": true, \"image_ref\": \"{}\"}}",
-                        image_ref
-                    ))]));
-                }
+            match crate::cri::image::image_status(&mut client_clone, image_reference.clone(), true)
+                .await
+            {
+                Ok(response) => match crate::cri::image::ImageDetails::from_status(&response) {
+                    Some(details) => {
+                        return Ok(CallToolResult::success(vec![Content::text(
+                            serde_json::to_string(&details).unwrap(),
+                        )]));
+                    }
+                    None => {
+                        return Ok(CallToolResult::error(vec![Content::text(format!(
+                            "Image {} not found, pull it first",
+                            image_reference
+                        ))]));
+                    }
+                },
                 Err(e) => {
                     return Ok(CallToolResult::error(vec![Content::text(format!(
-                        "Failed to pull image: {}",
+                        "Failed to get image status: {}",
                         e
                     ))]));
                 }
@@ -1067,6 +3678,78 @@ impl Server {
         )]))
     }
 
+    #[tool(
+        description = "Prune exited containers, not-ready pod sandboxes and dangling or unused images in dependency order: containers and pods first, then the images no remaining container uses. Runs as a dry run by default, returning the exact plan with the reclaimable bytes; set dry_run to false to execute it",
+        output_schema = cached_schema_for_type::<PrunePlan>()
+    )]
+    pub async fn prune(
+        &self,
+        Parameters(PruneParams {
+            dry_run,
+            containers,
+            pods,
+            images,
+            older_than_seconds,
+            label_selector,
+        }): Parameters<PruneParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let image_prune = match images.as_deref() {
+            None => ImagePrune::Dangling,
+            Some(mode) => match crate::cri::prune::parse_image_prune(mode) {
+                Some(image_prune) => image_prune,
+                None => {
+                    return Ok(CallToolResult::error(vec![Content::text(format!(
+                        "Invalid images mode '{}', expected none, dangling or unused",
+                        mode
+                    ))]));
+                }
+            },
+        };
+        let options = PruneOptions {
+            containers: containers.unwrap_or(true),
+            pods: pods.unwrap_or(true),
+            images: image_prune,
+            older_than: older_than_seconds.map(std::time::Duration::from_secs),
+            label_selector: label_selector.unwrap_or_default(),
+        };
+        let dry_run = dry_run.unwrap_or(true);
+        debug!("Prune (dry run: {}) with {:?}", dry_run, options);
+
+        let Some(mut runtime_client) = self.runtime_client.lock().await.clone() else {
+            return Ok(CallToolResult::error(vec![Content::text(
+                "Runtime client not connected",
+            )]));
+        };
+        let Some(mut image_client) = self.image_client.lock().await.clone() else {
+            return Ok(CallToolResult::error(vec![Content::text(
+                "Image client not connected",
+            )]));
+        };
+        match crate::cri::prune::prune(&mut runtime_client, &mut image_client, &options, dry_run)
+            .await
+        {
+            Ok(plan) => {
+                if !plan.dry_run {
+                    for pod in &plan.pods {
+                        self.pod_configs.remove(&pod.id).await;
+                        let failed = plan
+                            .errors
+                            .iter()
+                            .any(|e| e.kind == "pod" && e.id == pod.id);
+                        if !failed {
+                            remove_pod_volumes(&pod.uid).await;
+                        }
+                    }
+                }
+                Ok(structured(&plan))
+            }
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Failed to prune: {}",
+                e
+            ))])),
+        }
+    }
+
     #[tool(
         description = "Retrieve logs from a container with optional timestamp, tail lines, and follow options"
     )]
@@ -1110,7 +3793,10 @@ impl Server {
         )]))
     }
 
-    #[tool(description = "Get detailed resource usage statistics for a container")]
+    #[tool(
+        description = "Get detailed resource usage statistics for a container",
+        output_schema = cached_schema_for_type::<ContainerStatsResponse>()
+    )]
     pub async fn container_stats(
         &self,
         Parameters(ContainerStatsParams { container_id }): Parameters<ContainerStatsParams>,
@@ -1120,9 +3806,96 @@ impl Server {
             let mut client_clone = client.clone();
             match crate::cri::container::container_stats(&mut client_clone, container_id).await {
                 Ok(response) => {
-                    return Ok(CallToolResult::success(vec![Content::text(
-                        serde_json::to_string(&response).unwrap(),
-                    )]));
+                    return Ok(structured(&response));
+                }
+                Err(e) => {
+                    return Ok(CallToolResult::error(vec![Content::text(format!(
+                        "Failed to get container stats: {}",
+                        e
+                    ))]));
+                }
+            }
+        }
+
+        Ok(CallToolResult::error(vec![Content::text(
+            "Runtime client not connected",
+        )]))
+    }
+
+    #[tool(
+        description = "Get resource usage statistics for containers, optionally filtered by container, pod, labels, name or namespace",
+        output_schema = cached_schema_for_type::<ListPage<ContainerStats>>()
+    )]
+    pub async fn list_container_stats(
+        &self,
+        Parameters(ListContainerStatsParams {
+            container_id,
+            pod_id,
+            label_selector,
+            name,
+            namespace,
+            list,
+        }): Parameters<ListContainerStatsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let filter = ContainerStatsFilter {
+            id: container_id.unwrap_or_default(),
+            pod_sandbox_id: pod_id.unwrap_or_default(),
+            label_selector: label_selector.unwrap_or_default(),
+        };
+
+        let lock = self.runtime_client.lock().await;
+        if let Some(client) = &*lock {
+            let mut client_clone = client.clone();
+            let pod_ids = match &namespace {
+                Some(namespace) => match crate::cri::pod::matching_pod_ids(
+                    &mut client_clone,
+                    None,
+                    Some(namespace),
+                )
+                .await
+                {
+                    Ok(pod_ids) => Some(pod_ids),
+                    Err(e) => {
+                        return Ok(CallToolResult::error(vec![Content::text(format!(
+                            "Failed to list pods: {}",
+                            e
+                        ))]));
+                    }
+                },
+                None => None,
+            };
+            let container_pods = match &pod_ids {
+                Some(_) => {
+                    match crate::cri::container::list_containers(&mut client_clone, None).await {
+                        Ok(response) => response
+                            .containers
+                            .into_iter()
+                            .map(|c| (c.id, c.pod_sandbox_id))
+                            .collect(),
+                        Err(e) => {
+                            return Ok(CallToolResult::error(vec![Content::text(format!(
+                                "Failed to list containers: {}",
+                                e
+                            ))]));
+                        }
+                    }
+                }
+                None => HashMap::new(),
+            };
+            match crate::cri::container::list_container_stats(&mut client_clone, Some(filter)).await
+            {
+                Ok(mut response) => {
+                    response.stats.retain(|stats| {
+                        let attributes = stats.attributes.clone().unwrap_or_default();
+                        let metadata = attributes.metadata.unwrap_or_default();
+                        contains(&metadata.name, name.as_deref())
+                            && pod_ids.as_ref().is_none_or(|ids| {
+                                container_pods
+                                    .get(&attributes.id)
+                                    .is_some_and(|pod_id| ids.contains(pod_id))
+                            })
+                    });
+                    return Ok(structured(&render(response.stats, &list)));
                 }
                 Err(e) => {
                     return Ok(CallToolResult::error(vec![Content::text(format!(
@@ -1138,19 +3911,40 @@ impl Server {
         )]))
     }
 
-    #[tool(description = "Get aggregate resource usage statistics for all pods")]
+    #[tool(
+        description = "Get aggregate resource usage statistics for pods, optionally filtered by pod, labels, name or namespace",
+        output_schema = cached_schema_for_type::<ListPage<PodSandboxStats>>()
+    )]
     pub async fn pod_stats(
         &self,
-        Parameters(PodStatsParams { pod_id }): Parameters<PodStatsParams>,
+        Parameters(PodStatsParams {
+            pod_id,
+            label_selector,
+            name,
+            namespace,
+            list,
+        }): Parameters<PodStatsParams>,
     ) -> Result<CallToolResult, McpError> {
+        let filter = PodSandboxStatsFilter {
+            id: pod_id.unwrap_or_default(),
+            label_selector: label_selector.unwrap_or_default(),
+        };
+
         let lock = self.runtime_client.lock().await;
         if let Some(client) = &*lock {
             let mut client_clone = client.clone();
-            match crate::cri::pod::pod_stats(&mut client_clone, pod_id).await {
-                Ok(response) => {
-                    return Ok(CallToolResult::success(vec![Content::text(
-                        serde_json::to_string(&response).unwrap(),
-                    )]));
+            match crate::cri::pod::pod_stats(&mut client_clone, Some(filter)).await {
+                Ok(mut response) => {
+                    response.stats.retain(|stats| {
+                        let metadata = stats
+                            .attributes
+                            .clone()
+                            .and_then(|a| a.metadata)
+                            .unwrap_or_default();
+                        contains(&metadata.name, name.as_deref())
+                            && contains(&metadata.namespace, namespace.as_deref())
+                    });
+                    return Ok(structured(&render(response.stats, &list)));
                 }
                 Err(e) => {
                     return Ok(CallToolResult::error(vec![Content::text(format!(
@@ -1167,19 +3961,55 @@ impl Server {
     }
 }
 
+/// Resolve when an event for the given container arrives, or when events were missed
+async fn wait_for_container_event(
+    events: &mut broadcast::Receiver<ContainerEvent>,
+    container_id: &str,
+) {
+    loop {
+        match events.recv().await {
+            Ok(event) if event.container_id == container_id => return,
+            Ok(_) => continue,
+            Err(broadcast::error::RecvError::Lagged(_)) => return,
+            Err(broadcast::error::RecvError::Closed) => std::future::pending::<()>().await,
+        }
+    }
+}
+
 #[tool_handler]
 impl ServerHandler for Server {
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
             protocol_version: ProtocolVersion::V_2024_11_05,
             capabilities: ServerCapabilities::builder()
+                .enable_logging()
                 .enable_prompts()
                 .enable_resources()
                 .enable_tools()
                 .build(),
             server_info: Implementation::from_build_env(),
-            instructions: Some("This server provides tools to interact with Containerd through both CRI (Container Runtime Interface) and CTR (command line tool). CRI tools for K8s-style management: 'version', 'list_pods', 'list_containers', 'list_images', 'image_fs_info', 'create_pod', 'remove_pod', 'stop_pod', 'create_container', 'start_container', 'stop_container', 'remove_container', 'exec_sync', 'pull_image', 'remove_image', 'container_stats', 'pod_stats', 'container_logs'. CTR tools for direct containerd management (with _ctr suffix): 'run_ctr_command', 'list_containers_ctr', 'list_images_ctr', 'list_tasks_ctr', 'pull_image_ctr', 'remove_image_ctr', 'run_container_ctr', 'remove_container_ctr'. Use CRI tools for K8s-compatible container management and CTR tools for direct containerd operations.".to_string()),
+            instructions: Some("This server provides tools to interact with Containerd through both CRI (Container Runtime Interface) and CTR (command line tool). CRI tools for K8s-style management: 'version', 'runtime_status', 'runtime_config', 'update_runtime_config', 'list_pods', 'list_containers', 'list_images', 'image_status', 'image_fs_info', 'create_pod', 'remove_pod', 'stop_pod', 'create_container' (reuses the config of its pod, pod_config is only needed to override it), 'run_pod' (pulls images, runs the sandbox and starts every container, rolling back on failure), 'run_pod_manifest' (the same from a Kubernetes Pod manifest, reporting the fields it cannot translate; emptyDir volumes need the server on the containerd host), 'start_container', 'stop_container', 'remove_container', 'exec_sync', 'exec', 'exec_write', 'exec_read', 'exec_resize', 'exec_close', 'pull_image', 'remove_image', 'prune' (dry run by default, returns the plan and reclaimable bytes), 'container_stats', 'list_container_stats', 'pod_stats', 'container_logs', 'inspect_container', 'inspect_pod', 'update_container_resources', 'checkpoint_container', 'list_checkpoints', 'wait_for_container_state', 'recent_container_events'. List and stats tools accept optional filters (id, state, pod id, label selector, name/namespace substring) and should be filtered whenever possible. They return at most 'limit' items sorted by id (default 100) with a 'next_cursor' to pass as 'cursor' for the next page, 'compact' returns only id, name, state, pod, image and age, and 'fields' selects dotted paths of each item. Version, list, stats and exec tools return structured content described by their output schema. Pods and containers get a 'security' preset after the Kubernetes Pod Security Standards ('privileged', 'baseline' or 'restricted', default the minimum level of the server) and are rejected when they do not meet that minimum level, which also disables 'run_ctr_command' and 'run_container_ctr'. Pod and container options are typed partial CRI configs, their full schemas are published as the 'schema://pod-config' and 'schema://container-config' resources. Container lifecycle events are also pushed as logging notifications. CTR tools for direct containerd management through its native API, scoped by namespace (with _ctr suffix): 'run_ctr_command' (needs the ctr binary), 'version_ctr', 'list_containers_ctr', 'list_images_ctr', 'list_tasks_ctr', 'pause_task_ctr', 'resume_task_ctr', 'kill_task_ctr', 'list_task_pids_ctr', 'task_metrics_ctr', 'list_snapshots_ctr', 'snapshot_chain_ctr', 'orphan_snapshots_ctr', 'list_content_ctr', 'get_content_ctr', 'image_tree_ctr', 'list_leases_ctr', 'create_lease_ctr', 'delete_lease_ctr', 'lease_resources_ctr', 'gc_ctr' (set 'preview' to only report what would be reclaimed), 'pull_image_ctr', 'remove_image_ctr', 'run_container_ctr', 'remove_container_ctr'. The namespace of CTR tools must exist, CRI tools always work in 'k8s.io'. Namespace tools: 'list_namespaces', 'create_namespace', 'set_namespace_labels', 'delete_namespace' (refuses non-empty namespaces unless forced, never deletes 'k8s.io' or 'moby'). Use CRI tools for K8s-compatible container management and CTR tools for direct containerd operations.".to_string()),
+        }
+    }
+
+    async fn on_initialized(&self, context: NotificationContext<RoleServer>) {
+        debug!("client initialized, subscribing it to container events");
+        self.event_peers.lock().await.push(EventPeer {
+            peer: context.peer,
+            level: LoggingLevel::Info,
+        });
+    }
+
+    async fn set_level(
+        &self,
+        request: SetLevelRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<(), McpError> {
+        let mut peers = self.event_peers.lock().await;
+        if let Some(event_peer) = peers.iter_mut().find(|p| p.is(&context.peer)) {
+            event_peer.level = request.level;
         }
+        Ok(())
     }
 
     async fn list_prompts(
@@ -1191,12 +4021,84 @@ impl ServerHandler for Server {
             next_cursor: None,
             prompts: vec![Prompt::new(
                 "check_containerd_status",
-                Some("Check if containerd is running"),
-                Some(vec![PromptArgument {
-                    name: "message".to_string(),
-                    description: Some("A message to put in the prompt".to_string()),
-                    required: Some(true),
-                }]),
+                Some("Check if containerd is running and healthy"),
+                None,
+            )],
+        })
+    }
+
+    async fn list_resources(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _: RequestContext<RoleServer>,
+    ) -> Result<ListResourcesResult, McpError> {
+        let schema = |uri: &str, name: &str, description: &str| {
+            let mut resource = RawResource::new(uri, name);
+            resource.description = Some(description.to_string());
+            resource.mime_type = Some("application/schema+json".to_string());
+            resource.no_annotation()
+        };
+        Ok(ListResourcesResult {
+            next_cursor: None,
+            resources: vec![
+                schema(
+                    POD_CONFIG_SCHEMA_URI,
+                    "pod-config",
+                    "JSON schema of the PodSandboxConfig accepted as pod options by create_pod and run_pod",
+                ),
+                schema(
+                    CONTAINER_CONFIG_SCHEMA_URI,
+                    "container-config",
+                    "JSON schema of the ContainerConfig accepted as container options by create_container and run_pod",
+                ),
+            ],
+        })
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParam,
+        _: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, McpError> {
+        let Some(schema) = config_schema(&request.uri) else {
+            return Err(McpError::resource_not_found(
+                format!("Unknown resource: {}", request.uri),
+                None,
+            ));
+        };
+        Ok(ReadResourceResult {
+            contents: vec![ResourceContents::TextResourceContents {
+                uri: request.uri,
+                mime_type: Some("application/schema+json".to_string()),
+                text: serde_json::to_string_pretty(&schema).unwrap(),
+            }],
+        })
+    }
+
+    async fn get_prompt(
+        &self,
+        request: GetPromptRequestParam,
+        _: RequestContext<RoleServer>,
+    ) -> Result<GetPromptResult, McpError> {
+        if request.name != "check_containerd_status" {
+            return Err(McpError::invalid_params(
+                format!("Unknown prompt: {}", request.name),
+                None,
+            ));
+        }
+
+        let report = self.runtime_health_report().await;
+        Ok(GetPromptResult {
+            description: Some("Check if containerd is running and healthy".to_string()),
+            messages: vec![PromptMessage::new_text(
+                PromptMessageRole::User,
+                format!(
+                    "Here is the current state of the containerd runtime, collected from the CRI Version, Status and RuntimeConfig calls:\n\n{}\n\n\
+                     Tell me whether containerd is running and ready to run containers. \
+                     Point out every runtime condition that is not true with its reason and message, \
+                     and suggest how to fix it.",
+                    serde_json::to_string_pretty(&report).unwrap()
+                ),
             )],
         })
     }
@@ -0,0 +1,380 @@
+/*
+ * Shared pagination and projection for list tools
+ *
+ * List responses from the CRI carry full metadata, labels and annotations for every
+ * item, which quickly overflows a model's context on a busy node. Every list tool
+ * flattens `ListOptions` into its parameters and renders its items with `render`
+ * into a `ListPage`, which is also published as the tool's output schema:
+ *
+ * - items are sorted by id, `cursor` is the last id of the previous page
+ * - `limit` bounds the page size, `next_cursor` is set when more items remain
+ * - `compact` replaces each item with a table row (id, name, state, pod, image, age)
+ * - `fields` keeps only the given dotted paths of each item or row
+ */
+use crate::api::runtime::v1::{Container, ContainerStats, Image, PodSandbox, PodSandboxStats};
+use crate::cri::container::container_state_name;
+use crate::cri::pod::pod_state_name;
+use crate::ctr::container::ContainerSummary;
+use crate::ctr::content::BlobSummary;
+use crate::ctr::image::ImageSummary;
+use crate::ctr::lease::LeaseSummary;
+use crate::ctr::namespace::NamespaceSummary;
+use crate::ctr::snapshot::SnapshotSummary;
+use crate::ctr::task::TaskSummary;
+use rmcp::schemars;
+use serde_json::{Map, Value};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Page size used when a list tool is called without a limit
+pub const DEFAULT_PAGE_SIZE: usize = 100;
+
+#[derive(Debug, Default, serde::Deserialize, schemars::JsonSchema)]
+pub struct ListOptions {
+    #[schemars(description = "Maximum number of items to return (default 100)")]
+    pub limit: Option<usize>,
+    #[schemars(description = "Cursor returned as next_cursor by the previous page")]
+    pub cursor: Option<String>,
+    #[schemars(
+        description = "Only return these fields of each item, as dotted paths such as 'id' or 'metadata.name'"
+    )]
+    pub fields: Option<Vec<String>>,
+    #[schemars(description = "Return compact rows with only id, name, state, pod, image and age")]
+    pub compact: Option<bool>,
+}
+
+/// One page of a list tool result
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct ListPage<T> {
+    pub items: Vec<ListEntry<T>>,
+    /// Number of items matching the filters, across all pages
+    pub total: usize,
+    /// Cursor for the next page, absent on the last page
+    pub next_cursor: Option<String>,
+}
+
+/// A list item, in full, as a compact row, or reduced to the requested fields
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+#[serde(untagged)]
+pub enum ListEntry<T> {
+    Item(T),
+    Row(CompactRow),
+    Fields(Map<String, Value>),
+}
+
+/// One line of the compact table mode
+#[derive(Debug, Default, serde::Serialize, schemars::JsonSchema)]
+pub struct CompactRow {
+    pub id: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pod: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub age: Option<String>,
+}
+
+/// An item returned by a list tool
+pub trait ListItem: serde::Serialize {
+    fn id(&self) -> &str;
+    fn row(&self) -> CompactRow;
+}
+
+/// Render one page of `items` according to the list options
+pub fn render<T: ListItem>(mut items: Vec<T>, options: &ListOptions) -> ListPage<T> {
+    items.sort_by(|a, b| a.id().cmp(b.id()));
+    let total = items.len();
+
+    let start = match &options.cursor {
+        Some(cursor) => items.partition_point(|item| item.id() <= cursor.as_str()),
+        None => 0,
+    };
+    let limit = options.limit.unwrap_or(DEFAULT_PAGE_SIZE).max(1);
+    let end = total.min(start.saturating_add(limit));
+    let next_cursor = if end < total && end > start {
+        Some(items[end - 1].id().to_string())
+    } else {
+        None
+    };
+
+    let compact = options.compact.unwrap_or(false);
+    let fields = options.fields.as_ref().filter(|fields| !fields.is_empty());
+    let items = items
+        .into_iter()
+        .take(end)
+        .skip(start)
+        .map(|item| match fields {
+            Some(fields) => {
+                let value = if compact {
+                    serde_json::to_value(item.row())
+                } else {
+                    serde_json::to_value(&item)
+                }
+                .unwrap_or_default();
+                ListEntry::Fields(project(&value, fields))
+            }
+            None if compact => ListEntry::Row(item.row()),
+            None => ListEntry::Item(item),
+        })
+        .collect();
+
+    ListPage {
+        items,
+        total,
+        next_cursor,
+    }
+}
+
+/// Keep only the given dotted paths of a JSON object, preserving their nesting
+fn project(value: &Value, fields: &[String]) -> Map<String, Value> {
+    let mut output = Value::Object(Map::new());
+    for field in fields {
+        let pointer = format!("/{}", field.replace('.', "/"));
+        let Some(selected) = value.pointer(&pointer) else {
+            continue;
+        };
+
+        let mut target = &mut output;
+        let mut parts = field.split('.').peekable();
+        while let Some(part) = parts.next() {
+            let Value::Object(map) = target else { break };
+            if parts.peek().is_none() {
+                map.insert(part.to_string(), selected.clone());
+                break;
+            }
+            target = map
+                .entry(part.to_string())
+                .or_insert_with(|| Value::Object(Map::new()));
+        }
+    }
+    match output {
+        Value::Object(map) => map,
+        _ => Map::new(),
+    }
+}
+
+/// Human readable age of a timestamp in nanoseconds, such as "42s", "5m" or "3d"
+fn age(created_at: i64) -> Option<String> {
+    if created_at <= 0 {
+        return None;
+    }
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs() as i64;
+    let seconds = (now - created_at / 1_000_000_000).max(0);
+    Some(match seconds {
+        s if s < 60 => format!("{}s", s),
+        s if s < 3600 => format!("{}m", s / 60),
+        s if s < 86400 => format!("{}h", s / 3600),
+        s => format!("{}d", s / 86400),
+    })
+}
+
+impl ListItem for PodSandbox {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn row(&self) -> CompactRow {
+        let metadata = self.metadata.clone().unwrap_or_default();
+        CompactRow {
+            id: self.id.clone(),
+            name: format!("{}/{}", metadata.namespace, metadata.name),
+            state: Some(pod_state_name(self.state)),
+            age: age(self.created_at),
+            ..Default::default()
+        }
+    }
+}
+
+impl ListItem for Container {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn row(&self) -> CompactRow {
+        let image = self
+            .image
+            .as_ref()
+            .map(|image| image.image.clone())
+            .filter(|image| !image.is_empty())
+            .unwrap_or_else(|| self.image_ref.clone());
+        CompactRow {
+            id: self.id.clone(),
+            name: self
+                .metadata
+                .as_ref()
+                .map(|m| m.name.clone())
+                .unwrap_or_default(),
+            state: Some(container_state_name(self.state)),
+            pod: Some(self.pod_sandbox_id.clone()),
+            image: Some(image),
+            age: age(self.created_at),
+        }
+    }
+}
+
+impl ListItem for Image {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn row(&self) -> CompactRow {
+        CompactRow {
+            id: self.id.clone(),
+            name: self
+                .repo_tags
+                .first()
+                .or(self.repo_digests.first())
+                .cloned()
+                .unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+}
+
+impl ListItem for ContainerStats {
+    fn id(&self) -> &str {
+        self.attributes
+            .as_ref()
+            .map(|a| a.id.as_str())
+            .unwrap_or_default()
+    }
+
+    fn row(&self) -> CompactRow {
+        let attributes = self.attributes.clone().unwrap_or_default();
+        CompactRow {
+            id: attributes.id,
+            name: attributes.metadata.map(|m| m.name).unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+}
+
+impl ListItem for PodSandboxStats {
+    fn id(&self) -> &str {
+        self.attributes
+            .as_ref()
+            .map(|a| a.id.as_str())
+            .unwrap_or_default()
+    }
+
+    fn row(&self) -> CompactRow {
+        let attributes = self.attributes.clone().unwrap_or_default();
+        let metadata = attributes.metadata.unwrap_or_default();
+        CompactRow {
+            id: attributes.id,
+            name: format!("{}/{}", metadata.namespace, metadata.name),
+            ..Default::default()
+        }
+    }
+}
+
+impl ListItem for ContainerSummary {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn row(&self) -> CompactRow {
+        CompactRow {
+            id: self.id.clone(),
+            name: self.id.clone(),
+            image: Some(self.image.clone()),
+            age: self.created_at.and_then(|s| age(s * 1_000_000_000)),
+            ..Default::default()
+        }
+    }
+}
+
+impl ListItem for ImageSummary {
+    fn id(&self) -> &str {
+        &self.name
+    }
+
+    fn row(&self) -> CompactRow {
+        // names are the key of containerd images, several of them can share a digest
+        CompactRow {
+            id: self.name.clone(),
+            name: self.name.clone(),
+            image: Some(self.digest.clone()),
+            age: self.created_at.and_then(|s| age(s * 1_000_000_000)),
+            ..Default::default()
+        }
+    }
+}
+
+impl ListItem for TaskSummary {
+    fn id(&self) -> &str {
+        &self.container_id
+    }
+
+    fn row(&self) -> CompactRow {
+        CompactRow {
+            id: self.container_id.clone(),
+            name: self.container_id.clone(),
+            state: Some(self.status.clone()),
+            ..Default::default()
+        }
+    }
+}
+
+impl ListItem for NamespaceSummary {
+    fn id(&self) -> &str {
+        &self.name
+    }
+
+    fn row(&self) -> CompactRow {
+        CompactRow {
+            id: self.name.clone(),
+            name: self.name.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+impl ListItem for SnapshotSummary {
+    fn id(&self) -> &str {
+        &self.name
+    }
+
+    fn row(&self) -> CompactRow {
+        CompactRow {
+            id: self.name.clone(),
+            name: self.name.clone(),
+            state: Some(self.kind.clone()),
+            age: self.created_at.and_then(|s| age(s * 1_000_000_000)),
+            ..Default::default()
+        }
+    }
+}
+
+impl ListItem for BlobSummary {
+    fn id(&self) -> &str {
+        &self.digest
+    }
+
+    fn row(&self) -> CompactRow {
+        CompactRow {
+            id: self.digest.clone(),
+            name: format!("{} bytes", self.size),
+            age: self.created_at.and_then(|s| age(s * 1_000_000_000)),
+            ..Default::default()
+        }
+    }
+}
+
+impl ListItem for LeaseSummary {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn row(&self) -> CompactRow {
+        CompactRow {
+            id: self.id.clone(),
+            name: self.id.clone(),
+            state: Some(if self.expired { "expired" } else { "active" }.to_string()),
+            age: self.created_at.and_then(|s| age(s * 1_000_000_000)),
+            ..Default::default()
+        }
+    }
+}